@@ -367,3 +367,28 @@ self.addEventListener('activate', (event) => {
 });
 
 
+
+// Reminder notifications posted by the page (see `WebNotifier` in remind_me_shared::notifications).
+// Used where the page cannot construct `Notification` directly (e.g. Chrome on Android).
+self.addEventListener('message', (event) => {
+  const data = event.data || {};
+  if (data.type !== 'show-notification' || !data.title) return;
+
+  event.waitUntil(
+    self.registration.showNotification(data.title, data.options || {}).catch(() => {
+      // ignore (permission revoked or notifications unsupported)
+    })
+  );
+});
+
+// Focus (or open) the app when a reminder notification is clicked.
+self.addEventListener('notificationclick', (event) => {
+  event.notification.close();
+  event.waitUntil((async () => {
+    const clients_list = await self.clients.matchAll({ type: 'window', includeUncontrolled: true });
+    for (const client of clients_list) {
+      if ('focus' in client) return client.focus();
+    }
+    if (self.clients.openWindow) return self.clients.openWindow(`${BASE_PATH}/`);
+  })());
+});
@@ -1,6 +1,7 @@
 use dioxus::prelude::*;
 use remind_me_ui::{Button, ButtonVariant};
-use remind_me_shared::models::{Reminder, Tag, ReminderFilter, ReminderSort};
+use remind_me_shared::models::{PriorityFilter, Reminder, Tag, ReminderFilter, ReminderSort};
+use remind_me_shared::snooze::SnoozeOption;
 use super::super::ReminderCard;
 use remind_me_shared::utils::{
     get_current_date, get_days_in_month, get_first_day_of_week,
@@ -13,27 +14,29 @@ pub fn CalendarView(
     reminders: Vec<Reminder>,
     tags: Vec<Tag>,
     filter: ReminderFilter,
+    priority_filter: PriorityFilter,
     search_query: String,
     sort_by: ReminderSort,
     on_toggle: EventHandler<String>,
     on_edit: EventHandler<String>,
     on_delete: EventHandler<String>,
+    on_snooze: EventHandler<(String, Option<SnoozeOption>)>,
+    on_toggle_item: EventHandler<(String, String)>,
 ) -> Element {
     // Get filtered and sorted reminders
     let filtered_reminders = get_filtered_and_sorted_reminders(
         &reminders,
+        &tags,
         &filter,
+        &priority_filter,
         &search_query,
         &sort_by,
     );
 
-    // Group reminders by date
-    let reminders_by_date = group_reminders_by_date(&filtered_reminders);
-
     // Get reminders without dates (unscheduled)
     let unscheduled: Vec<Reminder> = filtered_reminders
         .iter()
-        .filter(|r| r.due_date.is_empty())
+        .filter(|r| r.due_date.is_none())
         .cloned()
         .collect();
 
@@ -43,6 +46,9 @@ pub fn CalendarView(
     let mut view_month = use_signal(|| current_month);
     let mut selected_date = use_signal(|| None::<String>); // YYYY-MM-DD format
 
+    // Group reminders by date, expanding recurring reminders across the visible month
+    let reminders_by_date = group_reminders_by_date(&filtered_reminders, view_year(), view_month());
+
     // Get reminders for selected date
     let selected_date_reminders = if let Some(date_key) = selected_date() {
         reminders_by_date.get(&date_key).cloned().unwrap_or_default()
@@ -211,6 +217,8 @@ pub fn CalendarView(
                             on_toggle: move |id: String| on_toggle.call(id),
                             on_edit: move |id: String| on_edit.call(id),
                             on_delete: move |id: String| on_delete.call(id),
+                            on_snooze: move |args: (String, Option<SnoozeOption>)| on_snooze.call(args),
+                            on_toggle_item: move |args: (String, String)| on_toggle_item.call(args),
                         }
                     }
                 }
@@ -235,6 +243,8 @@ pub fn CalendarView(
                             on_toggle: move |id: String| on_toggle.call(id),
                             on_edit: move |id: String| on_edit.call(id),
                             on_delete: move |id: String| on_delete.call(id),
+                            on_snooze: move |args: (String, Option<SnoozeOption>)| on_snooze.call(args),
+                            on_toggle_item: move |args: (String, String)| on_toggle_item.call(args),
                         }
                     }
                 }
@@ -1,5 +1,6 @@
 use dioxus::prelude::*;
 use remind_me_shared::models::{Reminder, Tag};
+use remind_me_shared::snooze::SnoozeOption;
 use super::super::ReminderCard;
 
 #[component]
@@ -9,6 +10,8 @@ pub fn CardView(
     on_toggle: EventHandler<String>,
     on_edit: EventHandler<String>,
     on_delete: EventHandler<String>,
+    on_snooze: EventHandler<(String, Option<SnoozeOption>)>,
+    on_toggle_item: EventHandler<(String, String)>,
 ) -> Element {
     rsx! {
         section {
@@ -27,6 +30,8 @@ pub fn CardView(
                             on_toggle: move |id: String| on_toggle.call(id),
                             on_edit: move |id: String| on_edit.call(id),
                             on_delete: move |id: String| on_delete.call(id),
+                            on_snooze: move |args: (String, Option<SnoozeOption>)| on_snooze.call(args),
+                            on_toggle_item: move |args: (String, String)| on_toggle_item.call(args),
                         }
                     }
                 }
@@ -1,5 +1,6 @@
 use dioxus::prelude::*;
 use remind_me_shared::models::{Reminder, Tag};
+use remind_me_shared::snooze::SnoozeOption;
 use super::super::ReminderCard;
 use crate::i18n::use_t;
 
@@ -10,6 +11,8 @@ pub fn FolderView(
     on_toggle: EventHandler<String>,
     on_edit: EventHandler<String>,
     on_delete: EventHandler<String>,
+    on_snooze: EventHandler<(String, Option<SnoozeOption>)>,
+    on_toggle_item: EventHandler<(String, String)>,
 ) -> Element {
     // Group reminders by tag
     let tag_groups: Vec<(Tag, Vec<Reminder>)> = tags
@@ -70,6 +73,8 @@ pub fn FolderView(
                                         on_toggle: move |id: String| on_toggle.call(id),
                                         on_edit: move |id: String| on_edit.call(id),
                                         on_delete: move |id: String| on_delete.call(id),
+                                        on_snooze: move |args: (String, Option<SnoozeOption>)| on_snooze.call(args),
+                                        on_toggle_item: move |args: (String, String)| on_toggle_item.call(args),
                                     }
                                 }
                             }
@@ -106,6 +111,8 @@ pub fn FolderView(
                                         on_toggle: move |id: String| on_toggle.call(id),
                                         on_edit: move |id: String| on_edit.call(id),
                                         on_delete: move |id: String| on_delete.call(id),
+                                        on_snooze: move |args: (String, Option<SnoozeOption>)| on_snooze.call(args),
+                                        on_toggle_item: move |args: (String, String)| on_toggle_item.call(args),
                                     }
                                 }
                             }
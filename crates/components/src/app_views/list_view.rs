@@ -1,6 +1,8 @@
 use dioxus::prelude::*;
 use remind_me_ui::EmptyState;
 use remind_me_shared::models::{Reminder, Tag, ReminderFilter};
+use remind_me_shared::query::Query;
+use remind_me_shared::snooze::SnoozeOption;
 use super::super::ReminderCard;
 use crate::i18n::use_t;
 
@@ -13,8 +15,12 @@ pub fn ListView(
     on_toggle: EventHandler<String>,
     on_edit: EventHandler<String>,
     on_delete: EventHandler<String>,
+    on_snooze: EventHandler<(String, Option<SnoozeOption>)>,
+    on_toggle_item: EventHandler<(String, String)>,
     on_new_reminder: EventHandler<()>,
 ) -> Element {
+    let query = Query::parse_lenient(&search_query);
+
     rsx! {
         section {
             class: "reminders-list",
@@ -64,9 +70,12 @@ pub fn ListView(
                         ReminderCard {
                             reminder: reminder.clone(),
                             tags: tags.clone(),
+                            highlight: query.search_match(reminder, &tags),
                             on_toggle: move |id: String| on_toggle.call(id),
                             on_edit: move |id: String| on_edit.call(id),
                             on_delete: move |id: String| on_delete.call(id),
+                            on_snooze: move |args: (String, Option<SnoozeOption>)| on_snooze.call(args),
+                            on_toggle_item: move |args: (String, String)| on_toggle_item.call(args),
                         }
                     }
                 }
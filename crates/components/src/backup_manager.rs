@@ -0,0 +1,428 @@
+//! Backup and restore of all reminders, tags and settings, and files for other apps.
+//!
+//! Exporting saves a `Backup` as a file (a download on the web). Importing
+//! reads a backup file, previews what the chosen `ImportMode` would change,
+//! conflicts included, and only saves once the user confirms.
+//!
+//! Files for other apps (see `TransferFormat`) carry reminders only: the
+//! ones currently shown are exported, and imported ones are added (or
+//! replace those with the same id) after a summary of what the file
+//! contains, including the rows that couldn't be read.
+
+use dioxus::prelude::*;
+use remind_me_ui::{Button, ButtonVariant, Modal, ModalSize, Select, SelectOption};
+use remind_me_shared::models::Reminder;
+use remind_me_shared::storage::{Backup, BackupItemKind, ImportMode, ImportPreview, Resolution, TagRepository};
+use remind_me_shared::transfer::{ReminderImport, TransferFormat};
+use crate::export::export_file;
+use crate::i18n::{use_current_locale, use_set_locale, use_t, Locale};
+use crate::storage::{use_reminder_repository, use_repository};
+
+#[component]
+pub fn BackupManager(
+    open: Signal<bool>,
+    on_close: EventHandler<()>,
+    /// Reminders currently shown (after filtering), for export to other apps
+    shown_reminders: Vec<Reminder>,
+    /// Called after a backup or other file was imported, so stored data can be reloaded
+    on_imported: EventHandler<()>,
+) -> Element {
+    let repository = use_repository();
+    let locale = use_current_locale();
+    let mut set_locale = use_set_locale();
+    let mut backup = use_signal(|| None::<Backup>);
+    let mut mode = use_signal(|| ImportMode::MergeById);
+    let mut preview = use_signal(|| None::<ImportPreview>);
+    // Outcome of the last action, and whether it failed
+    let mut status = use_signal(|| None::<(String, bool)>);
+
+    // Start over whenever the modal opens
+    use_effect(move || {
+        if open() {
+            backup.set(None);
+            preview.set(None);
+            status.set(None);
+        }
+    });
+
+    // Work out what importing the loaded backup with `mode` would do
+    let mut refresh_preview = move || {
+        let Some(loaded) = backup() else {
+            preview.set(None);
+            return;
+        };
+        match loaded.preview(&repository.read(), mode()) {
+            Ok(result) => preview.set(Some(result)),
+            Err(error) => {
+                preview.set(None);
+                status.set(Some((format!("{} {}", use_t("backup.import_failed"), error), true)));
+            }
+        }
+    };
+
+    let export_backup = move |_| {
+        let result = Backup::create(&repository.read(), Some(&locale))
+            .and_then(|created| Ok((created.file_name(), created.to_json()?)))
+            .map_err(|e| e.to_string())
+            .and_then(|(file_name, json)| export_file(&file_name, "application/json", &json));
+        status.set(Some(match result {
+            Ok(Some(path)) => (format!("{} {}", use_t("backup.exported_to"), path), false),
+            Ok(None) => (use_t("backup.exported"), false),
+            Err(e) => (format!("{} {}", use_t("backup.export_failed"), e), true),
+        }));
+    };
+
+    let read_backup = move |evt: FormEvent| async move {
+        let Some(file) = evt.files().into_iter().next() else {
+            return;
+        };
+        backup.set(None);
+        status.set(None);
+        let parsed = match file.read_string().await {
+            Ok(json) => Backup::parse(&json).map_err(|e| e.to_string()),
+            Err(e) => Err(e.to_string()),
+        };
+        match parsed {
+            Ok(loaded) => {
+                backup.set(Some(loaded));
+                refresh_preview();
+            }
+            Err(e) => {
+                preview.set(None);
+                status.set(Some((format!("{} {}", use_t("backup.import_failed"), e), true)));
+            }
+        }
+    };
+
+    let apply_import = move |_| {
+        let Some(result) = preview() else {
+            return;
+        };
+        if let Err(error) = result.apply(&repository.read()) {
+            status.set(Some((format!("{} {}", use_t("backup.import_failed"), error), true)));
+            return;
+        }
+        if let Some(locale) = &result.locale {
+            set_locale(Locale::from_str(locale));
+        }
+        backup.set(None);
+        preview.set(None);
+        status.set(Some((use_t("backup.imported"), false)));
+        on_imported.call(());
+    };
+
+    let mode_options: Vec<SelectOption> = ImportMode::ALL
+        .iter()
+        .map(|m| SelectOption {
+            value: m.as_str().to_string(),
+            label: use_t(&format!("backup.mode.{}", m.as_str())),
+        })
+        .collect();
+
+    rsx! {
+        Modal {
+            open: open,
+            size: ModalSize::Large,
+            title: use_t("backup.title"),
+            close_on_backdrop: true,
+            on_close: move |_| on_close.call(()),
+
+            if let Some((message, failed)) = status() {
+                p {
+                    class: if failed { "text-sm text-red-600 mb-2" } else { "text-sm text-green-700 mb-2" },
+                    role: if failed { "alert" } else { "status" },
+                    {message}
+                }
+            }
+
+            div {
+                class: "backup-manager-content",
+                section {
+                    class: "backup-export mb-4",
+                    h3 { class: "font-semibold mb-1", {use_t("backup.export_title")} }
+                    p { class: "text-sm mb-2", {use_t("backup.export_description")} }
+                    Button {
+                        variant: ButtonVariant::Primary,
+                        onclick: export_backup,
+                        {use_t("backup.export")}
+                    }
+                }
+
+                section {
+                    class: "backup-import",
+                    h3 { class: "font-semibold mb-1", {use_t("backup.import_title")} }
+                    label {
+                        class: "text-sm",
+                        r#for: "backup_file",
+                        {use_t("backup.choose_file")}
+                    }
+                    input {
+                        id: "backup_file",
+                        name: "backup_file",
+                        r#type: "file",
+                        accept: ".json,application/json",
+                        class: "block mb-2",
+                        onchange: read_backup,
+                    }
+
+                    if let Some(loaded) = backup() {
+                        p {
+                            class: "text-sm mb-2",
+                            {format!("{} {}", use_t("backup.created_at"), loaded.exported_at.format_display())}
+                        }
+                        label {
+                            class: "text-sm",
+                            r#for: "import_mode",
+                            {use_t("backup.mode.label")}
+                        }
+                        Select {
+                            id: "import_mode".to_string(),
+                            name: "import_mode".to_string(),
+                            value: mode().as_str().to_string(),
+                            onchange: move |value: String| {
+                                if let Some(selected) = ImportMode::parse(&value) {
+                                    mode.set(selected);
+                                    refresh_preview();
+                                }
+                            },
+                            options: mode_options,
+                        }
+                    }
+
+                    if let Some(result) = preview() {
+                        ImportPreviewSummary { preview: result }
+                        div {
+                            class: "flex gap-2 mt-2",
+                            Button {
+                                variant: if mode() == ImportMode::Replace { ButtonVariant::Danger } else { ButtonVariant::Primary },
+                                onclick: apply_import,
+                                {use_t("backup.apply")}
+                            }
+                            Button {
+                                variant: ButtonVariant::Ghost,
+                                onclick: move |_| {
+                                    backup.set(None);
+                                    preview.set(None);
+                                },
+                                {use_t("app.header.cancel")}
+                            }
+                        }
+                    }
+                }
+
+                for format in TransferFormat::ALL {
+                    TransferSection {
+                        key: "{format.as_str()}",
+                        format,
+                        shown_reminders: shown_reminders.clone(),
+                        status,
+                        on_imported,
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Counts of what an import changes, and the conflicts it resolves
+#[component]
+fn ImportPreviewSummary(preview: ImportPreview) -> Element {
+    let kind_label = |kind: BackupItemKind| {
+        use_t(match kind {
+            BackupItemKind::Reminder => "backup.kind.reminder",
+            BackupItemKind::Tag => "backup.kind.tag",
+            BackupItemKind::SavedView => "backup.kind.saved_view",
+        })
+    };
+
+    rsx! {
+        div {
+            class: "import-preview text-sm mt-2",
+            ul {
+                li { {format!("{} {}", use_t("backup.preview.added"), preview.added)} }
+                if preview.mode == ImportMode::Replace {
+                    li { {format!("{} {}", use_t("backup.preview.removed"), preview.removed)} }
+                }
+                li { {format!("{} {}", use_t("backup.preview.conflicts"), preview.conflicts.len())} }
+            }
+            if !preview.conflicts.is_empty() {
+                ul {
+                    class: "import-conflicts mt-2",
+                    for conflict in preview.conflicts.iter() {
+                        li {
+                            key: "{conflict.id}",
+                            {
+                                let kept = match conflict.resolution {
+                                    Resolution::UseBackup => use_t("backup.preview.use_backup"),
+                                    Resolution::KeepLocal => use_t("backup.preview.keep_local"),
+                                };
+                                let name = if conflict.local_name == conflict.backup_name {
+                                    conflict.local_name.clone()
+                                } else {
+                                    format!("{} → {}", conflict.local_name, conflict.backup_name)
+                                };
+                                format!("{} “{}”: {}", kind_label(conflict.kind), name, kept)
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Export of the shown reminders to one file format, and import from it
+#[component]
+fn TransferSection(
+    format: TransferFormat,
+    shown_reminders: Vec<Reminder>,
+    /// Outcome of the last action, and whether it failed
+    status: Signal<Option<(String, bool)>>,
+    on_imported: EventHandler<()>,
+) -> Element {
+    let repository = use_repository();
+    let reminder_repository = use_reminder_repository();
+    let mut loaded = use_signal(|| None::<ReminderImport>);
+    let key = format.as_str();
+    let file_input_id = format!("{}_file", key);
+    let shown_count = shown_reminders.len();
+
+    let export = move |_| {
+        let tags = repository.read().load_tags().items;
+        let result = export_file(&format.file_name(), format.mime_type(), &format.export(&shown_reminders, &tags));
+        status.set(Some(match result {
+            Ok(Some(path)) => (format!("{} {}", use_t("transfer.exported_to"), path), false),
+            Ok(None) => (use_t("transfer.exported"), false),
+            Err(e) => (format!("{} {}", use_t("transfer.export_failed"), e), true),
+        }));
+    };
+
+    let read_file = move |evt: FormEvent| async move {
+        let Some(file) = evt.files().into_iter().next() else {
+            return;
+        };
+        loaded.set(None);
+        status.set(None);
+        let parsed = match file.read_string().await {
+            Ok(text) => format.import(&text, &repository.read().load_tags().items),
+            Err(e) => Err(e.to_string()),
+        };
+        match parsed {
+            Ok(result) => loaded.set(Some(result)),
+            Err(e) => status.set(Some((format!("{} {}", use_t("transfer.import_failed"), e), true))),
+        }
+    };
+
+    // Save the file's new tags, then its reminders
+    let apply = move |_| {
+        let Some(result) = loaded() else {
+            return;
+        };
+        let tag_repository = TagRepository::new(repository.cloned());
+        let saved = result
+            .new_tags
+            .into_iter()
+            .try_for_each(|tag| tag_repository.save(tag))
+            .and_then(|()| reminder_repository.read().import(result.reminders));
+        match saved {
+            Ok((added, replaced)) => {
+                loaded.set(None);
+                status.set(Some((
+                    format!(
+                        "{} {} {} {}",
+                        use_t("transfer.imported_added"),
+                        added,
+                        use_t("transfer.imported_replaced"),
+                        replaced
+                    ),
+                    false,
+                )));
+                on_imported.call(());
+            }
+            Err(error) => {
+                status.set(Some((format!("{} {}", use_t("transfer.import_failed"), error), true)));
+            }
+        }
+    };
+
+    rsx! {
+        section {
+            class: "transfer-format mt-4",
+            h3 { class: "font-semibold mb-1", {use_t(&format!("transfer.{}.title", key))} }
+            p { class: "text-sm mb-2", {use_t(&format!("transfer.{}.description", key))} }
+            Button {
+                variant: ButtonVariant::Secondary,
+                disabled: shown_count == 0,
+                onclick: export,
+                {format!("{} ({})", use_t("transfer.export_shown"), shown_count)}
+            }
+            label {
+                class: "text-sm block mt-2",
+                r#for: file_input_id.clone(),
+                {use_t("transfer.choose_file")}
+            }
+            input {
+                id: file_input_id.clone(),
+                name: file_input_id,
+                r#type: "file",
+                accept: format!(".{},{}", format.extension(), format.mime_type()),
+                class: "block mb-2",
+                onchange: read_file,
+            }
+
+            if let Some(result) = loaded() {
+                ul {
+                    class: "import-preview text-sm mt-2",
+                    li { {format!("{} {}", use_t("transfer.found"), result.reminders.len())} }
+                    li {
+                        {
+                            let existing = result
+                                .reminders
+                                .iter()
+                                .filter(|r| reminder_repository.read().get_by_id(&r.id).is_some())
+                                .count();
+                            format!("{} {}", use_t("transfer.existing"), existing)
+                        }
+                    }
+                    if !result.new_tags.is_empty() {
+                        li {
+                            {
+                                let names: Vec<&str> = result.new_tags.iter().map(|t| t.name.as_str()).collect();
+                                format!("{} {}", use_t("transfer.new_tags"), names.join(", "))
+                            }
+                        }
+                    }
+                }
+                if !result.errors.is_empty() {
+                    p {
+                        class: "text-sm text-red-600 mt-2",
+                        {format!("{} {}", use_t("transfer.errors"), result.errors.len())}
+                    }
+                    ul {
+                        class: "import-errors text-sm",
+                        for error in result.errors.iter() {
+                            li {
+                                key: "{error.row}",
+                                {format!("{} {}: {}", use_t("transfer.row"), error.row, error.reason)}
+                            }
+                        }
+                    }
+                }
+                div {
+                    class: "flex gap-2 mt-2",
+                    Button {
+                        variant: ButtonVariant::Primary,
+                        disabled: result.reminders.is_empty(),
+                        onclick: apply,
+                        {use_t("transfer.import")}
+                    }
+                    Button {
+                        variant: ButtonVariant::Ghost,
+                        onclick: move |_| loaded.set(None),
+                        {use_t("app.header.cancel")}
+                    }
+                }
+            }
+        }
+    }
+}
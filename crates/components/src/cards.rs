@@ -3,10 +3,12 @@ use remind_me_ui::{
     Badge, BadgeVariant,
     Button, ButtonVariant, ButtonSize,
     Card, CardContent,
-    Checkbox,
+    Checkbox, Input,
 };
-use remind_me_shared::models::{Reminder, Tag};
-use remind_me_shared::utils::{format_date, is_overdue};
+use remind_me_shared::models::{Priority, Reminder, Tag};
+use remind_me_shared::datetime::LocalDateTime;
+use remind_me_shared::snooze::{is_snoozed, SnoozeOption};
+use remind_me_shared::utils::{format_due_date, is_overdue, SearchMatch};
 use crate::i18n::use_t;
 
 #[component]
@@ -16,10 +18,20 @@ pub fn ReminderCard(
     on_toggle: EventHandler<String>,
     on_edit: EventHandler<String>,
     on_delete: EventHandler<String>,
+    on_snooze: EventHandler<(String, Option<SnoozeOption>)>,
+    on_toggle_item: EventHandler<(String, String)>,
+    /// Search match to highlight in the title, description and tags
+    #[props(default)]
+    highlight: Option<SearchMatch>,
 ) -> Element {
-    let is_overdue = !reminder.completed
-        && !reminder.due_date.is_empty()
-        && is_overdue(&reminder.due_date);
+    let highlight = highlight.unwrap_or_default();
+    let title_segments = highlight_segments(&reminder.title, &highlight.title);
+    let description_segments = highlight_segments(&reminder.description, &highlight.description);
+    let is_overdue = is_overdue(&reminder);
+    let is_snoozed = !reminder.completed && is_snoozed(&reminder);
+    let can_snooze = !reminder.completed && reminder.due_date.is_some();
+    let mut show_snooze_menu = use_signal(|| false);
+    let mut custom_snooze_until = use_signal(String::new);
 
     let card_class = if reminder.completed {
         "reminder-card completed"
@@ -32,6 +44,8 @@ pub fn ReminderCard(
     let reminder_id_toggle = reminder.id.clone();
     let reminder_id_edit = reminder.id.clone();
     let reminder_id_delete = reminder.id.clone();
+    let reminder_id_snooze = reminder.id.clone();
+    let (checklist_done, checklist_total) = reminder.checklist_progress();
 
     rsx! {
         Card {
@@ -50,17 +64,64 @@ pub fn ReminderCard(
                         }
                         div {
                             class: "flex-1",
-                            h3 {
-                                class: if reminder.completed { "line-through text-gray-500" } else { "font-semibold" },
-                                "{reminder.title}"
+                            div {
+                                class: "flex items-center gap-2",
+                                h3 {
+                                    class: if reminder.completed { "line-through text-gray-500" } else { "font-semibold" },
+                                    for (text, matched) in title_segments {
+                                        if matched {
+                                            mark { class: "search-highlight", "{text}" }
+                                        } else {
+                                            "{text}"
+                                        }
+                                    }
+                                }
+                                if checklist_total > 0 {
+                                    Badge {
+                                        variant: if checklist_done == checklist_total { BadgeVariant::Success } else { BadgeVariant::Default },
+                                        "☑ {checklist_done}/{checklist_total}"
+                                    }
+                                }
+                                if reminder.priority != Priority::None {
+                                    Badge {
+                                        variant: priority_badge_variant(reminder.priority),
+                                        {use_t(&format!("priority.{}", reminder.priority.as_str()))}
+                                    }
+                                }
                             }
                             if !reminder.description.is_empty() {
                                 p {
                                     class: "text-sm text-gray-600 mt-1",
-                                    "{reminder.description}"
+                                    for (text, matched) in description_segments {
+                                        if matched {
+                                            mark { class: "search-highlight", "{text}" }
+                                        } else {
+                                            "{text}"
+                                        }
+                                    }
+                                }
+                            }
+                            if checklist_total > 0 {
+                                ul {
+                                    class: "checklist mt-2 space-y-1",
+                                    aria_label: use_t("checklist.label"),
+                                    for item in reminder.checklist.clone() {
+                                        li {
+                                            key: "{item.id}",
+                                            class: if item.completed { "checklist-item text-sm line-through text-gray-500" } else { "checklist-item text-sm" },
+                                            Checkbox {
+                                                checked: item.completed,
+                                                label: item.text.clone(),
+                                                onchange: {
+                                                    let reminder_id = reminder.id.clone();
+                                                    move |_| on_toggle_item.call((reminder_id.clone(), item.id.clone()))
+                                                },
+                                            }
+                                        }
+                                    }
                                 }
                             }
-                            if !reminder.due_date.is_empty() {
+                            if let Some(due_date) = format_due_date(&reminder) {
                                 div {
                                     class: "mt-2 flex items-center gap-2",
                                     if is_overdue {
@@ -72,7 +133,103 @@ pub fn ReminderCard(
                                     span {
                                         class: "text-sm text-gray-500",
                                         {
-                                            format!("{} {}", use_t("reminder.due"), format_date(&reminder.due_date))
+                                            format!("{} {}", use_t("reminder.due"), due_date)
+                                        }
+                                    }
+                                    if is_snoozed {
+                                        Badge {
+                                            variant: BadgeVariant::Warning,
+                                            {
+                                                format!(
+                                                    "💤 {} {}",
+                                                    use_t("snooze.snoozed_until"),
+                                                    reminder.snoozed_until.map(|t| t.format_display()).unwrap_or_default()
+                                                )
+                                            }
+                                        }
+                                    }
+                                    if let Some(rule) = reminder.recurrence.as_ref() {
+                                        Badge {
+                                            variant: BadgeVariant::Info,
+                                            {
+                                                let label = use_t(&format!("recurrence.{}", rule.frequency.as_str()));
+                                                if rule.interval > 1 {
+                                                    format!("🔁 {} ×{}", label, rule.interval)
+                                                } else {
+                                                    format!("🔁 {}", label)
+                                                }
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                            if show_snooze_menu() {
+                                {
+                                    let id_5m = reminder_id_snooze.clone();
+                                    let id_1h = reminder_id_snooze.clone();
+                                    let id_tomorrow = reminder_id_snooze.clone();
+                                    let id_custom = reminder_id_snooze.clone();
+                                    let id_cancel = reminder_id_snooze.clone();
+                                    rsx! {
+                                        div {
+                                            class: "snooze-menu mt-2 flex flex-wrap items-center gap-2",
+                                            Button {
+                                                variant: ButtonVariant::Ghost,
+                                                size: ButtonSize::Small,
+                                                onclick: move |_| {
+                                                    on_snooze.call((id_5m.clone(), Some(SnoozeOption::FiveMinutes)));
+                                                    show_snooze_menu.set(false);
+                                                },
+                                                {use_t("snooze.five_minutes")}
+                                            }
+                                            Button {
+                                                variant: ButtonVariant::Ghost,
+                                                size: ButtonSize::Small,
+                                                onclick: move |_| {
+                                                    on_snooze.call((id_1h.clone(), Some(SnoozeOption::OneHour)));
+                                                    show_snooze_menu.set(false);
+                                                },
+                                                {use_t("snooze.one_hour")}
+                                            }
+                                            Button {
+                                                variant: ButtonVariant::Ghost,
+                                                size: ButtonSize::Small,
+                                                onclick: move |_| {
+                                                    on_snooze.call((id_tomorrow.clone(), Some(SnoozeOption::TomorrowMorning)));
+                                                    show_snooze_menu.set(false);
+                                                },
+                                                {use_t("snooze.tomorrow_morning")}
+                                            }
+                                            Input {
+                                                r#type: "datetime-local",
+                                                aria_label: use_t("snooze.custom"),
+                                                value: "{custom_snooze_until()}",
+                                                oninput: move |value| custom_snooze_until.set(value),
+                                            }
+                                            Button {
+                                                variant: ButtonVariant::Primary,
+                                                size: ButtonSize::Small,
+                                                disabled: custom_snooze_until().parse::<LocalDateTime>().is_err(),
+                                                onclick: move |_| {
+                                                    if let Ok(until) = custom_snooze_until().parse() {
+                                                        on_snooze.call((id_custom.clone(), Some(SnoozeOption::Until(until))));
+                                                    }
+                                                    custom_snooze_until.set(String::new());
+                                                    show_snooze_menu.set(false);
+                                                },
+                                                {use_t("snooze.custom")}
+                                            }
+                                            if is_snoozed {
+                                                Button {
+                                                    variant: ButtonVariant::Ghost,
+                                                    size: ButtonSize::Small,
+                                                    onclick: move |_| {
+                                                        on_snooze.call((id_cancel.clone(), None));
+                                                        show_snooze_menu.set(false);
+                                                    },
+                                                    {use_t("snooze.cancel")}
+                                                }
+                                            }
                                         }
                                     }
                                 }
@@ -83,7 +240,7 @@ pub fn ReminderCard(
                                     for tag_id in reminder.tag_ids.iter() {
                                         if let Some(tag) = tags.iter().find(|t| t.id == *tag_id) {
                                             span {
-                                                class: "tag-chip",
+                                                class: if highlight.tag_ids.contains(&tag.id) { "tag-chip search-highlight" } else { "tag-chip" },
                                                 style: format!("background-color: {};", tag.color),
                                                 {tag.name.clone()}
                                             }
@@ -95,6 +252,15 @@ pub fn ReminderCard(
                     }
                     div {
                         class: "flex gap-2",
+                        if can_snooze {
+                            Button {
+                                variant: ButtonVariant::Ghost,
+                                size: ButtonSize::Small,
+                                aria_label: Some(format!("{} {}", use_t("snooze.snooze"), reminder.title.clone())),
+                                onclick: move |_| show_snooze_menu.set(!show_snooze_menu()),
+                                "💤"
+                            }
+                        }
                         Button {
                             variant: ButtonVariant::Ghost,
                             size: ButtonSize::Small,
@@ -119,3 +285,41 @@ pub fn ReminderCard(
         }
     }
 }
+
+/// Badge colour for a priority level
+fn priority_badge_variant(priority: Priority) -> BadgeVariant {
+    match priority {
+        Priority::None | Priority::Low => BadgeVariant::Default,
+        Priority::Medium => BadgeVariant::Info,
+        Priority::High => BadgeVariant::Warning,
+        Priority::Urgent => BadgeVariant::Danger,
+    }
+}
+
+/// Split `text` into `(segment, highlighted)` pairs at the given byte ranges
+fn highlight_segments(text: &str, ranges: &[std::ops::Range<usize>]) -> Vec<(String, bool)> {
+    let mut ranges: Vec<_> = ranges
+        .iter()
+        .filter(|r| r.start < r.end && text.is_char_boundary(r.start) && text.get(..r.end).is_some())
+        .cloned()
+        .collect();
+    ranges.sort_by_key(|r| r.start);
+
+    let mut segments = Vec::new();
+    let mut pos = 0;
+    for range in ranges {
+        let start = range.start.max(pos);
+        if start >= range.end {
+            continue;
+        }
+        if start > pos {
+            segments.push((text[pos..start].to_string(), false));
+        }
+        segments.push((text[start..range.end].to_string(), true));
+        pos = range.end;
+    }
+    if pos < text.len() {
+        segments.push((text[pos..].to_string(), false));
+    }
+    segments
+}
@@ -0,0 +1,48 @@
+//! Saving exported data as a file the user can keep
+//!
+//! On the web the file is offered as a browser download. On native platforms
+//! it is written to the user's Downloads folder (or the system temp folder if
+//! there is none) and the path is returned so it can be shown.
+
+/// Save `contents` as `file_name`, returning where it was written (native only)
+#[cfg(target_arch = "wasm32")]
+pub fn export_file(file_name: &str, mime_type: &str, contents: &str) -> Result<Option<String>, String> {
+    use wasm_bindgen::{JsCast, JsValue};
+
+    let document = web_sys::window()
+        .and_then(|w| w.document())
+        .ok_or("document unavailable")?;
+
+    let parts = js_sys::Array::new();
+    parts.push(&JsValue::from_str(contents));
+    let options = web_sys::BlobPropertyBag::new();
+    options.set_type(mime_type);
+    let blob = web_sys::Blob::new_with_str_sequence_and_options(&parts, &options)
+        .map_err(|_| "failed to create file")?;
+    let url = web_sys::Url::create_object_url_with_blob(&blob).map_err(|_| "failed to create file")?;
+
+    let anchor = document
+        .create_element("a")
+        .ok()
+        .and_then(|el| el.dyn_into::<web_sys::HtmlAnchorElement>().ok())
+        .ok_or("failed to start download")?;
+    anchor.set_href(&url);
+    anchor.set_download(file_name);
+    anchor.click();
+    let _ = web_sys::Url::revoke_object_url(&url);
+
+    Ok(None)
+}
+
+/// Save `contents` as `file_name`, returning where it was written (native only)
+#[cfg(not(target_arch = "wasm32"))]
+pub fn export_file(file_name: &str, _mime_type: &str, contents: &str) -> Result<Option<String>, String> {
+    let downloads = std::env::var_os("HOME")
+        .or_else(|| std::env::var_os("USERPROFILE"))
+        .map(|home| std::path::PathBuf::from(home).join("Downloads"))
+        .filter(|dir| dir.is_dir());
+    let path = downloads.unwrap_or_else(std::env::temp_dir).join(file_name);
+
+    std::fs::write(&path, contents).map_err(|e| e.to_string())?;
+    Ok(Some(path.display().to_string()))
+}
@@ -1,12 +1,18 @@
+use std::collections::BTreeMap;
+
+use chrono::NaiveDate;
+use chrono_tz::{Tz, TZ_VARIANTS};
 use dioxus::prelude::*;
 use remind_me_ui::{
     Button, ButtonVariant,
     Card, CardContent, CardHeader, CardTitle,
-    FormField, Input, Textarea, Checkbox,
+    FormField, Input, Textarea, Checkbox, Select, SelectOption,
 };
-use remind_me_shared::models::{Reminder, Tag};
+use remind_me_shared::models::{ChecklistItem, LeadTime, Priority, Recurrence, RecurrenceFrequency, Reminder, Tag, Weekday};
 use crate::i18n::use_t;
-use remind_me_shared::utils::{now_rfc3339, now_timestamp_millis, to_datetime_local_value};
+use remind_me_shared::checklist::new_item;
+use remind_me_shared::datetime::{device_time_zone, Timestamp};
+use remind_me_shared::utils::now_timestamp_millis;
 
 #[component]
 pub fn AddReminderForm(
@@ -16,7 +22,13 @@ pub fn AddReminderForm(
     let mut title = use_signal(String::new);
     let mut description = use_signal(String::new);
     let mut due_date = use_signal(String::new);
+    let mut time_zone = use_signal(device_time_zone);
+    let mut priority = use_signal(Priority::default);
+    let mut checklist = use_signal(Vec::<ChecklistItem>::new);
+    let mut auto_complete = use_signal(|| false);
     let mut selected_tag_ids = use_signal(|| Vec::<String>::new());
+    let mut recurrence = use_signal(|| None::<Recurrence>);
+    let mut lead_times = use_signal(Vec::<LeadTime>::new);
 
     rsx! {
         Card {
@@ -69,6 +81,32 @@ pub fn AddReminderForm(
                     }
                 }
 
+                TimeZoneField {
+                    id_prefix: "reminder".to_string(),
+                    time_zone: time_zone,
+                }
+
+                PriorityField {
+                    id_prefix: "reminder".to_string(),
+                    priority: priority,
+                }
+
+                ChecklistFields {
+                    id_prefix: "reminder".to_string(),
+                    checklist: checklist,
+                    auto_complete: auto_complete,
+                }
+
+                LeadTimeFields {
+                    id_prefix: "reminder".to_string(),
+                    lead_times: lead_times,
+                }
+
+                RecurrenceFields {
+                    id_prefix: "reminder".to_string(),
+                    recurrence: recurrence,
+                }
+
                 FormField {
                     id: "reminder_tags".to_string(),
                     name: "tags".to_string(),
@@ -128,16 +166,32 @@ pub fn AddReminderForm(
                                     id: format!("reminder_{}", now_timestamp_millis()),
                                     title: title(),
                                     description: description(),
-                                    due_date: due_date(),
+                                    due_date: due_date().parse().ok(),
+                                    time_zone: time_zone(),
                                     completed: false,
-                                    created_at: now_rfc3339(),
+                                    created_at: Timestamp::now(),
+                                    updated_at: None,
                                     tag_ids: selected_tag_ids(),
+                                    priority: priority(),
+                                    recurrence: recurrence(),
+                                    lead_times: lead_times(),
+                                    snoozed_until: None,
+                                    snooze_log: Vec::new(),
+                                    checklist: checklist(),
+                                    auto_complete: auto_complete(),
+                                    extensions: BTreeMap::new(),
                                 };
                                 on_add.call(reminder);
                                 title.set(String::new());
                                 description.set(String::new());
                                 due_date.set(String::new());
+                                time_zone.set(device_time_zone());
+                                priority.set(Priority::default());
+                                checklist.set(Vec::new());
+                                auto_complete.set(false);
                                 selected_tag_ids.set(Vec::new());
+                                recurrence.set(None);
+                                lead_times.set(Vec::new());
                             }
                         },
                         {use_t("form.add")}
@@ -158,9 +212,18 @@ pub fn EditReminderForm(
     let mut title = use_signal(|| reminder.title.clone());
     let mut description = use_signal(|| reminder.description.clone());
     let mut due_date = use_signal(|| {
-        to_datetime_local_value(&reminder.due_date)
+        reminder
+            .due_zone_local()
+            .map(|d| d.format("%Y-%m-%dT%H:%M").to_string())
+            .unwrap_or_default()
     });
+    let time_zone = use_signal(|| reminder.time_zone);
+    let priority = use_signal(|| reminder.priority);
+    let checklist = use_signal(|| reminder.checklist.clone());
+    let auto_complete = use_signal(|| reminder.auto_complete);
     let mut selected_tag_ids = use_signal(|| reminder.tag_ids.clone());
+    let recurrence = use_signal(|| reminder.recurrence.clone());
+    let lead_times = use_signal(|| reminder.lead_times.clone());
 
     rsx! {
         Card {
@@ -213,6 +276,32 @@ pub fn EditReminderForm(
                     }
                 }
 
+                TimeZoneField {
+                    id_prefix: "edit_reminder".to_string(),
+                    time_zone: time_zone,
+                }
+
+                PriorityField {
+                    id_prefix: "edit_reminder".to_string(),
+                    priority: priority,
+                }
+
+                ChecklistFields {
+                    id_prefix: "edit_reminder".to_string(),
+                    checklist: checklist,
+                    auto_complete: auto_complete,
+                }
+
+                LeadTimeFields {
+                    id_prefix: "edit_reminder".to_string(),
+                    lead_times: lead_times,
+                }
+
+                RecurrenceFields {
+                    id_prefix: "edit_reminder".to_string(),
+                    recurrence: recurrence,
+                }
+
                 FormField {
                     id: "edit_reminder_tags".to_string(),
                     name: "tags".to_string(),
@@ -278,10 +367,20 @@ pub fn EditReminderForm(
                                     id: reminder.id.clone(),
                                     title: title(),
                                     description: description(),
-                                    due_date: due_date(),
+                                    due_date: due_date().parse().ok(),
+                                    time_zone: time_zone(),
                                     completed: reminder.completed,
-                                    created_at: reminder.created_at.clone(),
+                                    created_at: reminder.created_at,
+                                    updated_at: reminder.updated_at,
                                     tag_ids: selected_tag_ids(),
+                                    priority: priority(),
+                                    recurrence: recurrence(),
+                                    lead_times: lead_times(),
+                                    snoozed_until: reminder.snoozed_until,
+                                    snooze_log: reminder.snooze_log.clone(),
+                                    checklist: checklist(),
+                                    auto_complete: auto_complete(),
+                                    extensions: reminder.extensions.clone(),
                                 };
                                 on_save.call(updated);
                                 title.set(String::new());
@@ -296,3 +395,317 @@ pub fn EditReminderForm(
         }
     }
 }
+
+/// Checklist editor shared by the add and edit forms
+#[component]
+fn ChecklistFields(
+    id_prefix: String,
+    checklist: Signal<Vec<ChecklistItem>>,
+    auto_complete: Signal<bool>,
+) -> Element {
+    let mut new_item_text = use_signal(String::new);
+
+    rsx! {
+        FormField {
+            id: format!("{}_checklist", id_prefix),
+            name: "checklist".to_string(),
+            label: use_t("checklist.label"),
+            div {
+                class: "checklist-editor space-y-2",
+                for (index, item) in checklist().into_iter().enumerate() {
+                    div {
+                        key: "{item.id}",
+                        class: "flex items-center gap-2",
+                        Checkbox {
+                            checked: item.completed,
+                            onchange: move |_| {
+                                let mut items = checklist();
+                                items[index].completed = !items[index].completed;
+                                checklist.set(items);
+                            },
+                        }
+                        Input {
+                            r#type: "text",
+                            aria_label: use_t("checklist.item"),
+                            value: item.text.clone(),
+                            oninput: move |value: String| {
+                                let mut items = checklist();
+                                items[index].text = value;
+                                checklist.set(items);
+                            },
+                        }
+                        Button {
+                            variant: ButtonVariant::Ghost,
+                            aria_label: Some(format!("{} {}", use_t("checklist.remove"), item.text)),
+                            onclick: move |_| {
+                                let mut items = checklist();
+                                items.remove(index);
+                                checklist.set(items);
+                            },
+                            "✕"
+                        }
+                    }
+                }
+                div {
+                    class: "flex items-center gap-2",
+                    Input {
+                        id: format!("{}_checklist_new", id_prefix),
+                        r#type: "text",
+                        placeholder: use_t("checklist.placeholder"),
+                        aria_label: use_t("checklist.placeholder"),
+                        value: "{new_item_text()}",
+                        oninput: move |value| new_item_text.set(value),
+                    }
+                    Button {
+                        variant: ButtonVariant::Secondary,
+                        disabled: new_item_text().trim().is_empty(),
+                        aria_label: Some(use_t("checklist.add")),
+                        onclick: move |_| {
+                            let mut items = checklist();
+                            let item = new_item(&items, &new_item_text());
+                            items.push(item);
+                            checklist.set(items);
+                            new_item_text.set(String::new());
+                        },
+                        {use_t("checklist.add")}
+                    }
+                }
+                if !checklist().is_empty() {
+                    Checkbox {
+                        checked: auto_complete(),
+                        label: use_t("checklist.auto_complete"),
+                        onchange: move |_| auto_complete.set(!auto_complete()),
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Priority choice shared by the add and edit forms
+#[component]
+fn PriorityField(
+    id_prefix: String,
+    priority: Signal<Priority>,
+) -> Element {
+    rsx! {
+        FormField {
+            id: format!("{}_priority", id_prefix),
+            name: "priority".to_string(),
+            label: use_t("form.priority.label"),
+            Select {
+                id: format!("{}_priority", id_prefix),
+                name: "priority".to_string(),
+                value: priority().as_str().to_string(),
+                onchange: move |value: String| {
+                    priority.set(Priority::parse(&value).unwrap_or_default());
+                },
+                options: Priority::ALL
+                    .iter()
+                    .map(|p| SelectOption {
+                        value: p.as_str().to_string(),
+                        label: use_t(&format!("priority.{}", p.as_str())),
+                    })
+                    .collect::<Vec<_>>(),
+            }
+        }
+    }
+}
+
+/// Time zone choice shared by the add and edit forms
+///
+/// The empty value means "floating": the due time follows the device's zone.
+#[component]
+fn TimeZoneField(
+    id_prefix: String,
+    time_zone: Signal<Option<Tz>>,
+) -> Element {
+    let options: Vec<SelectOption> = std::iter::once(SelectOption {
+        value: String::new(),
+        label: use_t("form.time_zone.floating"),
+    })
+    .chain(TZ_VARIANTS.iter().map(|tz| SelectOption {
+        value: tz.name().to_string(),
+        label: tz.name().to_string(),
+    }))
+    .collect();
+
+    rsx! {
+        FormField {
+            id: format!("{}_time_zone", id_prefix),
+            name: "time_zone".to_string(),
+            label: use_t("form.time_zone.label"),
+            Select {
+                id: format!("{}_time_zone", id_prefix),
+                name: "time_zone".to_string(),
+                value: time_zone().map(|tz| tz.name().to_string()).unwrap_or_default(),
+                onchange: move |value: String| time_zone.set(value.parse().ok()),
+                options: options,
+            }
+        }
+    }
+}
+
+/// Notification lead-time choices shared by the add and edit forms
+#[component]
+fn LeadTimeFields(
+    id_prefix: String,
+    lead_times: Signal<Vec<LeadTime>>,
+) -> Element {
+    rsx! {
+        FormField {
+            id: format!("{}_lead_times", id_prefix),
+            name: "lead_times".to_string(),
+            label: use_t("form.lead_times.label"),
+            div {
+                class: "tag-checkboxes",
+                for lead_time in LeadTime::PRESETS {
+                    div {
+                        class: "tag-checkbox-label",
+                        Checkbox {
+                            checked: lead_times().contains(&lead_time),
+                            label: use_t(&format!("lead_time.{}", lead_time.minutes)),
+                            onchange: move |_| {
+                                let mut current = lead_times();
+                                if current.contains(&lead_time) {
+                                    current.retain(|l| *l != lead_time);
+                                } else {
+                                    current.push(lead_time);
+                                    current.sort();
+                                }
+                                lead_times.set(current);
+                            },
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Repeat settings shared by the add and edit forms
+#[component]
+fn RecurrenceFields(
+    id_prefix: String,
+    recurrence: Signal<Option<Recurrence>>,
+) -> Element {
+    let frequency_value = recurrence()
+        .map(|r| r.frequency.as_str())
+        .unwrap_or("none")
+        .to_string();
+
+    rsx! {
+        FormField {
+            id: format!("{}_recurrence", id_prefix),
+            name: "recurrence".to_string(),
+            label: use_t("form.recurrence.label"),
+            Select {
+                id: format!("{}_recurrence", id_prefix),
+                name: "recurrence".to_string(),
+                value: frequency_value,
+                onchange: move |value: String| {
+                    let updated = RecurrenceFrequency::parse(&value).map(|frequency| {
+                        match recurrence() {
+                            Some(existing) => Recurrence { frequency, ..existing },
+                            None => Recurrence::new(frequency),
+                        }
+                    });
+                    recurrence.set(updated);
+                },
+                options: vec![
+                    SelectOption { value: "none".to_string(), label: use_t("recurrence.none") },
+                    SelectOption { value: "daily".to_string(), label: use_t("recurrence.daily") },
+                    SelectOption { value: "weekly".to_string(), label: use_t("recurrence.weekly") },
+                    SelectOption { value: "monthly".to_string(), label: use_t("recurrence.monthly") },
+                    SelectOption { value: "yearly".to_string(), label: use_t("recurrence.yearly") },
+                ],
+            }
+        }
+
+        if let Some(rule) = recurrence() {
+            FormField {
+                id: format!("{}_recurrence_interval", id_prefix),
+                name: "recurrence_interval".to_string(),
+                label: use_t("form.recurrence.interval"),
+                Input {
+                    id: format!("{}_recurrence_interval", id_prefix),
+                    name: "recurrence_interval".to_string(),
+                    r#type: "number",
+                    value: rule.interval.to_string(),
+                    oninput: move |value: String| {
+                        if let Some(mut rule) = recurrence() {
+                            rule.interval = value.trim().parse::<u32>().unwrap_or(1).max(1);
+                            recurrence.set(Some(rule));
+                        }
+                    },
+                }
+            }
+
+            if matches!(rule.frequency, RecurrenceFrequency::Daily | RecurrenceFrequency::Weekly) {
+                FormField {
+                    id: format!("{}_recurrence_weekdays", id_prefix),
+                    name: "recurrence_weekdays".to_string(),
+                    label: use_t("form.recurrence.weekdays"),
+                    div {
+                        class: "tag-checkboxes",
+                        for day in Weekday::ALL {
+                            div {
+                                class: "tag-checkbox-label",
+                                Checkbox {
+                                    checked: rule.by_weekday.contains(&day),
+                                    label: use_t(&format!("recurrence.weekday.{}", day.as_str().to_lowercase())),
+                                    onchange: move |_| {
+                                        if let Some(mut rule) = recurrence() {
+                                            if rule.by_weekday.contains(&day) {
+                                                rule.by_weekday.retain(|d| *d != day);
+                                            } else {
+                                                rule.by_weekday.push(day);
+                                            }
+                                            recurrence.set(Some(rule));
+                                        }
+                                    },
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+
+            FormField {
+                id: format!("{}_recurrence_count", id_prefix),
+                name: "recurrence_count".to_string(),
+                label: use_t("form.recurrence.count"),
+                Input {
+                    id: format!("{}_recurrence_count", id_prefix),
+                    name: "recurrence_count".to_string(),
+                    r#type: "number",
+                    value: rule.count.map(|c| c.to_string()).unwrap_or_default(),
+                    oninput: move |value: String| {
+                        if let Some(mut rule) = recurrence() {
+                            rule.count = value.trim().parse::<u32>().ok().filter(|c| *c > 0);
+                            recurrence.set(Some(rule));
+                        }
+                    },
+                }
+            }
+
+            FormField {
+                id: format!("{}_recurrence_until", id_prefix),
+                name: "recurrence_until".to_string(),
+                label: use_t("form.recurrence.until"),
+                Input {
+                    id: format!("{}_recurrence_until", id_prefix),
+                    name: "recurrence_until".to_string(),
+                    r#type: "date",
+                    value: rule.until.map(|d| d.format("%Y-%m-%d").to_string()).unwrap_or_default(),
+                    oninput: move |value: String| {
+                        if let Some(mut rule) = recurrence() {
+                            rule.until = NaiveDate::parse_from_str(&value, "%Y-%m-%d").ok();
+                            recurrence.set(Some(rule));
+                        }
+                    },
+                }
+            }
+        }
+    }
+}
@@ -15,6 +15,7 @@ pub mod cards;
 pub mod modals;
 pub mod tag_form;
 pub mod tag_manager;
+pub mod backup_manager;
 pub mod media;
 pub mod language_switcher;
 pub mod hero_section;
@@ -25,6 +26,8 @@ pub mod faq_section;
 pub mod final_cta_section;
 pub mod app_views;
 pub mod i18n;
+pub mod storage;
+pub mod export;
 pub use landing::LandingPage;
 pub use features_section::FeaturesSection;
 pub use legal::{PrivacyPolicyPage, TermsOfUsePage};
@@ -35,6 +38,7 @@ pub use cards::ReminderCard;
 pub use modals::DeleteConfirmModal;
 pub use tag_form::TagForm;
 pub use tag_manager::TagManager;
+pub use backup_manager::BackupManager;
 #[allow(unused_imports)]
 pub use media::{CachedImage, CachedVideo, MediaCacheProvider, ManagedCachedImage, ManagedCachedVideo};
 pub use language_switcher::LanguageSwitcher;
@@ -7,27 +7,91 @@ use remind_me_ui::{
     Button, ButtonVariant, Input, Select, SelectOption,
     EmptyState, Toast, ToastPosition, ToastVariant,
 };
-use remind_me_shared::models::{Reminder, ReminderFilter, ReminderSort};
-use remind_me_shared::storage::{load_reminders, save_reminders, load_tags};
-use remind_me_shared::utils::get_filtered_and_sorted_reminders;
+use remind_me_shared::models::{Priority, PriorityFilter, Reminder, ReminderFilter, ReminderSort, SavedView};
+use remind_me_shared::notifications::{Clock, NotificationPlatform, NotificationScheduler, PlatformNotifier, SystemClock};
+use remind_me_shared::checklist::{should_auto_complete, toggle_item};
+use remind_me_shared::query::Query;
+use remind_me_shared::snooze::{clear_snooze, snooze, SnoozeOption};
+use remind_me_shared::storage::{IntegrityReport, ReminderRepository, RepositoryError, TagRepository, ToggleOutcome};
+use remind_me_shared::utils::{get_filtered_and_sorted_reminders, now_timestamp_millis};
 // Use re-exports from mod.rs to avoid clippy warnings
-use super::{StatisticsDisplay, AddReminderForm, EditReminderForm, DeleteConfirmModal, ListView, CardView, FolderView, CalendarView, TagManager};
-use crate::i18n::use_t;
+use super::{StatisticsDisplay, AddReminderForm, EditReminderForm, DeleteConfirmModal, ListView, CardView, FolderView, CalendarView, TagManager, BackupManager};
+use crate::export::export_file;
+use crate::i18n::{use_i18n, use_t};
+use crate::storage::use_repository;
+
+/// Longest time the notification loop sleeps before re-checking reminders
+const NOTIFICATION_POLL_MS: i64 = 30_000;
+
+/// Sleep for `ms` milliseconds on the current platform's async runtime
+async fn sleep_ms(ms: i64) {
+    #[cfg(target_arch = "wasm32")]
+    gloo_timers::future::TimeoutFuture::new(ms as u32).await;
+
+    #[cfg(not(target_arch = "wasm32"))]
+    tokio::time::sleep(std::time::Duration::from_millis(ms as u64)).await;
+}
 
 #[component]
 pub fn ReminderApp() -> Element {
-    let mut reminders = use_signal(load_reminders);
-    let mut tags = use_signal(load_tags);
+    // Load stored data once, recovering what it can and keeping the first error to report
+    // Dangling tag references are repaired on the way (only if everything loaded)
+    // Values the schema upgrade left out are reported if nothing else went wrong
+    let repository = use_repository();
+    let (reminder_repository, initial_reminders, initial_tags, initial_views, load_error, repaired) = use_hook(|| {
+        let repository = repository.cloned();
+        let (reminders, reminders_error) = ReminderRepository::open(repository.clone());
+        let tags = repository.load_tags();
+        let views = repository.load_saved_views();
+        let mut error = reminders_error.or(tags.error).or(views.error);
+        let mut repaired = Vec::new();
+        if error.is_none() {
+            match TagRepository::new(repository.clone()).repair_references(&reminders) {
+                Ok(report) => repaired = repaired_titles(&report, &reminders),
+                Err(RepositoryError::Storage(storage_error)) => error = Some(storage_error),
+                Err(_) => {}
+            }
+        }
+        let error = error.or_else(|| repository.migration_dropped().first().cloned());
+        let initial = reminders.all();
+        (CopyValue::new(reminders), initial, tags.items, views.items, error, repaired)
+    });
+    use_context_provider(|| reminder_repository);
+    let mut reminders = use_signal(|| initial_reminders);
+    // Mirror every change made through the repository into the rendered list
+    use_hook(move || {
+        reminder_repository.read().subscribe(move |change| {
+            let mut reminders = reminders;
+            reminders.with_mut(|list| change.apply(list));
+        })
+    });
+    let mut tags = use_signal(|| initial_tags);
     let mut show_add_form = use_signal(|| false);
     let mut filter = use_signal(|| ReminderFilter::All);
+    let mut priority_filter = use_signal(|| PriorityFilter::All);
     let mut search_query = use_signal(String::new);
     let mut sort_by = use_signal(|| ReminderSort::Date);
     let mut editing_id = use_signal(|| None::<String>);
 
-    // Toast notification state
-    let mut show_toast = use_signal(|| false);
-    let mut toast_message = use_signal(String::new);
-    let mut toast_variant = use_signal(|| ToastVariant::Success);
+    // Toast notification state (opens with the integrity repair notice, if any)
+    let was_repaired = !repaired.is_empty();
+    let repaired_message = format!("{} {}", use_t("tags.repaired"), repaired.join(", "));
+    let mut show_toast = use_signal(|| was_repaired);
+    let mut toast_message = use_signal(|| if was_repaired { repaired_message } else { String::new() });
+    let mut toast_variant = use_signal(|| if was_repaired { ToastVariant::Info } else { ToastVariant::Success });
+
+    // Storage problem found while loading (stays open until dismissed)
+    let show_load_error = load_error.is_some();
+    let mut storage_error = use_signal(|| load_error);
+    let mut show_storage_error = use_signal(|| show_load_error);
+    // Writes that fail after the call returned (IndexedDB) are reported as they fail
+    use_hook(move || {
+        repository.read().storage().set_error_listener(Box::new(move |error| {
+            let (mut storage_error, mut show_storage_error) = (storage_error, show_storage_error);
+            storage_error.set(Some(error));
+            show_storage_error.set(true);
+        }))
+    });
 
     // Delete confirmation state
     let mut delete_confirm_id = use_signal(|| None::<String>);
@@ -38,6 +102,208 @@ pub fn ReminderApp() -> Element {
     // Tag manager modal state
     let mut show_tag_manager = use_signal(|| false);
 
+    // Backup modal state
+    let mut show_backup_manager = use_signal(|| false);
+
+    // Saved views (named filter/search/sort/view presets)
+    let mut saved_views = use_signal(|| initial_views);
+    let mut show_save_view = use_signal(|| false);
+    let mut new_view_name = use_signal(String::new);
+    let active_view_id = saved_views()
+        .iter()
+        .find(|v| v.is_applied(&filter(), &priority_filter(), &search_query(), &sort_by(), &current_view()))
+        .map(|v| v.id.clone());
+
+    // Notification scheduler: fires due reminders and mirrors them as in-app toasts
+    let i18n = use_i18n();
+    let mut scheduler = use_signal(|| NotificationScheduler::new(PlatformNotifier::default(), SystemClock));
+    let mut notifications_permitted = use_signal(|| scheduler.peek().platform().is_permitted());
+    use_future(move || async move {
+        loop {
+            let fired = scheduler.write().tick(&reminders.peek());
+            if let Some(latest) = fired.last() {
+                toast_message.set(format!("{} {}", i18n.peek().t("notifications.due"), latest.title));
+                toast_variant.set(ToastVariant::Warning);
+                show_toast.set(true);
+            }
+
+            let permitted = scheduler.peek().platform().is_permitted();
+            if permitted != *notifications_permitted.peek() {
+                notifications_permitted.set(permitted);
+            }
+
+            let now = scheduler.peek().clock().now_ms();
+            let wait = scheduler
+                .peek()
+                .next_fire_at(&reminders.peek())
+                .map(|fire_at| (fire_at - now).clamp(1_000, NOTIFICATION_POLL_MS))
+                .unwrap_or(NOTIFICATION_POLL_MS);
+            sleep_ms(wait).await;
+        }
+    });
+
+    // Show an error toast for a failed reminder change
+    let mut report_error = move |error: RepositoryError| {
+        toast_message.set(format!("{} {}", use_t("storage.save_failed"), error));
+        toast_variant.set(ToastVariant::Error);
+        show_toast.set(true);
+    };
+
+    // Toggle completion; completing a recurring reminder rolls it forward instead
+    let mut toggle_reminder = move |id: String| {
+        let status = match reminder_repository.read().toggle(&id) {
+            Ok(ToggleOutcome::Rescheduled) => use_t("toast.rescheduled"),
+            Ok(ToggleOutcome::Completed) => use_t("toast.completed"),
+            Ok(ToggleOutcome::Reactivated) => use_t("toast.marked_active"),
+            Err(error) => {
+                report_error(error);
+                return;
+            }
+        };
+
+        toast_message.set(format!("{} {}", use_t("toast.info"), status));
+        toast_variant.set(ToastVariant::Info);
+        show_toast.set(true);
+    };
+
+    // Toggle a checklist item, completing the reminder once its checklist is done
+    let toggle_checklist_item = move |(id, item_id): (String, String)| {
+        let Some(mut r) = reminder_repository.read().get_by_id(&id) else {
+            return;
+        };
+        if !toggle_item(&mut r, &item_id) {
+            return;
+        }
+        let complete_reminder = should_auto_complete(&r);
+        if let Err(error) = reminder_repository.read().update(r) {
+            report_error(error);
+            return;
+        }
+
+        if complete_reminder {
+            toggle_reminder(id);
+        }
+    };
+
+    // Snooze (or, with `None`, cancel the snooze of) a reminder
+    let snooze_reminder = move |(id, option): (String, Option<SnoozeOption>)| {
+        let Some(mut r) = reminder_repository.read().get_by_id(&id) else {
+            return;
+        };
+        let message = match option {
+            Some(option) => {
+                if !snooze(&mut r, &option) {
+                    toast_message.set(use_t("snooze.invalid"));
+                    toast_variant.set(ToastVariant::Error);
+                    show_toast.set(true);
+                    return;
+                }
+                format!(
+                    "{} {}",
+                    use_t("snooze.snoozed_until"),
+                    r.snoozed_until.map(|t| t.format_display()).unwrap_or_default()
+                )
+            }
+            None => {
+                clear_snooze(&mut r);
+                use_t("snooze.cancelled")
+            }
+        };
+        if let Err(error) = reminder_repository.read().update(r) {
+            report_error(error);
+            return;
+        }
+
+        toast_message.set(message);
+        toast_variant.set(ToastVariant::Info);
+        show_toast.set(true);
+    };
+
+    // Export the raw data preserved for the storage error, so it can be recovered by hand
+    let export_raw_data = move |_| {
+        let Some(key) = storage_error().and_then(|e| e.raw_data_key().map(str::to_string)) else {
+            return;
+        };
+        let Ok(Some(data)) = repository.read().storage().get(&key) else {
+            return;
+        };
+        match export_file(&format!("remind-me-{}.json", key), "application/json", &data) {
+            Ok(Some(path)) => {
+                toast_message.set(format!("{} {}", use_t("storage.exported_to"), path));
+                toast_variant.set(ToastVariant::Success);
+            }
+            Ok(None) => {
+                toast_message.set(use_t("storage.exported"));
+                toast_variant.set(ToastVariant::Success);
+            }
+            Err(e) => {
+                toast_message.set(format!("{} {}", use_t("storage.export_failed"), e));
+                toast_variant.set(ToastVariant::Error);
+            }
+        }
+        show_toast.set(true);
+    };
+
+    // Save the current filter, search, sort and view under a name
+    let mut save_current_view = move || {
+        let name = new_view_name().trim().to_string();
+        if name.is_empty() {
+            return;
+        }
+        let mut views = saved_views();
+        views.push(SavedView {
+            id: format!("view_{}", now_timestamp_millis()),
+            name,
+            filter: filter(),
+            priority_filter: priority_filter(),
+            search_query: search_query(),
+            sort: sort_by(),
+            view: current_view(),
+        });
+        saved_views.set(views);
+        new_view_name.set(String::new());
+        show_save_view.set(false);
+
+        match repository.read().save_saved_views(&saved_views()) {
+            Ok(()) => {
+                toast_message.set(use_t("views.saved"));
+                toast_variant.set(ToastVariant::Success);
+            }
+            Err(error) => {
+                toast_message.set(format!("{} {}", use_t("storage.save_failed"), error));
+                toast_variant.set(ToastVariant::Error);
+            }
+        }
+        show_toast.set(true);
+    };
+
+    // Reload everything a backup import replaced, repairing tag references it left dangling
+    let reload_after_import = move |_| {
+        let mut error = reminder_repository.read().reload();
+        if error.is_none() {
+            match TagRepository::new(repository.cloned()).repair_references(&reminder_repository.read()) {
+                Ok(report) if !report.is_empty() => {
+                    let titles = repaired_titles(&report, &reminder_repository.read());
+                    toast_message.set(format!("{} {}", use_t("tags.repaired"), titles.join(", ")));
+                    toast_variant.set(ToastVariant::Info);
+                    show_toast.set(true);
+                }
+                Err(RepositoryError::Storage(storage_error)) => error = Some(storage_error),
+                _ => {}
+            }
+        }
+        let loaded_tags = repository.read().load_tags();
+        let loaded_views = repository.read().load_saved_views();
+        reminders.set(reminder_repository.read().all());
+        tags.set(loaded_tags.items);
+        saved_views.set(loaded_views.items);
+
+        if let Some(error) = error.or(loaded_tags.error).or(loaded_views.error) {
+            storage_error.set(Some(error));
+            show_storage_error.set(true);
+        }
+    };
+
     // Keyboard shortcuts (global event listener)
     #[cfg(target_arch = "wasm32")]
     let _keyboard_listener = use_hook_with_cleanup(
@@ -152,12 +418,76 @@ pub fn ReminderApp() -> Element {
                 h1 { {use_t("app.header.title")} }
                 div {
                     class: "app-header-actions",
+                    if !saved_views().is_empty() {
+                        Select {
+                            id: "saved_views".to_string(),
+                            name: "saved_view".to_string(),
+                            aria_label: use_t("views.switch"),
+                            value: active_view_id.clone().unwrap_or_default(),
+                            onchange: move |id: String| {
+                                if let Some(view) = saved_views().into_iter().find(|v| v.id == id) {
+                                    filter.set(view.filter);
+                                    priority_filter.set(view.priority_filter);
+                                    search_query.set(view.search_query);
+                                    sort_by.set(view.sort);
+                                    current_view.set(view.view);
+                                }
+                            },
+                            options: std::iter::once(SelectOption {
+                                value: String::new(),
+                                label: use_t("views.custom"),
+                            })
+                            .chain(saved_views().into_iter().map(|v| SelectOption {
+                                value: v.id,
+                                label: v.name,
+                            }))
+                            .collect::<Vec<_>>(),
+                        }
+                    }
+                    if let Some(view_id) = active_view_id.clone() {
+                        Button {
+                            variant: ButtonVariant::Ghost,
+                            aria_label: Some(use_t("views.delete")),
+                            onclick: move |_| {
+                                let mut views = saved_views();
+                                views.retain(|v| v.id != view_id);
+                                saved_views.set(views);
+                                if let Err(error) = repository.read().save_saved_views(&saved_views()) {
+                                    toast_message.set(format!("{} {}", use_t("storage.save_failed"), error));
+                                    toast_variant.set(ToastVariant::Error);
+                                    show_toast.set(true);
+                                }
+                            },
+                            "🗑️"
+                        }
+                    } else {
+                        Button {
+                            variant: ButtonVariant::Ghost,
+                            aria_label: Some(use_t("views.save")),
+                            onclick: move |_| show_save_view.set(!show_save_view()),
+                            "💾"
+                        }
+                    }
+                    if !notifications_permitted() && scheduler.peek().platform().is_available() {
+                        Button {
+                            variant: ButtonVariant::Ghost,
+                            aria_label: Some(use_t("notifications.enable")),
+                            onclick: move |_| scheduler.peek().platform().request_permission(),
+                            "🔔"
+                        }
+                    }
                     Button {
                         variant: ButtonVariant::Ghost,
                         aria_label: Some(use_t("tags.manage")),
                         onclick: move |_| show_tag_manager.set(true),
                         "🏷️"
                     }
+                    Button {
+                        variant: ButtonVariant::Ghost,
+                        aria_label: Some(use_t("backup.manage")),
+                        onclick: move |_| show_backup_manager.set(true),
+                        "📦"
+                    }
                     Button {
                         variant: ButtonVariant::Primary,
                         aria_label: Some(if show_add_form() {
@@ -177,6 +507,32 @@ pub fn ReminderApp() -> Element {
                 }
             }
 
+            if show_save_view() {
+                div {
+                    class: "save-view-form flex gap-2 mb-4",
+                    Input {
+                        id: "saved_view_name".to_string(),
+                        name: "saved_view_name".to_string(),
+                        r#type: "text",
+                        aria_label: use_t("views.name"),
+                        placeholder: use_t("views.name_placeholder"),
+                        value: "{new_view_name()}",
+                        oninput: move |value| new_view_name.set(value),
+                    }
+                    Button {
+                        variant: ButtonVariant::Primary,
+                        disabled: new_view_name().trim().is_empty(),
+                        onclick: move |_| save_current_view(),
+                        {use_t("views.save")}
+                    }
+                    Button {
+                        variant: ButtonVariant::Ghost,
+                        onclick: move |_| show_save_view.set(false),
+                        {use_t("app.header.cancel")}
+                    }
+                }
+            }
+
             main {
                 role: "main",
                 // Statistics section
@@ -206,8 +562,41 @@ pub fn ReminderApp() -> Element {
                                 SelectOption { value: "date".to_string(), label: use_t("sort.date") },
                                 SelectOption { value: "title".to_string(), label: use_t("sort.title") },
                                 SelectOption { value: "status".to_string(), label: use_t("sort.status") },
+                                SelectOption { value: "priority".to_string(), label: use_t("sort.priority") },
+                                SelectOption { value: "smart".to_string(), label: use_t("sort.smart") },
                             ],
                         }
+                        Select {
+                            id: "filter_priority".to_string(),
+                            name: "priority_filter".to_string(),
+                            aria_label: use_t("filter.priority"),
+                            value: priority_filter().as_str().to_string(),
+                            onchange: move |value: String| {
+                                priority_filter.set(PriorityFilter::parse(&value));
+                            },
+                            options: std::iter::once(SelectOption {
+                                value: "all".to_string(),
+                                label: use_t("filter.priority"),
+                            })
+                            .chain(Priority::ALL.iter().rev().map(|p| SelectOption {
+                                value: p.as_str().to_string(),
+                                label: use_t(&format!("priority.{}", p.as_str())),
+                            }))
+                            .collect::<Vec<_>>(),
+                        }
+                    }
+                    if let Err(error) = Query::parse(&search_query()) {
+                        p {
+                            class: "search-error text-sm text-red-600",
+                            role: "alert",
+                            {
+                                let mut message = use_t(&format!("query.error.{}", error.code()));
+                                if let Some(subject) = error.subject() {
+                                    message = format!("{} “{}”", message, subject);
+                                }
+                                format!("{} ({} {})", message, use_t("query.column"), error.position() + 1)
+                            }
+                        }
                     }
                 }
 
@@ -218,13 +607,11 @@ pub fn ReminderApp() -> Element {
                                 reminder: reminder.clone(),
                                 tags: tags(),
                                 on_save: move |updated: Reminder| {
-                                    let mut updated_reminders = reminders();
-                                    if let Some(r) = updated_reminders.iter_mut().find(|r| r.id == updated.id) {
-                                        *r = updated.clone();
-                                    }
-                                    reminders.set(updated_reminders);
-                                    save_reminders(&reminders());
                                     editing_id.set(None);
+                                    if let Err(error) = reminder_repository.read().update(updated) {
+                                        report_error(error);
+                                        return;
+                                    }
 
                                     toast_message.set(use_t("toast.updated"));
                                     toast_variant.set(ToastVariant::Success);
@@ -237,11 +624,11 @@ pub fn ReminderApp() -> Element {
                         AddReminderForm {
                             tags: tags(),
                             on_add: move |reminder: Reminder| {
-                                let mut new_reminders = reminders();
-                                new_reminders.push(reminder);
-                                reminders.set(new_reminders);
-                                save_reminders(&reminders());
                                 show_add_form.set(false);
+                                if let Err(error) = reminder_repository.read().create(reminder) {
+                                    report_error(error);
+                                    return;
+                                }
 
                                 toast_message.set(use_t("toast.added"));
                                 toast_variant.set(ToastVariant::Success);
@@ -304,7 +691,9 @@ pub fn ReminderApp() -> Element {
                 {
                     let filtered_reminders = get_filtered_and_sorted_reminders(
                         &reminders(),
+                        &tags(),
                         &filter(),
+                        &priority_filter(),
                         &search_query(),
                         &sort_by()
                     );
@@ -316,19 +705,9 @@ pub fn ReminderApp() -> Element {
                                 tags: tags(),
                                 filter: filter(),
                                 search_query: search_query(),
-                                on_toggle: move |id: String| {
-                                    let mut updated = reminders();
-                                    if let Some(r) = updated.iter_mut().find(|r| r.id == id) {
-                                        r.completed = !r.completed;
-                                        let status = if r.completed { use_t("toast.completed") } else { use_t("toast.marked_active") };
-                                        reminders.set(updated);
-                                        save_reminders(&reminders());
-
-                                        toast_message.set(format!("{} {}", use_t("toast.info"), status));
-                                        toast_variant.set(ToastVariant::Info);
-                                        show_toast.set(true);
-                                    }
-                                },
+                                on_toggle: toggle_reminder,
+                                on_snooze: snooze_reminder,
+                                on_toggle_item: toggle_checklist_item,
                                 on_edit: move |id: String| {
                                     editing_id.set(Some(id));
                                     show_add_form.set(false);
@@ -343,19 +722,9 @@ pub fn ReminderApp() -> Element {
                             CardView {
                                 reminders: filtered_reminders,
                                 tags: tags(),
-                                on_toggle: move |id: String| {
-                                    let mut updated = reminders();
-                                    if let Some(r) = updated.iter_mut().find(|r| r.id == id) {
-                                        r.completed = !r.completed;
-                                        let status = if r.completed { use_t("toast.completed") } else { use_t("toast.marked_active") };
-                                        reminders.set(updated);
-                                        save_reminders(&reminders());
-
-                                        toast_message.set(format!("{} {}", use_t("toast.info"), status));
-                                        toast_variant.set(ToastVariant::Info);
-                                        show_toast.set(true);
-                                    }
-                                },
+                                on_toggle: toggle_reminder,
+                                on_snooze: snooze_reminder,
+                                on_toggle_item: toggle_checklist_item,
                                 on_edit: move |id: String| {
                                     editing_id.set(Some(id));
                                     show_add_form.set(false);
@@ -369,19 +738,9 @@ pub fn ReminderApp() -> Element {
                             FolderView {
                                 reminders: filtered_reminders,
                                 tags: tags(),
-                                on_toggle: move |id: String| {
-                                    let mut updated = reminders();
-                                    if let Some(r) = updated.iter_mut().find(|r| r.id == id) {
-                                        r.completed = !r.completed;
-                                        let status = if r.completed { use_t("toast.completed") } else { use_t("toast.marked_active") };
-                                        reminders.set(updated);
-                                        save_reminders(&reminders());
-
-                                        toast_message.set(format!("{} {}", use_t("toast.info"), status));
-                                        toast_variant.set(ToastVariant::Info);
-                                        show_toast.set(true);
-                                    }
-                                },
+                                on_toggle: toggle_reminder,
+                                on_snooze: snooze_reminder,
+                                on_toggle_item: toggle_checklist_item,
                                 on_edit: move |id: String| {
                                     editing_id.set(Some(id));
                                     show_add_form.set(false);
@@ -396,21 +755,12 @@ pub fn ReminderApp() -> Element {
                                 reminders: reminders(),
                                 tags: tags(),
                                 filter: filter(),
+                                priority_filter: priority_filter(),
                                 search_query: search_query(),
                                 sort_by: sort_by(),
-                                on_toggle: move |id: String| {
-                                    let mut updated = reminders();
-                                    if let Some(r) = updated.iter_mut().find(|r| r.id == id) {
-                                        r.completed = !r.completed;
-                                        let status = if r.completed { use_t("toast.completed") } else { use_t("toast.marked_active") };
-                                        reminders.set(updated);
-                                        save_reminders(&reminders());
-
-                                        toast_message.set(format!("{} {}", use_t("toast.info"), status));
-                                        toast_variant.set(ToastVariant::Info);
-                                        show_toast.set(true);
-                                    }
-                                },
+                                on_toggle: toggle_reminder,
+                                on_snooze: snooze_reminder,
+                                on_toggle_item: toggle_checklist_item,
                                 on_edit: move |id: String| {
                                     editing_id.set(Some(id));
                                     show_add_form.set(false);
@@ -426,19 +776,9 @@ pub fn ReminderApp() -> Element {
                                 tags: tags(),
                                 filter: filter(),
                                 search_query: search_query(),
-                                on_toggle: move |id: String| {
-                                    let mut updated = reminders();
-                                    if let Some(r) = updated.iter_mut().find(|r| r.id == id) {
-                                        r.completed = !r.completed;
-                                        let status = if r.completed { use_t("toast.completed") } else { use_t("toast.marked_active") };
-                                        reminders.set(updated);
-                                        save_reminders(&reminders());
-
-                                        toast_message.set(format!("{} {}", use_t("toast.info"), status));
-                                        toast_variant.set(ToastVariant::Info);
-                                        show_toast.set(true);
-                                    }
-                                },
+                                on_toggle: toggle_reminder,
+                                on_snooze: snooze_reminder,
+                                on_toggle_item: toggle_checklist_item,
                                 on_edit: move |id: String| {
                                     editing_id.set(Some(id));
                                     show_add_form.set(false);
@@ -469,11 +809,11 @@ pub fn ReminderApp() -> Element {
                     open: delete_confirm_id,
                     reminder_id: delete_id.clone(),
                     on_confirm: move |id: String| {
-                        let mut updated = reminders();
-                        updated.retain(|r| r.id != id);
-                        reminders.set(updated);
-                        save_reminders(&reminders());
                         delete_confirm_id.set(None);
+                        if let Err(error) = reminder_repository.read().delete(&id) {
+                            report_error(error);
+                            return;
+                        }
 
                         toast_message.set(use_t("toast.deleted"));
                         toast_variant.set(ToastVariant::Success);
@@ -489,10 +829,44 @@ pub fn ReminderApp() -> Element {
                 on_close: move |_| {
                     show_tag_manager.set(false);
                     // Reload tags after closing tag manager
-                    tags.set(load_tags());
+                    tags.set(repository.read().load_tags().items);
                 },
             }
 
+            // Backup export and import
+            BackupManager {
+                open: show_backup_manager,
+                on_close: move |_| show_backup_manager.set(false),
+                shown_reminders: if show_backup_manager() {
+                    get_filtered_and_sorted_reminders(
+                        &reminders(),
+                        &tags(),
+                        &filter(),
+                        &priority_filter(),
+                        &search_query(),
+                        &sort_by()
+                    )
+                } else {
+                    Vec::new()
+                },
+                on_imported: reload_after_import,
+            }
+
+            // Storage error, with an export of the preserved raw data
+            if let Some(error) = storage_error() {
+                Toast {
+                    open: show_storage_error,
+                    variant: ToastVariant::Error,
+                    title: use_t("storage.error_title"),
+                    message: format!("{} ({})", use_t("storage.error_message"), error),
+                    position: ToastPosition::TopCenter,
+                    duration: 0,
+                    action_label: if error.raw_data_key().is_some() { use_t("storage.export") } else { String::new() },
+                    on_action: export_raw_data,
+                    on_close: move |_| show_storage_error.set(false),
+                }
+            }
+
             // Toast notification
             Toast {
                 open: show_toast,
@@ -513,3 +887,12 @@ pub fn ReminderApp() -> Element {
         }
     }
 }
+
+/// Titles of the reminders a tag reference repair changed, for the notice
+fn repaired_titles(report: &IntegrityReport, reminders: &ReminderRepository) -> Vec<String> {
+    report
+        .reminder_ids()
+        .filter_map(|id| reminders.get_by_id(id))
+        .map(|reminder| format!("\u{201c}{}\u{201d}", reminder.title))
+        .collect()
+}
@@ -32,6 +32,23 @@ pub fn StatisticsDisplay(reminders: Vec<Reminder>) -> Element {
                     span { class: "stat-value", "{stats.overdue}" }
                     span { class: "stat-label", {use_t("stats.overdue")} }
                 }
+                div {
+                    class: "stat-item",
+                    span { class: "stat-value", "{stats.snoozed}" }
+                    span { class: "stat-label", {use_t("stats.snoozed")} }
+                }
+                div {
+                    class: "stat-item",
+                    span { class: "stat-value", "{stats.snooze_count}" }
+                    span { class: "stat-label", {use_t("stats.snooze_count")} }
+                }
+                if stats.checklist_items > 0 {
+                    div {
+                        class: "stat-item",
+                        span { class: "stat-value", "{stats.checklist_completed}/{stats.checklist_items}" }
+                        span { class: "stat-label", {use_t("stats.checklist")} }
+                    }
+                }
             }
         }
     }
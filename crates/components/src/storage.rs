@@ -0,0 +1,36 @@
+//! Storage context for UI components.
+//!
+//! Platform crates choose the storage backend and register it near the app
+//! root with `use_init_storage` (or `StorageProvider` for backends that open
+//! asynchronously); components load and save through `use_repository`.
+
+use dioxus::prelude::*;
+
+use remind_me_shared::storage::{ReminderRepository, Repository, StorageHandle};
+
+/// Initialize the storage context provider (call once near app root).
+pub fn use_init_storage(init: impl FnOnce() -> StorageHandle) {
+    use_context_provider(|| CopyValue::new(Repository::new(init())));
+}
+
+/// Provide an already opened storage backend to `children`.
+///
+/// For backends that open asynchronously: render this once the handle is ready.
+#[component]
+pub fn StorageProvider(storage: CopyValue<StorageHandle>, children: Element) -> Element {
+    use_context_provider(|| CopyValue::new(Repository::new(storage.cloned())));
+    children
+}
+
+/// Hook to access the reminder repository `ReminderApp` provides to its children.
+pub fn use_reminder_repository() -> CopyValue<ReminderRepository> {
+    use_context()
+}
+
+/// Hook to access the repository over the registered backend.
+///
+/// The handle is `Copy`, so it can be moved into any number of event
+/// handlers; use `repository.read()` to load and save.
+pub fn use_repository() -> CopyValue<Repository> {
+    use_context()
+}
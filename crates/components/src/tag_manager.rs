@@ -4,32 +4,71 @@ use remind_me_ui::{
     Card, CardContent,
     Modal, ModalSize,
     EmptyState,
+    Select, SelectOption,
 };
+use remind_me_shared::ical::{export_ics, ICS_MIME_TYPE};
 use remind_me_shared::models::Tag;
-use remind_me_shared::storage::{load_tags, save_tags};
+use remind_me_shared::storage::{TagDeletion, TagRepository};
 use crate::TagForm;
+use crate::export::export_file;
 use crate::i18n::use_t;
+use crate::storage::{use_reminder_repository, use_repository};
 
 #[component]
 pub fn TagManager(
     open: Signal<bool>,
     on_close: EventHandler<()>,
 ) -> Element {
-    let mut tags = use_signal(load_tags);
+    let repository = use_repository();
+    let reminder_repository = use_reminder_repository();
+    // Load failures are reported by ReminderApp
+    let mut tags = use_signal(|| repository.read().load_tags().items);
     let mut editing_tag = use_signal(|| None::<Tag>);
     let mut show_form = use_signal(|| false);
     let mut delete_confirm_id = use_signal(|| None::<String>);
+    let mut save_error = use_signal(|| None::<String>);
+    // Outcome of the last calendar export, and whether it failed
+    let mut export_status = use_signal(|| None::<(String, bool)>);
+
+    // Reload tags after a change, keeping the failure (if any) to show
+    let mut after_change = move |error: Option<String>| {
+        save_error.set(error);
+        tags.set(repository.read().load_tags().items);
+    };
 
     // Reload tags when modal opens
     use_effect(move || {
         if open() {
-            tags.set(load_tags());
+            tags.set(repository.read().load_tags().items);
+            save_error.set(None);
+            export_status.set(None);
             editing_tag.set(None);
             show_form.set(false);
             delete_confirm_id.set(None);
         }
     });
 
+    // Export every reminder with `tag` as an iCalendar file named after it
+    let export_tag = move |tag: Tag| {
+        let reminders: Vec<_> = reminder_repository
+            .read()
+            .all()
+            .into_iter()
+            .filter(|r| r.tag_ids.contains(&tag.id))
+            .collect();
+        let file_name: String = tag
+            .name
+            .chars()
+            .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' { c } else { '-' })
+            .collect();
+        let result = export_file(&format!("{}.ics", file_name), ICS_MIME_TYPE, &export_ics(&reminders, &tags()));
+        export_status.set(Some(match result {
+            Ok(Some(path)) => (format!("{} {}", use_t("transfer.exported_to"), path), false),
+            Ok(None) => (use_t("transfer.exported"), false),
+            Err(e) => (format!("{} {}", use_t("transfer.export_failed"), e), true),
+        }));
+    };
+
     rsx! {
         Modal {
             open: open,
@@ -38,18 +77,28 @@ pub fn TagManager(
             close_on_backdrop: true,
             on_close: move |_| on_close.call(()),
             
+            if let Some(error) = save_error() {
+                p {
+                    class: "text-sm text-red-600 mb-2",
+                    role: "alert",
+                    {format!("{} {}", use_t("storage.save_failed"), error)}
+                }
+            }
+
+            if let Some((message, failed)) = export_status() {
+                p {
+                    class: if failed { "text-sm text-red-600 mb-2" } else { "text-sm text-green-700 mb-2" },
+                    role: if failed { "alert" } else { "status" },
+                    {message}
+                }
+            }
+
             if show_form() {
                 TagForm {
                     tag: editing_tag(),
                     on_save: move |tag: Tag| {
-                        let mut updated_tags = tags();
-                        if let Some(existing_index) = updated_tags.iter().position(|t| t.id == tag.id) {
-                            updated_tags[existing_index] = tag;
-                        } else {
-                            updated_tags.push(tag);
-                        }
-                        tags.set(updated_tags.clone());
-                        save_tags(&updated_tags);
+                        let result = TagRepository::new(repository.cloned()).save(tag);
+                        after_change(result.err().map(|e| e.to_string()));
                         editing_tag.set(None);
                         show_form.set(false);
                     },
@@ -90,16 +139,21 @@ pub fn TagManager(
                             for tag in tags().iter() {
                                 TagItem {
                                     tag: tag.clone(),
+                                    other_tags: tags().into_iter().filter(|t| t.id != tag.id).collect::<Vec<_>>(),
                                     delete_confirm_id: delete_confirm_id,
                                     on_edit: move |t: Tag| {
                                         editing_tag.set(Some(t));
                                         show_form.set(true);
                                     },
-                                    on_delete: move |tag_id: String| {
-                                        let mut updated_tags = tags();
-                                        updated_tags.retain(|t| t.id != tag_id);
-                                        tags.set(updated_tags.clone());
-                                        save_tags(&updated_tags);
+                                    on_export: export_tag,
+                                    on_delete: move |(tag_id, reassign_to): (String, Option<String>)| {
+                                        let deletion = match reassign_to {
+                                            Some(target) => TagDeletion::ReassignTo(target),
+                                            None => TagDeletion::RemoveFromReminders,
+                                        };
+                                        let result = TagRepository::new(repository.cloned())
+                                            .delete(&tag_id, deletion, &reminder_repository.read());
+                                        after_change(result.err().map(|e| e.to_string()));
                                         delete_confirm_id.set(None);
                                     },
                                     on_confirm_delete: move |tag_id: String| {
@@ -121,12 +175,30 @@ pub fn TagManager(
 #[component]
 fn TagItem(
     tag: Tag,
+    /// Tags the deleted tag's reminders can be moved to
+    other_tags: Vec<Tag>,
     delete_confirm_id: Signal<Option<String>>,
     on_edit: EventHandler<Tag>,
-    on_delete: EventHandler<String>,
+    /// Export the tag's reminders as an iCalendar file
+    on_export: EventHandler<Tag>,
+    /// Tag id, and the tag its reminders move to (`None` drops it from them)
+    on_delete: EventHandler<(String, Option<String>)>,
     on_confirm_delete: EventHandler<String>,
     on_cancel_delete: EventHandler<()>,
 ) -> Element {
+    let mut reassign_to = use_signal(String::new);
+    let reassign_id = format!("reassign_{}", tag.id);
+    let move_to = use_t("tags.delete_confirm.move_to");
+    let reassign_options: Vec<SelectOption> = std::iter::once(SelectOption {
+        value: String::new(),
+        label: use_t("tags.delete_confirm.remove"),
+    })
+    .chain(other_tags.iter().map(|t| SelectOption {
+        value: t.id.clone(),
+        label: format!("{} {}", move_to, t.name),
+    }))
+    .collect();
+
     rsx! {
         div {
             key: "{tag.id}",
@@ -139,6 +211,7 @@ fn TagItem(
                         {
                             let tag_id = tag.id.clone();
                             let tag_clone = tag.clone();
+                            let tag_export = tag.clone();
                             rsx! {
                                 span {
                                     class: "tag-preview",
@@ -154,6 +227,18 @@ fn TagItem(
                                                 class: "mb-2 text-sm",
                                                 {use_t("tags.delete_confirm.message")}
                                             }
+                                            label {
+                                                class: "text-sm",
+                                                r#for: reassign_id.clone(),
+                                                {use_t("tags.delete_confirm.reminders")}
+                                            }
+                                            Select {
+                                                id: reassign_id.clone(),
+                                                name: "reassign_to".to_string(),
+                                                value: reassign_to(),
+                                                onchange: move |value: String| reassign_to.set(value),
+                                                options: reassign_options.clone(),
+                                            }
                                             div {
                                                 class: "flex gap-2",
                                                 Button {
@@ -165,7 +250,10 @@ fn TagItem(
                                                 Button {
                                                     variant: ButtonVariant::Danger,
                                                     size: ButtonSize::Small,
-                                                    onclick: move |_| on_delete.call(tag_id.clone()),
+                                                    onclick: move |_| {
+                                                        let target = Some(reassign_to()).filter(|t| !t.is_empty());
+                                                        on_delete.call((tag_id.clone(), target));
+                                                    },
                                                     {use_t("tags.delete")}
                                                 }
                                             }
@@ -177,6 +265,13 @@ fn TagItem(
                                             onclick: move |_| on_edit.call(tag_clone.clone()),
                                             {use_t("tags.edit")}
                                         }
+                                        Button {
+                                            variant: ButtonVariant::Ghost,
+                                            size: ButtonSize::Small,
+                                            aria_label: Some(use_t("transfer.calendar.export_tag")),
+                                            onclick: move |_| on_export.call(tag_export.clone()),
+                                            "📅"
+                                        }
                                         Button {
                                             variant: ButtonVariant::Danger,
                                             size: ButtonSize::Small,
@@ -6,7 +6,9 @@
 use dioxus::prelude::*;
 use remind_me_shared::router::Route;
 use remind_me_components::{ReminderApp, MediaCacheProvider, PrivacyPolicyPage, TermsOfUsePage};
+use remind_me_components::storage::use_init_storage;
 use crate::i18n::{use_init_i18n, use_i18n};
+use crate::storage::mobile_storage;
 
 /// Main App component for mobile platforms
 #[component]
@@ -14,6 +16,9 @@ pub fn App() -> Element {
     // Initialize i18n context provider for the entire app
     use_init_i18n();
 
+    // Provide file-backed storage to the app's components
+    use_init_storage(mobile_storage);
+
     // Get access to i18n context
     let _i18n = use_i18n();
 
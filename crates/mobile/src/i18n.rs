@@ -94,13 +94,7 @@ fn save_locale_to_storage(locale: &str) {
 
 /// Get app data directory for mobile platforms
 fn get_app_data_dir() -> Option<std::path::PathBuf> {
-    // For mobile platforms, use a platform-specific data directory
-    // This is a simplified implementation - in production, you'd use
-    // platform-specific APIs (e.g., NSDocumentDirectory on iOS, getFilesDir on Android)
-    
-    // For now, use a relative "data" directory
-    // In production, this should be replaced with platform-specific paths
-    Some(std::path::PathBuf::from("data"))
+    Some(remind_me_shared::storage::app_data_dir())
 }
 
 /// Initialize i18n context provider
@@ -0,0 +1,321 @@
+//! HTTP endpoints of the sync API
+
+use std::sync::Arc;
+
+use axum::extract::rejection::JsonRejection;
+use axum::extract::{Path, State};
+use axum::http::{header, HeaderMap, HeaderValue, Method, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use tower_http::cors::{AllowOrigin, CorsLayer};
+
+use remind_me_shared::models::{Reminder, Tag};
+use remind_me_shared::storage::{
+    Loaded, ReminderRepository, Repository, RepositoryError, StorageError, TagDeletion, TagRepository,
+};
+use remind_me_shared::sync::{ErrorBody, API_PREFIX};
+
+use super::config::{AllowedOrigins, SyncConfig};
+use super::store::Store;
+use super::users::Users;
+
+/// Routes of the sync API under `API_PREFIX`
+///
+/// Cross-origin requests are allowed from `config.allowed_origins`, so a web
+/// app served from elsewhere can sync; requests are authenticated by token,
+/// not cookies.
+pub fn router(config: SyncConfig) -> Router {
+    let cors = cors_layer(&config.allowed_origins);
+    let api = Arc::new(Api {
+        users: config.users,
+        store: Store::new(config.data_dir, config.backend),
+    });
+
+    let routes = Router::new()
+        .route("/reminders", get(list_reminders).post(create_reminder))
+        .route(
+            "/reminders/:id",
+            get(get_reminder).put(update_reminder).delete(delete_reminder),
+        )
+        .route("/tags", get(list_tags).post(create_tag))
+        .route("/tags/:id", get(get_tag).put(update_tag).delete(delete_tag))
+        .with_state(api);
+    let router = Router::new().nest(API_PREFIX, routes);
+    match cors {
+        Some(cors) => router.layer(cors),
+        None => router,
+    }
+}
+
+/// CORS headers for `origins`, or `None` to send none (same origin only)
+fn cors_layer(origins: &AllowedOrigins) -> Option<CorsLayer> {
+    let allow_origin = match origins {
+        AllowedOrigins::SameOrigin => return None,
+        AllowedOrigins::Any => AllowOrigin::any(),
+        AllowedOrigins::List(origins) => {
+            AllowOrigin::list(origins.iter().filter_map(|origin| HeaderValue::from_str(origin).ok()))
+        }
+    };
+    Some(
+        CorsLayer::new()
+            .allow_origin(allow_origin)
+            .allow_methods([Method::GET, Method::POST, Method::PUT, Method::DELETE])
+            .allow_headers([header::AUTHORIZATION, header::CONTENT_TYPE, header::ACCEPT]),
+    )
+}
+
+struct Api {
+    users: Users,
+    store: Store,
+}
+
+impl Api {
+    /// Name of the user the request's token belongs to
+    fn authenticate(&self, headers: &HeaderMap) -> Result<String, ApiError> {
+        headers
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| self.users.authenticate(value))
+            .map(str::to_string)
+            .ok_or_else(|| ApiError::new(StatusCode::UNAUTHORIZED, "missing or unknown token"))
+    }
+}
+
+/// Run `f` on the user's data on a blocking thread
+async fn run<T: Send + 'static>(
+    api: Arc<Api>,
+    user: String,
+    f: impl FnOnce(&Repository) -> Result<T, ApiError> + Send + 'static,
+) -> Result<T, ApiError> {
+    tokio::task::spawn_blocking(move || api.store.with_user(&user, f))
+        .await
+        .map_err(|e| ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+}
+
+async fn list_reminders(State(api): State<Arc<Api>>, headers: HeaderMap) -> Result<Json<Vec<Reminder>>, ApiError> {
+    let user = api.authenticate(&headers)?;
+    let reminders = run(api, user, |repository| Ok(open_reminders(repository)?.all())).await?;
+    Ok(Json(reminders))
+}
+
+async fn get_reminder(
+    State(api): State<Arc<Api>>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> Result<Json<Reminder>, ApiError> {
+    let user = api.authenticate(&headers)?;
+    let reminder = run(api, user, move |repository| {
+        open_reminders(repository)?
+            .get_by_id(&id)
+            .ok_or_else(|| RepositoryError::NotFound(id).into())
+    })
+    .await?;
+    Ok(Json(reminder))
+}
+
+async fn create_reminder(
+    State(api): State<Arc<Api>>,
+    headers: HeaderMap,
+    body: Result<Json<Reminder>, JsonRejection>,
+) -> Result<(StatusCode, Json<Reminder>), ApiError> {
+    let user = api.authenticate(&headers)?;
+    let Json(reminder) = body?;
+    check_id(&reminder.id, None)?;
+    let created = run(api, user, move |repository| {
+        let reminders = open_reminders(repository)?;
+        let id = reminder.id.clone();
+        reminders.create(reminder)?;
+        reminders.get_by_id(&id).ok_or_else(|| RepositoryError::NotFound(id).into())
+    })
+    .await?;
+    Ok((StatusCode::CREATED, Json(created)))
+}
+
+async fn update_reminder(
+    State(api): State<Arc<Api>>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+    body: Result<Json<Reminder>, JsonRejection>,
+) -> Result<Json<Reminder>, ApiError> {
+    let user = api.authenticate(&headers)?;
+    let Json(reminder) = body?;
+    check_id(&reminder.id, Some(&id))?;
+    let updated = run(api, user, move |repository| {
+        let reminders = open_reminders(repository)?;
+        reminders.update(reminder)?;
+        reminders.get_by_id(&id).ok_or_else(|| RepositoryError::NotFound(id).into())
+    })
+    .await?;
+    Ok(Json(updated))
+}
+
+async fn delete_reminder(
+    State(api): State<Arc<Api>>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> Result<StatusCode, ApiError> {
+    let user = api.authenticate(&headers)?;
+    run(api, user, move |repository| {
+        open_reminders(repository)?.delete(&id)?;
+        Ok(())
+    })
+    .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn list_tags(State(api): State<Arc<Api>>, headers: HeaderMap) -> Result<Json<Vec<Tag>>, ApiError> {
+    let user = api.authenticate(&headers)?;
+    let tags = run(api, user, load_tags).await?;
+    Ok(Json(tags))
+}
+
+async fn get_tag(
+    State(api): State<Arc<Api>>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> Result<Json<Tag>, ApiError> {
+    let user = api.authenticate(&headers)?;
+    let tag = run(api, user, move |repository| {
+        load_tags(repository)?
+            .into_iter()
+            .find(|tag| tag.id == id)
+            .ok_or_else(|| RepositoryError::TagNotFound(id).into())
+    })
+    .await?;
+    Ok(Json(tag))
+}
+
+async fn create_tag(
+    State(api): State<Arc<Api>>,
+    headers: HeaderMap,
+    body: Result<Json<Tag>, JsonRejection>,
+) -> Result<(StatusCode, Json<Tag>), ApiError> {
+    let user = api.authenticate(&headers)?;
+    let Json(tag) = body?;
+    check_id(&tag.id, None)?;
+    let created = run(api, user, move |repository| {
+        if load_tags(repository)?.iter().any(|t| t.id == tag.id) {
+            return Err(ApiError::new(
+                StatusCode::CONFLICT,
+                format!("a tag with id \"{}\" already exists", tag.id),
+            ));
+        }
+        TagRepository::new(repository.clone()).save(tag.clone())?;
+        Ok(tag)
+    })
+    .await?;
+    Ok((StatusCode::CREATED, Json(created)))
+}
+
+async fn update_tag(
+    State(api): State<Arc<Api>>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+    body: Result<Json<Tag>, JsonRejection>,
+) -> Result<Json<Tag>, ApiError> {
+    let user = api.authenticate(&headers)?;
+    let Json(tag) = body?;
+    check_id(&tag.id, Some(&id))?;
+    let updated = run(api, user, move |repository| {
+        if !load_tags(repository)?.iter().any(|t| t.id == id) {
+            return Err(RepositoryError::TagNotFound(id).into());
+        }
+        TagRepository::new(repository.clone()).save(tag.clone())?;
+        Ok(tag)
+    })
+    .await?;
+    Ok(Json(updated))
+}
+
+/// Delete a tag, removing it from the reminders using it
+async fn delete_tag(
+    State(api): State<Arc<Api>>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> Result<StatusCode, ApiError> {
+    let user = api.authenticate(&headers)?;
+    run(api, user, move |repository| {
+        let reminders = open_reminders(repository)?;
+        TagRepository::new(repository.clone()).delete(&id, TagDeletion::RemoveFromReminders, &reminders)?;
+        Ok(())
+    })
+    .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// The user's reminders; fails if any couldn't be read, as a partial list
+/// would make clients delete the missing ones
+fn open_reminders(repository: &Repository) -> Result<ReminderRepository, ApiError> {
+    match ReminderRepository::open(repository.clone()) {
+        (reminders, None) => Ok(reminders),
+        (_, Some(error)) => Err(error.into()),
+    }
+}
+
+/// The user's tags; fails if any couldn't be read (see `open_reminders`)
+fn load_tags(repository: &Repository) -> Result<Vec<Tag>, ApiError> {
+    match repository.load_tags() {
+        Loaded { items, error: None } => Ok(items),
+        Loaded { error: Some(error), .. } => Err(error.into()),
+    }
+}
+
+/// Reject an empty id, or one that doesn't match the request path
+fn check_id(id: &str, path_id: Option<&str>) -> Result<(), ApiError> {
+    if id.trim().is_empty() {
+        return Err(ApiError::new(StatusCode::BAD_REQUEST, "the id is empty"));
+    }
+    match path_id {
+        Some(path_id) if path_id != id => Err(ApiError::new(
+            StatusCode::BAD_REQUEST,
+            format!("the id \"{}\" doesn't match the path", id),
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Failed request, answered with an `ErrorBody`
+struct ApiError {
+    status: StatusCode,
+    message: String,
+}
+
+impl ApiError {
+    fn new(status: StatusCode, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            message: message.into(),
+        }
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.status, Json(ErrorBody { error: self.message })).into_response()
+    }
+}
+
+impl From<RepositoryError> for ApiError {
+    fn from(error: RepositoryError) -> Self {
+        let status = match &error {
+            RepositoryError::DuplicateId(_) | RepositoryError::TagNameTaken(_) => StatusCode::CONFLICT,
+            RepositoryError::NotFound(_) | RepositoryError::TagNotFound(_) => StatusCode::NOT_FOUND,
+            RepositoryError::UnknownTag { .. } => StatusCode::UNPROCESSABLE_ENTITY,
+            RepositoryError::Storage(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        ApiError::new(status, error.to_string())
+    }
+}
+
+impl From<StorageError> for ApiError {
+    fn from(error: StorageError) -> Self {
+        ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, error.to_string())
+    }
+}
+
+impl From<JsonRejection> for ApiError {
+    fn from(rejection: JsonRejection) -> Self {
+        ApiError::new(rejection.status(), rejection.body_text())
+    }
+}
@@ -0,0 +1,153 @@
+//! Sync server settings, read from the environment
+
+use std::env;
+use std::fmt;
+use std::path::PathBuf;
+
+use super::users::Users;
+
+/// `name:token` pairs of the users allowed to sync; sync is off when unset
+pub const SYNC_USERS_ENV: &str = "SYNC_USERS";
+/// Directory holding each user's data (default `sync-data`)
+pub const SYNC_DATA_DIR_ENV: &str = "SYNC_DATA_DIR";
+/// Storage backend: `sqlite` (default) or `file`
+pub const SYNC_STORE_ENV: &str = "SYNC_STORE";
+/// Web origins allowed to call the API, separated by commas, or `*` for
+/// any; only same-origin pages may call it when unset
+pub const SYNC_ALLOWED_ORIGINS_ENV: &str = "SYNC_ALLOWED_ORIGINS";
+
+/// How each user's data is stored
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StoreBackend {
+    /// One SQLite database per user (`SqliteStorage`)
+    Sqlite,
+    /// One JSON file per key and user (`FileStorage`)
+    File,
+}
+
+impl StoreBackend {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            StoreBackend::Sqlite => "sqlite",
+            StoreBackend::File => "file",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        [StoreBackend::Sqlite, StoreBackend::File]
+            .into_iter()
+            .find(|backend| backend.as_str() == s.trim().to_lowercase())
+    }
+}
+
+/// Web origins whose pages may call the API (CORS)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AllowedOrigins {
+    /// Only pages served from the API's own origin; no CORS headers are sent
+    SameOrigin,
+    /// Pages from any origin; requests are still authenticated by token
+    Any,
+    /// Pages from these origins, e.g. `https://remind-me.example`
+    List(Vec<String>),
+}
+
+impl AllowedOrigins {
+    /// Read origins separated by commas, or `*` for any
+    ///
+    /// An origin is a scheme, host and optional port, with no path:
+    /// `https://remind-me.example` or `http://localhost:8080`.
+    pub fn parse(spec: &str) -> Result<Self, ConfigError> {
+        let origins: Vec<&str> = spec.split(',').map(str::trim).filter(|origin| !origin.is_empty()).collect();
+        match origins[..] {
+            [] => return Ok(AllowedOrigins::SameOrigin),
+            ["*"] => return Ok(AllowedOrigins::Any),
+            _ => {}
+        }
+        for origin in &origins {
+            let host = origin.strip_prefix("https://").or_else(|| origin.strip_prefix("http://"));
+            let valid = host.is_some_and(|host| {
+                !host.is_empty() && !host.contains('/') && host.chars().all(|c| c.is_ascii_graphic())
+            });
+            if !valid {
+                return Err(ConfigError::InvalidOrigin(origin.to_string()));
+            }
+        }
+        Ok(AllowedOrigins::List(origins.into_iter().map(str::to_string).collect()))
+    }
+}
+
+/// Settings of the sync API
+#[derive(Clone)]
+pub struct SyncConfig {
+    pub users: Users,
+    pub data_dir: PathBuf,
+    pub backend: StoreBackend,
+    pub allowed_origins: AllowedOrigins,
+}
+
+impl SyncConfig {
+    /// Settings from `SYNC_USERS`, `SYNC_DATA_DIR`, `SYNC_STORE` and
+    /// `SYNC_ALLOWED_ORIGINS`, or `None` if `SYNC_USERS` isn't set
+    pub fn from_env() -> Result<Option<Self>, ConfigError> {
+        let Ok(users) = env::var(SYNC_USERS_ENV) else {
+            return Ok(None);
+        };
+        let backend = match env::var(SYNC_STORE_ENV) {
+            Ok(value) => StoreBackend::parse(&value).ok_or(ConfigError::UnknownStore(value))?,
+            Err(_) => StoreBackend::Sqlite,
+        };
+        let allowed_origins = match env::var(SYNC_ALLOWED_ORIGINS_ENV) {
+            Ok(value) => AllowedOrigins::parse(&value)?,
+            Err(_) => AllowedOrigins::SameOrigin,
+        };
+        Ok(Some(SyncConfig {
+            users: Users::parse(&users)?,
+            data_dir: env::var(SYNC_DATA_DIR_ENV)
+                .map(PathBuf::from)
+                .unwrap_or_else(|_| PathBuf::from("sync-data")),
+            backend,
+            allowed_origins,
+        }))
+    }
+}
+
+/// Invalid sync settings
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigError {
+    /// `SYNC_USERS` names no user
+    NoUsers,
+    /// A user entry isn't `name:token`, or the name has characters other
+    /// than ASCII letters, digits, `-` and `_`
+    InvalidUser(String),
+    /// The user has an empty token
+    MissingToken(String),
+    /// The user's name or token is used twice
+    DuplicateUser(String),
+    /// `SYNC_STORE` names no backend
+    UnknownStore(String),
+    /// An entry of `SYNC_ALLOWED_ORIGINS` isn't `*` alone or an origin
+    InvalidOrigin(String),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::NoUsers => write!(f, "{} names no users", SYNC_USERS_ENV),
+            ConfigError::InvalidUser(entry) => {
+                write!(f, "\"{}\" is not a valid user (expected name:token, names use A-Z, a-z, 0-9, - and _)", entry)
+            }
+            ConfigError::MissingToken(name) => write!(f, "user \"{}\" has no token", name),
+            ConfigError::DuplicateUser(name) => write!(f, "user \"{}\" repeats a name or token", name),
+            ConfigError::UnknownStore(value) => {
+                write!(f, "unknown {} \"{}\" (expected sqlite or file)", SYNC_STORE_ENV, value)
+            }
+            ConfigError::InvalidOrigin(origin) => write!(
+                f,
+                "\"{}\" is not a valid origin in {} (expected e.g. https://example.com, or * alone)",
+                origin, SYNC_ALLOWED_ORIGINS_ENV
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
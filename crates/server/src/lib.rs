@@ -0,0 +1,18 @@
+//! Self-hosted sync server for Remind Me
+//!
+//! Serves the sync API described in `remind_me_shared::sync`: per-user CRUD
+//! endpoints for reminders and tags, authenticated with bearer tokens, and
+//! stored with the same backends the apps use (`SqliteStorage` or
+//! `FileStorage`, one directory per user).
+//!
+//! The `sync-server` binary is the only host of `router`; the SSR server
+//! binary doesn't mount it.
+
+pub mod api;
+pub mod config;
+pub mod store;
+pub mod users;
+
+pub use api::router;
+pub use config::{AllowedOrigins, ConfigError, StoreBackend, SyncConfig};
+pub use users::Users;
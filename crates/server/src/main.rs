@@ -0,0 +1,58 @@
+//! Standalone sync server
+//!
+//! Runs only the sync API, e.g. to self-host sync without the web pages or
+//! to try the sync client against a local server:
+//!
+//! ```text
+//! SYNC_USERS=me:change-me cargo run -p remind-me-server
+//! ```
+//!
+//! Environment:
+//! - `SYNC_USERS`: `name:token` pairs separated by commas (required)
+//! - `SYNC_DATA_DIR`: directory holding each user's data (default: `sync-data`)
+//! - `SYNC_STORE`: `sqlite` (default) or `file`
+//! - `SYNC_ALLOWED_ORIGINS`: web origins allowed to call the API, separated by
+//!   commas, or `*` for any (default: none, same-origin pages only)
+//! - `HOST`: bind host (default: `127.0.0.1`)
+//! - `PORT`: bind port (default: `8080`)
+
+use std::net::SocketAddr;
+
+use remind_me_server::{config::SYNC_USERS_ENV, router, SyncConfig};
+
+#[tokio::main]
+async fn main() {
+    let config = match SyncConfig::from_env() {
+        Ok(Some(config)) => config,
+        Ok(None) => {
+            eprintln!("Set {} to name:token pairs to run the sync server", SYNC_USERS_ENV);
+            std::process::exit(2);
+        }
+        Err(error) => {
+            eprintln!("Invalid sync settings: {}", error);
+            std::process::exit(2);
+        }
+    };
+    println!(
+        "Syncing {} user(s), stored with {} in {}",
+        config.users.len(),
+        config.backend.as_str(),
+        config.data_dir.display()
+    );
+
+    let host = std::env::var("HOST").unwrap_or_else(|_| "127.0.0.1".to_string());
+    let port: u16 = std::env::var("PORT")
+        .ok()
+        .and_then(|p| p.parse().ok())
+        .unwrap_or(8080);
+    let addr: SocketAddr = format!("{}:{}", host, port)
+        .parse()
+        .expect("HOST/PORT should form a valid socket address");
+
+    let listener = tokio::net::TcpListener::bind(addr)
+        .await
+        .expect("Failed to bind server");
+
+    println!("Sync server listening on http://{}", addr);
+    axum::serve(listener, router(config)).await.expect("Server crashed");
+}
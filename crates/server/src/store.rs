@@ -0,0 +1,54 @@
+//! Per-user storage of synced data
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+
+use remind_me_shared::storage::{FileStorage, Repository, SqliteStorage, StorageError};
+
+use super::config::StoreBackend;
+
+/// Each user's data, in a directory of their own under `data_dir`
+pub struct Store {
+    data_dir: PathBuf,
+    backend: StoreBackend,
+    /// One lock per user, so each user's requests are applied one at a time
+    locks: Mutex<HashMap<String, Arc<Mutex<()>>>>,
+}
+
+impl Store {
+    pub fn new(data_dir: PathBuf, backend: StoreBackend) -> Self {
+        Self {
+            data_dir,
+            backend,
+            locks: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Run `f` on `user`'s data while holding their lock
+    ///
+    /// The storage is opened for each call, as repositories can't be shared
+    /// between threads. This blocks, so call it off the async runtime.
+    pub fn with_user<T, E: From<StorageError>>(
+        &self,
+        user: &str,
+        f: impl FnOnce(&Repository) -> Result<T, E>,
+    ) -> Result<T, E> {
+        let lock = self
+            .locks
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .entry(user.to_string())
+            .or_default()
+            .clone();
+        let _guard = lock.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+
+        let dir = self.data_dir.join(user);
+        let repository = match self.backend {
+            StoreBackend::Sqlite => Repository::new(Rc::new(SqliteStorage::open(&dir)?)),
+            StoreBackend::File => Repository::new(Rc::new(FileStorage::new(dir))),
+        };
+        f(&repository)
+    }
+}
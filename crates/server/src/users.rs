@@ -0,0 +1,79 @@
+//! Users of the sync API and their tokens
+
+use super::config::ConfigError;
+
+/// A user allowed to sync
+#[derive(Clone)]
+struct User {
+    name: String,
+    token: String,
+}
+
+/// The users allowed to sync, each with a bearer token
+///
+/// Not `Debug`, so tokens don't end up in logs.
+#[derive(Clone)]
+pub struct Users(Vec<User>);
+
+impl Users {
+    /// Read users from `name:token` pairs separated by commas or new lines
+    ///
+    /// Names become directory names, so they may only hold ASCII letters,
+    /// digits, `-` and `_`.
+    pub fn parse(spec: &str) -> Result<Self, ConfigError> {
+        let mut users: Vec<User> = Vec::new();
+        for entry in spec.split([',', '\n']).map(str::trim).filter(|entry| !entry.is_empty()) {
+            let (name, token) = entry
+                .split_once(':')
+                .ok_or_else(|| ConfigError::InvalidUser(entry.to_string()))?;
+            let (name, token) = (name.trim(), token.trim());
+            let valid_name = !name.is_empty()
+                && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+            if !valid_name {
+                return Err(ConfigError::InvalidUser(name.to_string()));
+            }
+            if token.is_empty() {
+                return Err(ConfigError::MissingToken(name.to_string()));
+            }
+            if users.iter().any(|user| user.name == name || user.token == token) {
+                return Err(ConfigError::DuplicateUser(name.to_string()));
+            }
+            users.push(User {
+                name: name.to_string(),
+                token: token.to_string(),
+            });
+        }
+        if users.is_empty() {
+            return Err(ConfigError::NoUsers);
+        }
+        Ok(Users(users))
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Name of the user an `Authorization` header value belongs to
+    pub fn authenticate(&self, authorization: &str) -> Option<&str> {
+        let token = authorization.strip_prefix("Bearer ")?.trim();
+        // Every token is compared in full, so timing doesn't reveal which one was close
+        self.0
+            .iter()
+            .fold(None, |found, user| {
+                if constant_time_eq(&user.token, token) {
+                    Some(user.name.as_str())
+                } else {
+                    found
+                }
+            })
+    }
+}
+
+/// Compare two strings in time that depends only on their lengths
+fn constant_time_eq(a: &str, b: &str) -> bool {
+    a.len() == b.len() && a.bytes().zip(b.bytes()).fold(0, |diff, (x, y)| diff | (x ^ y)) == 0
+}
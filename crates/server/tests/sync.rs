@@ -0,0 +1,321 @@
+//! The sync API on a local port: `SyncClient::sync` over `HttpTransport`, and CORS
+
+use std::future::Future;
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::task::{Context, Poll, Waker};
+
+use serde_json::json;
+
+use remind_me_server::{router, AllowedOrigins, ConfigError, StoreBackend, SyncConfig, Users};
+use remind_me_shared::datetime::Timestamp;
+use remind_me_shared::models::{Reminder, Tag};
+use remind_me_shared::storage::{MemoryStorage, Repository};
+use remind_me_shared::sync::{HttpTransport, SyncClient, SyncError, SyncReport, API_PREFIX};
+
+/// 2024-01-15 00:00:00 UTC
+const JAN_15_MS: i64 = 1_705_276_800_000;
+
+/// Data directory under the system temp dir, removed when dropped
+struct TempDir(PathBuf);
+
+impl TempDir {
+    fn new() -> Self {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let path = std::env::temp_dir().join(format!(
+            "remind-me-server-test-{}-{}",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        Self(path)
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+/// The sync API on a free local port, for `alice` and `bob`
+struct Server {
+    url: String,
+    _data_dir: TempDir,
+}
+
+impl Server {
+    fn start(backend: StoreBackend) -> Self {
+        Self::start_with_origins(backend, AllowedOrigins::SameOrigin)
+    }
+
+    fn start_with_origins(backend: StoreBackend, allowed_origins: AllowedOrigins) -> Self {
+        let data_dir = TempDir::new();
+        let config = SyncConfig {
+            users: Users::parse("alice:alice-token,bob:bob-token").unwrap(),
+            data_dir: data_dir.0.clone(),
+            backend,
+            allowed_origins,
+        };
+        // Bound here, so requests wait in the backlog until the runtime is up
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        listener.set_nonblocking(true).unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            let runtime = tokio::runtime::Runtime::new().unwrap();
+            runtime.block_on(async move {
+                let listener = tokio::net::TcpListener::from_std(listener).unwrap();
+                axum::serve(listener, router(config)).await.unwrap();
+            });
+        });
+        Self {
+            url,
+            _data_dir: data_dir,
+        }
+    }
+
+    fn client(&self, token: &str) -> SyncClient {
+        SyncClient::new(&self.url, token, Rc::new(HttpTransport::new()))
+    }
+}
+
+/// A device syncing as one user, with its own local store
+struct Device {
+    client: SyncClient,
+    repository: Repository,
+}
+
+impl Device {
+    fn new(server: &Server, token: &str) -> Self {
+        Self {
+            client: server.client(token),
+            repository: Repository::new(Rc::new(MemoryStorage::new())),
+        }
+    }
+
+    fn sync(&self) -> SyncReport {
+        block_on(self.client.sync(&self.repository)).unwrap()
+    }
+
+    fn reminders(&self) -> Vec<Reminder> {
+        by_id(self.repository.load_reminders().items, |r| &r.id)
+    }
+
+    fn tags(&self) -> Vec<Tag> {
+        by_id(self.repository.load_tags().items, |t| &t.id)
+    }
+
+    fn server_reminders(&self) -> Vec<Reminder> {
+        by_id(block_on(self.client.reminders()).unwrap(), |r| &r.id)
+    }
+
+    fn server_tags(&self) -> Vec<Tag> {
+        by_id(block_on(self.client.tags()).unwrap(), |t| &t.id)
+    }
+
+    fn titles(&self) -> Vec<String> {
+        self.reminders().into_iter().map(|r| r.title).collect()
+    }
+
+    fn save(&self, reminders: &[Reminder], tags: &[Tag]) {
+        self.repository.save_reminders(reminders).unwrap();
+        self.repository.save_tags(tags).unwrap();
+    }
+}
+
+/// Run a future from `HttpTransport`, which answers before returning it
+fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = std::pin::pin!(future);
+    match future.as_mut().poll(&mut Context::from_waker(Waker::noop())) {
+        Poll::Ready(output) => output,
+        Poll::Pending => panic!("HttpTransport answers at once"),
+    }
+}
+
+fn by_id<T>(mut items: Vec<T>, id: impl Fn(&T) -> &String) -> Vec<T> {
+    items.sort_by(|a, b| id(a).cmp(id(b)));
+    items
+}
+
+fn reminder(id: &str, title: &str, tag_ids: &[&str]) -> Reminder {
+    serde_json::from_value(json!({
+        "id": id,
+        "title": title,
+        "description": "",
+        "completed": false,
+        "created_at": JAN_15_MS,
+        "tag_ids": tag_ids,
+    }))
+    .unwrap()
+}
+
+fn tag(id: &str, name: &str) -> Tag {
+    Tag {
+        id: id.to_string(),
+        name: name.to_string(),
+        color: "#FA8A59".to_string(),
+    }
+}
+
+fn assert_in_sync(devices: &[&Device]) {
+    let server_reminders = devices[0].server_reminders();
+    let server_tags = devices[0].server_tags();
+    for device in devices {
+        assert_eq!(device.reminders(), server_reminders);
+        assert_eq!(device.tags(), server_tags);
+        assert!(device.sync().is_empty());
+    }
+}
+
+#[test]
+fn first_sync_merges_both_devices() {
+    for backend in [StoreBackend::Sqlite, StoreBackend::File] {
+        let server = Server::start(backend);
+        let laptop = Device::new(&server, "alice-token");
+        let phone = Device::new(&server, "alice-token");
+        laptop.save(&[reminder("a", "Laptop", &["work"])], &[tag("work", "Work")]);
+        phone.save(&[reminder("b", "Phone", &["home"])], &[tag("home", "Home")]);
+
+        let report = laptop.sync();
+        assert_eq!(report.sent, 2);
+        let report = phone.sync();
+        assert_eq!((report.sent, report.received), (2, 2));
+        assert_eq!(laptop.sync().received, 2);
+
+        assert_eq!(laptop.titles(), ["Laptop", "Phone"]);
+        assert_eq!(laptop.tags(), [tag("home", "Home"), tag("work", "Work")]);
+        assert_in_sync(&[&laptop, &phone]);
+
+        // Another user's data is their own
+        let bob = Device::new(&server, "bob-token");
+        assert!(bob.sync().is_empty());
+        assert!(bob.server_reminders().is_empty());
+    }
+}
+
+#[test]
+fn edits_on_both_sides_reach_every_device() {
+    let server = Server::start(StoreBackend::Sqlite);
+    let laptop = Device::new(&server, "alice-token");
+    let phone = Device::new(&server, "alice-token");
+    let reminders = [
+        reminder("a", "Laptop edits", &[]),
+        reminder("b", "Phone edits", &[]),
+        reminder("c", "Both edit", &[]),
+    ];
+    laptop.save(&reminders, &[tag("work", "Work")]);
+    laptop.sync();
+    phone.sync();
+
+    let mut laptop_reminders = laptop.reminders();
+    laptop_reminders[0].title = "Laptop edited".to_string();
+    laptop_reminders[2].title = "Laptop's edit".to_string();
+    // Changed last, so it wins over the phone's edit of the same reminder
+    laptop_reminders[2].updated_at = Some(Timestamp::from_millis(Timestamp::now().as_millis() + 3_600_000));
+    laptop.save(&laptop_reminders, &[tag("work", "Work, renamed")]);
+
+    let mut phone_reminders = phone.reminders();
+    phone_reminders[1].completed = true;
+    phone_reminders[2].title = "Phone's edit".to_string();
+    phone.save(&phone_reminders, &phone.tags());
+
+    assert_eq!(phone.sync().sent, 2);
+    let report = laptop.sync();
+    assert_eq!((report.sent, report.received, report.conflicts), (3, 1, 1));
+    phone.sync();
+
+    assert_eq!(phone.titles(), ["Laptop edited", "Phone edits", "Laptop's edit"]);
+    assert!(phone.reminders()[1].completed);
+    assert_eq!(phone.tags(), [tag("work", "Work, renamed")]);
+    assert_in_sync(&[&laptop, &phone]);
+}
+
+#[test]
+fn deletions_on_both_sides_reach_every_device() {
+    let server = Server::start(StoreBackend::File);
+    let laptop = Device::new(&server, "alice-token");
+    let phone = Device::new(&server, "alice-token");
+    laptop.save(
+        &[reminder("a", "A", &[]), reminder("b", "B", &[]), reminder("c", "C", &["old"])],
+        &[tag("old", "Old"), tag("kept", "Kept")],
+    );
+    laptop.sync();
+    phone.sync();
+
+    // The laptop deletes "a"; the phone deletes "b" and the tag "old", with it
+    laptop.save(&[reminder("b", "B", &[]), reminder("c", "C", &["old"])], &laptop.tags());
+    let mut untagged = reminder("c", "C", &[]);
+    untagged.updated_at = Some(Timestamp::now());
+    phone.save(&[reminder("a", "A", &[]), untagged], &[tag("kept", "Kept")]);
+
+    let report = laptop.sync();
+    assert_eq!(report.deleted_remotely, 1);
+    let report = phone.sync();
+    assert_eq!((report.deleted_locally, report.deleted_remotely), (1, 2));
+    let report = laptop.sync();
+    assert_eq!((report.deleted_locally, report.received), (2, 1));
+
+    assert_eq!(laptop.titles(), ["C"]);
+    assert!(laptop.reminders()[0].tag_ids.is_empty());
+    assert_eq!(laptop.tags(), [tag("kept", "Kept")]);
+    assert_in_sync(&[&laptop, &phone]);
+}
+
+#[test]
+fn unknown_tokens_are_refused() {
+    let server = Server::start(StoreBackend::Sqlite);
+    let stranger = Device::new(&server, "not-a-token");
+    stranger.save(&[reminder("a", "A", &[])], &[]);
+    assert!(matches!(block_on(stranger.client.sync(&stranger.repository)), Err(SyncError::Unauthorized)));
+    let alice = Device::new(&server, "alice-token");
+    assert!(alice.server_reminders().is_empty());
+}
+
+/// `Access-Control-Allow-Origin` of a preflight request from `origin`
+fn allowed_origin(server: &Server, origin: &str) -> Option<String> {
+    let response = ureq::request("OPTIONS", &format!("{}{}/reminders", server.url, API_PREFIX))
+        .set("Origin", origin)
+        .set("Access-Control-Request-Method", "PUT")
+        .set("Access-Control-Request-Headers", "authorization")
+        .call()
+        .unwrap_or_else(|error| match error {
+            ureq::Error::Status(_, response) => response,
+            ureq::Error::Transport(error) => panic!("{}", error),
+        });
+    response.header("Access-Control-Allow-Origin").map(str::to_string)
+}
+
+#[test]
+fn cross_origin_requests_follow_the_allowed_origins() {
+    let listed = AllowedOrigins::List(vec!["https://app.example".to_string()]);
+    let server = Server::start_with_origins(StoreBackend::Sqlite, listed);
+    assert_eq!(allowed_origin(&server, "https://app.example").as_deref(), Some("https://app.example"));
+    assert_eq!(allowed_origin(&server, "https://evil.example"), None);
+
+    let server = Server::start_with_origins(StoreBackend::Sqlite, AllowedOrigins::Any);
+    assert_eq!(allowed_origin(&server, "https://evil.example").as_deref(), Some("*"));
+
+    let server = Server::start(StoreBackend::Sqlite);
+    assert_eq!(allowed_origin(&server, "https://app.example"), None);
+}
+
+#[test]
+fn allowed_origins_are_checked() {
+    assert_eq!(AllowedOrigins::parse(" "), Ok(AllowedOrigins::SameOrigin));
+    assert_eq!(AllowedOrigins::parse(" * "), Ok(AllowedOrigins::Any));
+    assert_eq!(
+        AllowedOrigins::parse("https://app.example, http://localhost:8080,"),
+        Ok(AllowedOrigins::List(vec![
+            "https://app.example".to_string(),
+            "http://localhost:8080".to_string(),
+        ]))
+    );
+    for invalid in ["app.example", "https://app.example/", "https://app.example/path", "https://", "*,https://a.example"] {
+        let expected = invalid.split(',').next().unwrap();
+        assert_eq!(
+            AllowedOrigins::parse(invalid),
+            Err(ConfigError::InvalidOrigin(expected.to_string())),
+            "{invalid}"
+        );
+    }
+}
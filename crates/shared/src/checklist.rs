@@ -0,0 +1,99 @@
+//! Checklist items inside a reminder
+//!
+//! A reminder can hold an ordered list of subtasks. When `auto_complete` is
+//! set, finishing the last item completes the reminder itself (or, for a
+//! recurring reminder, rolls it forward to the next occurrence).
+
+use crate::models::{ChecklistItem, Reminder};
+
+/// Create a checklist item with an id unique within `existing`
+pub fn new_item(existing: &[ChecklistItem], text: &str) -> ChecklistItem {
+    let mut n = existing.len() + 1;
+    while existing.iter().any(|item| item.id == format!("item_{}", n)) {
+        n += 1;
+    }
+    ChecklistItem {
+        id: format!("item_{}", n),
+        text: text.trim().to_string(),
+        completed: false,
+    }
+}
+
+/// Toggle the checklist item `item_id` of `reminder`.
+///
+/// Returns `false` if the reminder has no such item.
+pub fn toggle_item(reminder: &mut Reminder, item_id: &str) -> bool {
+    match reminder.checklist.iter_mut().find(|item| item.id == item_id) {
+        Some(item) => {
+            item.completed = !item.completed;
+            true
+        }
+        None => false,
+    }
+}
+
+/// Whether `reminder` should now be completed because its checklist is done
+pub fn should_auto_complete(reminder: &Reminder) -> bool {
+    reminder.auto_complete
+        && !reminder.completed
+        && !reminder.checklist.is_empty()
+        && reminder.checklist.iter().all(|item| item.completed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::reminder;
+
+    fn with_items(texts: &[&str]) -> Reminder {
+        let mut r = reminder("a", "A");
+        for text in texts {
+            let item = new_item(&r.checklist, text);
+            r.checklist.push(item);
+        }
+        r
+    }
+
+    #[test]
+    fn new_items_get_unused_ids() {
+        let mut r = with_items(&["One", "Two", "Three"]);
+        assert_eq!(r.checklist.iter().map(|i| i.id.as_str()).collect::<Vec<_>>(), ["item_1", "item_2", "item_3"]);
+        r.checklist.remove(0);
+        let item = new_item(&r.checklist, "  Four ");
+        assert_eq!(item.id, "item_4");
+        assert_eq!(item.text, "Four");
+        assert!(!item.completed);
+    }
+
+    #[test]
+    fn toggling_flips_only_the_named_item() {
+        let mut r = with_items(&["One", "Two"]);
+        assert!(toggle_item(&mut r, "item_2"));
+        assert_eq!(r.checklist_progress(), (1, 2));
+        assert!(!toggle_item(&mut r, "item_9"));
+        assert_eq!(r.checklist_progress(), (1, 2));
+        assert!(toggle_item(&mut r, "item_2"));
+        assert_eq!(r.checklist_progress(), (0, 2));
+    }
+
+    #[test]
+    fn auto_completes_only_once_every_item_is_done() {
+        let mut empty = reminder("a", "A");
+        empty.auto_complete = true;
+        assert!(!should_auto_complete(&empty));
+
+        let mut r = with_items(&["One", "Two"]);
+        r.auto_complete = true;
+        toggle_item(&mut r, "item_1");
+        assert!(!should_auto_complete(&r));
+        toggle_item(&mut r, "item_2");
+        assert_eq!(r.checklist_progress(), (2, 2));
+        assert!(should_auto_complete(&r));
+
+        r.completed = true;
+        assert!(!should_auto_complete(&r));
+        r.completed = false;
+        r.auto_complete = false;
+        assert!(!should_auto_complete(&r));
+    }
+}
@@ -0,0 +1,385 @@
+//! CSV import and export
+//!
+//! Exported files have the columns `title`, `description`, `due_date`,
+//! `completed` and `tags` (names separated by `;`), quoted as in RFC 4180.
+//!
+//! On import the header row says which column holds what, in any order and
+//! ignoring case; common alternative names such as `name`, `notes`, `due` or
+//! `done` are understood (see `CsvField::from_header`), and other columns
+//! are ignored. Only a title column is required. Files separated by `;`
+//! instead of `,` (as some spreadsheet apps write them) are detected from
+//! the header. A row with a bad date or completion value is reported and
+//! left out; the other rows are still imported.
+
+use std::fmt;
+
+use crate::datetime::LocalDateTime;
+use crate::models::{Reminder, Tag};
+use crate::transfer::{format_due, ImportBuilder, ReminderImport};
+
+/// MIME type of `.csv` files
+pub const CSV_MIME_TYPE: &str = "text/csv";
+
+/// A reminder field a CSV column can hold
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CsvField {
+    Title,
+    Description,
+    DueDate,
+    Completed,
+    Tags,
+}
+
+impl CsvField {
+    /// Columns written on export, in order
+    pub const ALL: [CsvField; 5] = [
+        CsvField::Title,
+        CsvField::Description,
+        CsvField::DueDate,
+        CsvField::Completed,
+        CsvField::Tags,
+    ];
+
+    /// Header written on export
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CsvField::Title => "title",
+            CsvField::Description => "description",
+            CsvField::DueDate => "due_date",
+            CsvField::Completed => "completed",
+            CsvField::Tags => "tags",
+        }
+    }
+
+    /// The field a header names (ignoring case, spaces and `-`/`_`), if any
+    pub fn from_header(header: &str) -> Option<Self> {
+        let header: String = header
+            .chars()
+            .filter(|c| !matches!(c, ' ' | '_' | '-'))
+            .flat_map(char::to_lowercase)
+            .collect();
+        match header.as_str() {
+            "title" | "name" | "summary" | "subject" | "task" => Some(CsvField::Title),
+            "description" | "notes" | "note" | "details" => Some(CsvField::Description),
+            "duedate" | "due" | "date" | "deadline" => Some(CsvField::DueDate),
+            "completed" | "done" | "complete" | "status" => Some(CsvField::Completed),
+            "tags" | "tag" | "labels" | "categories" => Some(CsvField::Tags),
+            _ => None,
+        }
+    }
+}
+
+/// Error that stops a whole CSV file from being read
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CsvError {
+    /// The file has no header row
+    Empty,
+    /// No column is a title column
+    MissingTitle,
+}
+
+impl fmt::Display for CsvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CsvError::Empty => write!(f, "the file is empty"),
+            CsvError::MissingTitle => write!(f, "no title column in the header row"),
+        }
+    }
+}
+
+impl std::error::Error for CsvError {}
+
+/// Write `reminders` as CSV, naming tags from `tags`
+pub fn export_csv(reminders: &[Reminder], tags: &[Tag]) -> String {
+    let mut out = String::new();
+    let header: Vec<&str> = CsvField::ALL.iter().map(CsvField::as_str).collect();
+    push_record(&mut out, &header);
+
+    for reminder in reminders {
+        let tag_names: Vec<&str> = reminder
+            .tag_ids
+            .iter()
+            .filter_map(|id| tags.iter().find(|t| &t.id == id))
+            .map(|t| t.name.as_str())
+            .collect();
+        let due = format_due(reminder).unwrap_or_default();
+        let tag_names = tag_names.join("; ");
+        push_record(
+            &mut out,
+            &[
+                &reminder.title,
+                &reminder.description,
+                &due,
+                if reminder.completed { "true" } else { "false" },
+                &tag_names,
+            ],
+        );
+    }
+    out
+}
+
+/// Read reminders from CSV with a header row
+///
+/// Row numbers in errors count the header as row 1, as spreadsheets do.
+pub fn import_csv(text: &str, tags: &[Tag]) -> Result<ReminderImport, CsvError> {
+    let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+    let mut records = parse_records(text, detect_delimiter(text));
+    if records.is_empty() {
+        return Err(CsvError::Empty);
+    }
+    let columns: Vec<Option<CsvField>> = records.remove(0).iter().map(|h| CsvField::from_header(h)).collect();
+    if !columns.contains(&Some(CsvField::Title)) {
+        return Err(CsvError::MissingTitle);
+    }
+
+    let mut import = ImportBuilder::new(tags);
+    for (index, record) in records.iter().enumerate() {
+        let row = index + 2;
+        if record.iter().all(|cell| cell.trim().is_empty()) {
+            continue;
+        }
+        let cell = |field: CsvField| {
+            columns
+                .iter()
+                .position(|c| *c == Some(field))
+                .and_then(|i| record.get(i))
+                .map(|cell| cell.trim())
+                .unwrap_or_default()
+        };
+
+        let title = cell(CsvField::Title);
+        if title.is_empty() {
+            import.error(row, "the title is empty");
+            continue;
+        }
+        let due_date = match cell(CsvField::DueDate) {
+            "" => None,
+            due => match due.parse::<LocalDateTime>() {
+                Ok(date) => Some(date),
+                Err(e) => {
+                    import.error(row, e.to_string());
+                    continue;
+                }
+            },
+        };
+        let Some(completed) = parse_bool(cell(CsvField::Completed)) else {
+            import.error(row, format!("not a completion value: {:?}", cell(CsvField::Completed)));
+            continue;
+        };
+
+        let mut reminder = import.reminder(title.to_string());
+        reminder.description = cell(CsvField::Description).to_string();
+        reminder.due_date = due_date;
+        reminder.completed = completed;
+        import.add_tags(&mut reminder, cell(CsvField::Tags).split([';', ',']));
+        import.push(reminder);
+    }
+    Ok(import.finish())
+}
+
+/// Read a completion cell; blank means not completed
+fn parse_bool(value: &str) -> Option<bool> {
+    match value.to_lowercase().as_str() {
+        "" | "false" | "no" | "n" | "0" | "todo" | "open" | "active" => Some(false),
+        "true" | "yes" | "y" | "1" | "x" | "done" | "completed" => Some(true),
+        _ => None,
+    }
+}
+
+/// `;` if the header row has semicolons but no commas (outside quotes), else `,`
+fn detect_delimiter(text: &str) -> char {
+    let mut quoted = false;
+    let (mut commas, mut semicolons) = (0, 0);
+    for c in text.chars() {
+        match c {
+            '"' => quoted = !quoted,
+            '\n' | '\r' if !quoted => break,
+            ',' if !quoted => commas += 1,
+            ';' if !quoted => semicolons += 1,
+            _ => {}
+        }
+    }
+    if semicolons > 0 && commas == 0 {
+        ';'
+    } else {
+        ','
+    }
+}
+
+/// Split CSV text into records of fields
+///
+/// Quoted fields may contain the delimiter, line breaks and doubled quotes.
+/// An unterminated quote runs to the end of the file.
+fn parse_records(text: &str, delimiter: char) -> Vec<Vec<String>> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if quoted {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' => quoted = false,
+                _ => field.push(c),
+            }
+            continue;
+        }
+        match c {
+            '"' if field.is_empty() => quoted = true,
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' | '\r' => {
+                record.push(std::mem::take(&mut field));
+                records.push(std::mem::take(&mut record));
+            }
+            c if c == delimiter => record.push(std::mem::take(&mut field)),
+            _ => field.push(c),
+        }
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+    records
+}
+
+/// Append one CRLF-terminated record, quoting fields that need it
+fn push_record(out: &mut String, fields: &[&str]) {
+    for (i, field) in fields.iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        if field.contains([',', '"', '\n', '\r']) || field.starts_with(' ') || field.ends_with(' ') {
+            out.push('"');
+            out.push_str(&field.replace('"', "\"\""));
+            out.push('"');
+        } else {
+            out.push_str(field);
+        }
+    }
+    out.push_str("\r\n");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{datetime, due_reminder, reminder, tag};
+
+    fn import(text: &str, tags: &[Tag]) -> ReminderImport {
+        import_csv(text, tags).unwrap()
+    }
+
+    fn rows(import: &ReminderImport) -> Vec<(usize, &str)> {
+        import.errors.iter().map(|e| (e.row, e.reason.as_str())).collect()
+    }
+
+    #[test]
+    fn fields_that_need_it_are_quoted() {
+        let mut out = String::new();
+        push_record(&mut out, &["plain", "a,b", "say \"hi\"", "two\nlines", " padded", ""]);
+        assert_eq!(out, "plain,\"a,b\",\"say \"\"hi\"\"\",\"two\nlines\",\" padded\",\r\n");
+    }
+
+    #[test]
+    fn quoted_fields_keep_delimiters_quotes_and_line_breaks() {
+        let records = parse_records("a,\"b,c\",\"d \"\"e\"\"\"\r\n\"multi\r\nline\",,x\n\"open, to the end", ',');
+        assert_eq!(
+            records,
+            [
+                vec!["a", "b,c", "d \"e\""],
+                vec!["multi\r\nline", "", "x"],
+                vec!["open, to the end"],
+            ]
+        );
+        assert_eq!(parse_records("a;\"b;c\"\n", ';'), [vec!["a", "b;c"]]);
+    }
+
+    #[test]
+    fn exported_reminders_import_unchanged() {
+        let tags = [tag("work", "Work"), tag("garden", "Home garden")];
+        let mut quoted = due_reminder("quoted", datetime(2024, 1, 31, 9, 0));
+        quoted.time_zone = None;
+        quoted.title = "Call \"Anne\", then Bob".to_string();
+        quoted.description = "First line\nSecond, with comma".to_string();
+        quoted.tag_ids = vec!["work".to_string(), "garden".to_string()];
+        let mut zoned = due_reminder("zoned", datetime(2024, 7, 1, 18, 30));
+        zoned.time_zone = Some(chrono_tz::America::New_York);
+        zoned.completed = true;
+        let reminders = [quoted, zoned, reminder("plain", "Plain")];
+
+        let csv = export_csv(&reminders, &tags);
+        assert!(csv.starts_with("title,description,due_date,completed,tags\r\n"));
+        let imported = import(&csv, &tags);
+        assert!(imported.errors.is_empty(), "{:?}", imported.errors);
+        assert!(imported.new_tags.is_empty());
+        assert_eq!(imported.reminders.len(), 3);
+        for (original, read) in reminders.iter().zip(&imported.reminders) {
+            assert_eq!(read.title, original.title);
+            assert_eq!(read.description, original.description);
+            assert_eq!(read.completed, original.completed);
+            assert_eq!(read.tag_ids, original.tag_ids);
+            // Zoned due dates come back as the same instant
+            assert_eq!(read.due_timestamp(), original.due_timestamp());
+        }
+        assert_eq!(imported.reminders[0].due_date, reminders[0].due_date);
+    }
+
+    #[test]
+    fn headers_are_matched_by_name_in_any_order() {
+        let text = "\u{feff}Done;Extra;Due Date;NAME;Labels\n\
+                    yes;ignored;2024-02-01T10:00;Water plants;Home, garden\n\
+                    ;;;\"Semi; colon\";\n";
+        let imported = import(text, &[tag("home", "home")]);
+        assert!(imported.errors.is_empty());
+        let [plants, semi] = &imported.reminders[..] else {
+            panic!("expected two reminders");
+        };
+        assert_eq!(plants.title, "Water plants");
+        assert!(plants.completed);
+        assert_eq!(plants.due_date, Some(LocalDateTime::floating(datetime(2024, 2, 1, 10, 0))));
+        assert_eq!(plants.tag_ids[0], "home");
+        assert_eq!(imported.new_tags.len(), 1);
+        assert_eq!(imported.new_tags[0].name, "garden");
+        assert_eq!(semi.title, "Semi; colon");
+        assert!(!semi.completed);
+        assert!(semi.description.is_empty());
+
+        for (header, field) in [("Due-date", CsvField::DueDate), ("notes", CsvField::Description), ("Status", CsvField::Completed)] {
+            assert_eq!(CsvField::from_header(header), Some(field));
+        }
+        assert_eq!(CsvField::from_header("priority"), None);
+    }
+
+    #[test]
+    fn bad_rows_are_reported_and_the_rest_imported() {
+        let text = "title,due,done\n\
+                    Good,2024-01-31,no\n\
+                    ,2024-01-31,no\n\
+                    Bad date,someday,no\n\
+                    Bad done,,maybe\n\
+                    \"Two\nlines\",,x\n\
+                    ,,\n\
+                    Short row\n";
+        let imported = import(text, &[]);
+        let titles: Vec<&str> = imported.reminders.iter().map(|r| r.title.as_str()).collect();
+        assert_eq!(titles, ["Good", "Two\nlines", "Short row"]);
+        let errors = rows(&imported);
+        assert_eq!(errors.len(), 3);
+        assert_eq!(errors[0], (3, "the title is empty"));
+        assert_eq!(errors[1].0, 4);
+        assert_eq!(errors[2], (5, "not a completion value: \"maybe\""));
+        assert!(imported.reminders[1].completed);
+    }
+
+    #[test]
+    fn files_without_a_header_or_title_column_are_errors() {
+        assert_eq!(import_csv("", &[]), Err(CsvError::Empty));
+        assert_eq!(import_csv("\u{feff}", &[]), Err(CsvError::Empty));
+        assert_eq!(import_csv("due,done\n2024-01-31,yes\n", &[]), Err(CsvError::MissingTitle));
+        assert_eq!(import("title\n", &[]).reminders, []);
+    }
+}
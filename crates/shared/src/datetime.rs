@@ -0,0 +1,362 @@
+//! Typed date and time values
+//!
+//! Two types cover every date the app stores:
+//!
+//! - [`Timestamp`]: an instant in time (milliseconds since the Unix epoch),
+//!   used for `created_at`, snoozes and other machine-generated times.
+//! - [`LocalDateTime`]: a wall-clock date and time as entered by the user,
+//!   used for due dates. It is "floating" (interpreted in the device's time
+//!   zone) unless it was stored with an explicit UTC offset. Reminders can
+//!   also pin their due date to an IANA time zone (`Reminder::time_zone`).
+//!
+//! Both use chrono on every target. On wasm32 chrono reads the browser's time
+//! zone through `js_sys::Date`, so conversions behave the same in the browser
+//! and on native platforms.
+//!
+//! Values serialize as strings. Parsing accepts RFC 3339 as well as the
+//! `datetime-local` formats written by earlier versions of the app.
+
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::{
+    DateTime, Duration, FixedOffset, Local, NaiveDate, NaiveDateTime, NaiveTime, SecondsFormat, TimeZone, Timelike,
+    Utc,
+};
+use chrono_tz::Tz;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+/// Wall-clock formats accepted in addition to RFC 3339
+const LOCAL_FORMATS: [&str; 3] = ["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%dT%H:%M", "%Y-%m-%d %H:%M"];
+
+/// Format used to display dates to the user
+const DISPLAY_FORMAT: &str = "%Y-%m-%d %H:%M";
+
+/// Error returned when a date string cannot be parsed
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseDateTimeError(String);
+
+impl fmt::Display for ParseDateTimeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid date/time: {:?}", self.0)
+    }
+}
+
+impl std::error::Error for ParseDateTimeError {}
+
+/// The device's IANA time zone, if it can be determined
+pub fn device_time_zone() -> Option<Tz> {
+    iana_time_zone::get_timezone().ok()?.parse().ok()
+}
+
+/// An instant in time, in milliseconds since the Unix epoch
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Timestamp(i64);
+
+impl Timestamp {
+    /// The current time
+    pub fn now() -> Self {
+        Self(Utc::now().timestamp_millis())
+    }
+
+    /// Create a timestamp from milliseconds since the Unix epoch
+    pub fn from_millis(ms: i64) -> Self {
+        Self(ms)
+    }
+
+    /// Milliseconds since the Unix epoch
+    pub fn as_millis(self) -> i64 {
+        self.0
+    }
+
+    /// This instant shifted by `ms` milliseconds (saturating)
+    pub fn add_millis(self, ms: i64) -> Self {
+        Self(self.0.saturating_add(ms))
+    }
+
+    /// This instant as a UTC date-time
+    pub fn to_utc(self) -> DateTime<Utc> {
+        Utc.timestamp_millis_opt(self.0).single().unwrap_or_default()
+    }
+
+    /// Wall-clock time of this instant in the device's time zone
+    pub fn to_local(self) -> LocalDateTime {
+        LocalDateTime::floating(self.to_utc().with_timezone(&Local).naive_local())
+    }
+
+    /// RFC 3339 representation in UTC (e.g. `2024-01-31T09:00:00.000Z`)
+    pub fn to_rfc3339(self) -> String {
+        self.to_utc().to_rfc3339_opts(SecondsFormat::Millis, true)
+    }
+
+    /// Display string in the device's time zone (e.g. `2024-01-31 09:00`)
+    pub fn format_display(self) -> String {
+        self.to_local().naive().format(DISPLAY_FORMAT).to_string()
+    }
+}
+
+impl FromStr for Timestamp {
+    type Err = ParseDateTimeError;
+
+    /// Parse RFC 3339, or a wall-clock time interpreted in the device's time zone
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse::<LocalDateTime>().map(|dt| dt.to_timestamp())
+    }
+}
+
+impl fmt::Display for Timestamp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_rfc3339())
+    }
+}
+
+impl Serialize for Timestamp {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_rfc3339())
+    }
+}
+
+impl<'de> Deserialize<'de> for Timestamp {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Repr {
+            Millis(i64),
+            Text(String),
+        }
+
+        match Repr::deserialize(deserializer)? {
+            Repr::Millis(ms) => Ok(Timestamp(ms)),
+            Repr::Text(s) => s.parse().map_err(de::Error::custom),
+        }
+    }
+}
+
+/// A wall-clock date and time, optionally pinned to a UTC offset
+///
+/// Values entered through a `datetime-local` input are floating: 09:00 means
+/// 09:00 in whatever time zone the device is in. Values stored as RFC 3339
+/// keep their offset and always refer to the same instant.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct LocalDateTime {
+    naive: NaiveDateTime,
+    offset: Option<FixedOffset>,
+}
+
+impl LocalDateTime {
+    /// A floating wall-clock time
+    pub fn floating(naive: NaiveDateTime) -> Self {
+        Self { naive, offset: None }
+    }
+
+    /// A wall-clock time at a fixed UTC offset
+    pub fn with_offset(naive: NaiveDateTime, offset: FixedOffset) -> Self {
+        Self {
+            naive,
+            offset: Some(offset),
+        }
+    }
+
+    /// The current wall-clock time in the device's time zone
+    pub fn now() -> Self {
+        Timestamp::now().to_local()
+    }
+
+    /// The stored wall-clock time
+    pub fn naive(&self) -> NaiveDateTime {
+        self.naive
+    }
+
+    /// The UTC offset, or `None` for floating times
+    pub fn offset(&self) -> Option<FixedOffset> {
+        self.offset
+    }
+
+    /// Same offset handling, different wall-clock time
+    pub fn with_naive(self, naive: NaiveDateTime) -> Self {
+        Self { naive, ..self }
+    }
+
+    /// The instant this refers to, resolving floating times in `tz`.
+    ///
+    /// Ambiguous times (when clocks go back) resolve to the earlier instant;
+    /// times skipped when clocks go forward resolve to one hour later.
+    pub fn to_timestamp_in<Z: TimeZone>(&self, tz: &Z) -> Timestamp {
+        if let Some(offset) = self.offset {
+            return Timestamp((self.naive - offset).and_utc().timestamp_millis());
+        }
+        let resolved = tz
+            .from_local_datetime(&self.naive)
+            .earliest()
+            .or_else(|| tz.from_local_datetime(&(self.naive + Duration::hours(1))).earliest());
+        match resolved {
+            Some(dt) => Timestamp(dt.timestamp_millis()),
+            None => Timestamp(self.naive.and_utc().timestamp_millis()),
+        }
+    }
+
+    /// The instant this refers to, resolving floating times in the device's time zone
+    pub fn to_timestamp(&self) -> Timestamp {
+        self.to_timestamp_in(&Local)
+    }
+
+    /// Wall-clock time in `tz` (floating times are returned unchanged)
+    pub fn wall_clock_in<Z: TimeZone>(&self, tz: &Z) -> NaiveDateTime {
+        match self.offset {
+            Some(_) => self.to_timestamp().to_utc().with_timezone(tz).naive_local(),
+            None => self.naive,
+        }
+    }
+
+    /// Wall-clock time in the device's time zone
+    pub fn to_device_local(&self) -> NaiveDateTime {
+        self.wall_clock_in(&Local)
+    }
+
+    /// Calendar date in the device's time zone
+    pub fn date(&self) -> NaiveDate {
+        self.to_device_local().date()
+    }
+
+    /// Calendar date key (YYYY-MM-DD) in the device's time zone
+    pub fn date_key(&self) -> String {
+        self.date().format("%Y-%m-%d").to_string()
+    }
+
+    /// Value for a `datetime-local` input (e.g. `2024-01-31T09:00`)
+    pub fn to_input_value(&self) -> String {
+        self.to_device_local().format("%Y-%m-%dT%H:%M").to_string()
+    }
+
+    /// Display string in the device's time zone (e.g. `2024-01-31 09:00`)
+    pub fn format_display(&self) -> String {
+        self.to_device_local().format(DISPLAY_FORMAT).to_string()
+    }
+}
+
+impl FromStr for LocalDateTime {
+    type Err = ParseDateTimeError;
+
+    /// Parse RFC 3339 (keeping the offset) or a floating `datetime-local` value
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if let Ok(dt) = DateTime::parse_from_rfc3339(s) {
+            return Ok(Self::with_offset(dt.naive_local(), *dt.offset()));
+        }
+        LOCAL_FORMATS
+            .iter()
+            .find_map(|format| NaiveDateTime::parse_from_str(s, format).ok())
+            .or_else(|| {
+                NaiveDate::parse_from_str(s, "%Y-%m-%d")
+                    .ok()
+                    .map(|date| date.and_time(NaiveTime::MIN))
+            })
+            .map(Self::floating)
+            .ok_or_else(|| ParseDateTimeError(s.to_string()))
+    }
+}
+
+impl fmt::Display for LocalDateTime {
+    /// RFC 3339 for pinned times, `YYYY-MM-DDTHH:MM[:SS]` for floating ones
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.offset {
+            Some(offset) => {
+                let dt = (self.naive - offset).and_utc().with_timezone(&offset);
+                f.write_str(&dt.to_rfc3339_opts(SecondsFormat::AutoSi, false))
+            }
+            None if self.naive.second() == 0 && self.naive.nanosecond() == 0 => {
+                write!(f, "{}", self.naive.format("%Y-%m-%dT%H:%M"))
+            }
+            None => write!(f, "{}", self.naive.format("%Y-%m-%dT%H:%M:%S")),
+        }
+    }
+}
+
+impl Serialize for LocalDateTime {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for LocalDateTime {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?.parse().map_err(de::Error::custom)
+    }
+}
+
+/// Deserialize an optional date stored as a possibly empty string.
+///
+/// Earlier versions stored "no date" as `""`, so empty strings load as
+/// `None`. Anything else must parse: an unreadable date fails the record,
+/// which storage then quarantines instead of saving it back without its date.
+pub fn deserialize_optional<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    match Option::<String>::deserialize(deserializer)? {
+        Some(s) if !s.trim().is_empty() => s.trim().parse().map(Some).map_err(de::Error::custom),
+        _ => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Reminder;
+    use crate::storage::decode_lenient;
+
+    fn reminder_json(due_date: &str) -> String {
+        format!(
+            r#"{{"id":"a","title":"A","description":"","due_date":{},"completed":false,"created_at":0}}"#,
+            due_date
+        )
+    }
+
+    #[test]
+    fn parses_rfc3339_and_legacy_formats() {
+        let floating = NaiveDate::from_ymd_opt(2024, 1, 31).unwrap().and_hms_opt(9, 30, 0).unwrap();
+        for input in ["2024-01-31T09:30", "2024-01-31T09:30:00", "2024-01-31 09:30", " 2024-01-31T09:30 "] {
+            assert_eq!(input.parse::<LocalDateTime>(), Ok(LocalDateTime::floating(floating)), "{}", input);
+        }
+
+        let pinned: LocalDateTime = "2024-01-31T09:30:00+02:00".parse().unwrap();
+        assert_eq!(pinned.naive(), floating);
+        assert_eq!(pinned.offset(), FixedOffset::east_opt(2 * 3600));
+        assert_eq!(pinned.to_string(), "2024-01-31T09:30:00+02:00");
+
+        let midnight: LocalDateTime = "2024-01-31".parse().unwrap();
+        assert_eq!(midnight.to_string(), "2024-01-31T00:00");
+        assert!("31/01/2024".parse::<LocalDateTime>().is_err());
+    }
+
+    #[test]
+    fn timestamps_read_millis_and_strings() {
+        let from_millis: Timestamp = serde_json::from_str("1706693400000").unwrap();
+        let from_text: Timestamp = serde_json::from_str(r#""2024-01-31T09:30:00Z""#).unwrap();
+        assert_eq!(from_millis, from_text);
+        assert_eq!(serde_json::to_string(&from_millis).unwrap(), r#""2024-01-31T09:30:00.000Z""#);
+    }
+
+    #[test]
+    fn empty_optional_dates_load_as_none() {
+        for due_date in ["null", r#""""#, r#""  ""#] {
+            let reminder: Reminder = serde_json::from_str(&reminder_json(due_date)).unwrap();
+            assert_eq!(reminder.due_date, None, "{}", due_date);
+        }
+        let reminder: Reminder = serde_json::from_str(&reminder_json(r#""2024-01-31T09:30""#)).unwrap();
+        assert_eq!(reminder.due_date.map(|d| d.to_string()).as_deref(), Some("2024-01-31T09:30"));
+    }
+
+    #[test]
+    fn unreadable_optional_dates_fail_the_record() {
+        assert!(serde_json::from_str::<Reminder>(&reminder_json(r#""next tuesday""#)).is_err());
+
+        // So a lenient load leaves the record out (to be quarantined) instead of dropping its date
+        let data = format!("[{},{}]", reminder_json(r#""next tuesday""#), reminder_json("null"));
+        let (items, dropped) = decode_lenient::<Reminder>(&data);
+        assert_eq!(items.len(), 1);
+        assert_eq!(dropped, Some(1));
+    }
+}
@@ -0,0 +1,784 @@
+//! iCalendar (RFC 5545) import and export
+//!
+//! Reminders are written as `VTODO` components: summary, description, due
+//! date, status, priority, tags as `CATEGORIES`, recurrence as `RRULE` and
+//! lead times as `VALARM`s. Importing reads both `VTODO` and `VEVENT`
+//! components (an event's start becomes the due date), so files from most
+//! calendar and task apps can be brought in.
+//!
+//! Due dates pinned to a time zone are written with an IANA `TZID`
+//! parameter. No `VTIMEZONE` definitions are written; calendar apps resolve
+//! IANA names themselves. On import, a `TZID` that isn't an IANA name (e.g.,
+//! a Windows zone name) leaves the due date floating in the device's zone.
+
+use std::fmt;
+
+use chrono::{FixedOffset, NaiveDate, NaiveDateTime, NaiveTime};
+use chrono_tz::Tz;
+
+use crate::datetime::{LocalDateTime, Timestamp};
+use crate::models::{LeadTime, Priority, Recurrence, RecurrenceFrequency, Reminder, Tag, Weekday};
+use crate::transfer::{ImportBuilder, ReminderImport};
+
+/// MIME type of `.ics` files
+pub const ICS_MIME_TYPE: &str = "text/calendar";
+
+/// Identifies this app in exported calendars
+const PRODID: &str = "-//Remind Me//Remind Me PWA//EN";
+
+/// Longest content line, in octets, before it is folded
+const MAX_LINE_OCTETS: usize = 75;
+
+/// Basic date-time format (e.g., `20240131T090000`)
+const DATE_TIME_FORMAT: &str = "%Y%m%dT%H%M%S";
+
+/// Basic date format (e.g., `20240131`)
+const DATE_FORMAT: &str = "%Y%m%d";
+
+/// Time of day given to imported all-day items, so they're due during the day
+const ALL_DAY_TIME: NaiveTime = match NaiveTime::from_hms_opt(9, 0, 0) {
+    Some(time) => time,
+    None => NaiveTime::MIN,
+};
+
+/// Error reading an iCalendar file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IcsError {
+    /// The file has no `VCALENDAR`
+    NotCalendar,
+    /// A line (1-based, after unfolding) couldn't be read
+    Malformed { line: usize, reason: String },
+}
+
+impl fmt::Display for IcsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IcsError::NotCalendar => write!(f, "not an iCalendar file"),
+            IcsError::Malformed { line, reason } => write!(f, "line {}: {}", line, reason),
+        }
+    }
+}
+
+impl std::error::Error for IcsError {}
+
+/// Write `reminders` as an iCalendar file, naming tags from `tags`
+pub fn export_ics(reminders: &[Reminder], tags: &[Tag]) -> String {
+    let stamp = format_utc(Timestamp::now());
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        format!("PRODID:{}", PRODID),
+        "CALSCALE:GREGORIAN".to_string(),
+    ];
+
+    for reminder in reminders {
+        lines.push("BEGIN:VTODO".to_string());
+        lines.push(format!("UID:{}", escape_text(&reminder.id)));
+        lines.push(format!("DTSTAMP:{}", stamp));
+        lines.push(format!("CREATED:{}", format_utc(reminder.created_at)));
+        if let Some(updated_at) = reminder.updated_at {
+            lines.push(format!("LAST-MODIFIED:{}", format_utc(updated_at)));
+        }
+        lines.push(format!("SUMMARY:{}", escape_text(&reminder.title)));
+        if !reminder.description.is_empty() {
+            lines.push(format!("DESCRIPTION:{}", escape_text(&reminder.description)));
+        }
+        if let Some(due) = format_due(reminder) {
+            // A rule is anchored on DTSTART, so recurring to-dos get one too
+            if reminder.recurrence.is_some() {
+                lines.push(format!("DTSTART{}", due));
+            }
+            lines.push(format!("DUE{}", due));
+        }
+        if let Some(rule) = &reminder.recurrence {
+            lines.push(format!("RRULE:{}", format_rrule(rule, reminder)));
+        }
+        lines.push(format!("STATUS:{}", if reminder.completed { "COMPLETED" } else { "NEEDS-ACTION" }));
+        if let Some(priority) = priority_to_ics(reminder.priority) {
+            lines.push(format!("PRIORITY:{}", priority));
+        }
+        let categories: Vec<String> = reminder
+            .tag_ids
+            .iter()
+            .filter_map(|id| tags.iter().find(|t| &t.id == id))
+            .map(|t| escape_text(&t.name))
+            .collect();
+        if !categories.is_empty() {
+            lines.push(format!("CATEGORIES:{}", categories.join(",")));
+        }
+        if reminder.due_date.is_some() {
+            for lead_time in &reminder.lead_times {
+                lines.push("BEGIN:VALARM".to_string());
+                lines.push("ACTION:DISPLAY".to_string());
+                lines.push(format!("DESCRIPTION:{}", escape_text(&reminder.title)));
+                lines.push(format!("TRIGGER;RELATED=END:{}", format_lead_time(*lead_time)));
+                lines.push("END:VALARM".to_string());
+            }
+        }
+        lines.push("END:VTODO".to_string());
+    }
+
+    lines.push("END:VCALENDAR".to_string());
+    lines.iter().map(|line| fold(line)).collect()
+}
+
+/// Read the to-dos and events in an iCalendar file
+///
+/// Each reminder keeps the component's `UID` as its id, and categories
+/// become tags. Events without a start, overrides of a single occurrence
+/// (`RECURRENCE-ID`) and repeated `UID`s are reported as row errors, with
+/// the line the component starts on.
+pub fn import_ics(text: &str, tags: &[Tag]) -> Result<ReminderImport, IcsError> {
+    let mut import = ImportBuilder::new(tags);
+    let mut seen_calendar = false;
+    let mut stack: Vec<String> = Vec::new();
+    let mut item: Option<Component> = None;
+
+    let lines = unfold(text);
+    for (index, line) in lines.iter().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let malformed = |reason: &str| IcsError::Malformed {
+            line: index + 1,
+            reason: reason.to_string(),
+        };
+        let property = Property::parse(line).ok_or_else(|| malformed("expected NAME:value"))?;
+
+        match property.name.as_str() {
+            "BEGIN" => {
+                let name = property.value.to_ascii_uppercase();
+                if name == "VCALENDAR" {
+                    seen_calendar = true;
+                } else if (name == "VTODO" || name == "VEVENT") && item.is_none() {
+                    item = Some(Component {
+                        kind: name.clone(),
+                        line: index + 1,
+                        properties: Vec::new(),
+                        alarms: Vec::new(),
+                    });
+                } else if name == "VALARM" {
+                    if let Some(component) = item.as_mut() {
+                        component.alarms.push(Vec::new());
+                    }
+                }
+                stack.push(name);
+            }
+            "END" => {
+                let name = property.value.to_ascii_uppercase();
+                if stack.pop().as_deref() != Some(name.as_str()) {
+                    return Err(malformed("END doesn't match the open component"));
+                }
+                if let Some(component) = item.take_if(|component| component.kind == name) {
+                    match component.to_reminder(&mut import) {
+                        Ok(reminder) if import.contains(&reminder.id) => {
+                            import.error(component.line, format!("repeated UID {}", reminder.id))
+                        }
+                        Ok(reminder) => import.push(reminder),
+                        Err(reason) => import.error(component.line, reason),
+                    }
+                }
+            }
+            _ => {
+                let Some(component) = item.as_mut() else {
+                    continue;
+                };
+                match stack.last().map(String::as_str) {
+                    Some("VALARM") => {
+                        if let Some(alarm) = component.alarms.last_mut() {
+                            alarm.push(property);
+                        }
+                    }
+                    Some(name) if name == component.kind => component.properties.push(property),
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    if !seen_calendar {
+        return Err(IcsError::NotCalendar);
+    }
+    if let Some(open) = stack.last() {
+        return Err(IcsError::Malformed {
+            line: lines.len(),
+            reason: format!("{} is never closed", open),
+        });
+    }
+    Ok(import.finish())
+}
+
+/// A `VTODO` or `VEVENT` being read
+struct Component {
+    kind: String,
+    /// Line of its `BEGIN`
+    line: usize,
+    properties: Vec<Property>,
+    /// Properties of each `VALARM` inside it
+    alarms: Vec<Vec<Property>>,
+}
+
+impl Component {
+    fn get(&self, name: &str) -> Option<&Property> {
+        self.properties.iter().find(|p| p.name == name)
+    }
+
+    /// The reminder this component describes, or why it's left out
+    fn to_reminder(&self, import: &mut ImportBuilder) -> Result<Reminder, &'static str> {
+        if self.get("RECURRENCE-ID").is_some() {
+            return Err("changes a single occurrence of a repeating item");
+        }
+        let is_event = self.kind == "VEVENT";
+        let due_property = if is_event { self.get("DTSTART") } else { self.get("DUE").or(self.get("DTSTART")) };
+        let due = due_property.and_then(parse_due);
+        if is_event && due.is_none() {
+            return Err("event has no readable start");
+        }
+
+        let mut reminder = import.reminder(self.get("SUMMARY").map(|p| unescape_text(&p.value)).unwrap_or_default());
+        if let Some(uid) = self.get("UID").map(|p| unescape_text(&p.value)).filter(|uid| !uid.is_empty()) {
+            reminder.id = uid;
+        }
+        reminder.description = self.get("DESCRIPTION").map(|p| unescape_text(&p.value)).unwrap_or_default();
+        if let Some((due_date, time_zone)) = due {
+            reminder.due_date = Some(due_date);
+            reminder.time_zone = time_zone;
+            reminder.recurrence = self.get("RRULE").and_then(|p| parse_rrule(&p.value));
+            reminder.lead_times = self
+                .alarms
+                .iter()
+                .filter_map(|alarm| alarm.iter().find(|p| p.name == "TRIGGER"))
+                .filter(|trigger| trigger.param("VALUE").is_none_or(|v| v.eq_ignore_ascii_case("DURATION")))
+                .filter_map(|trigger| parse_lead_time(&trigger.value))
+                .collect();
+            reminder.lead_times.sort();
+            reminder.lead_times.dedup();
+        }
+        let status = self.get("STATUS").map(|p| p.value.to_ascii_uppercase());
+        reminder.completed = status.as_deref() == Some("COMPLETED") || (!is_event && self.get("COMPLETED").is_some());
+        if let Some(created_at) = self.get("CREATED").or(self.get("DTSTAMP")).and_then(|p| parse_utc(&p.value)) {
+            reminder.created_at = created_at;
+        }
+        reminder.updated_at = self.get("LAST-MODIFIED").and_then(|p| parse_utc(&p.value));
+        reminder.priority = self
+            .get("PRIORITY")
+            .and_then(|p| p.value.trim().parse().ok())
+            .map(priority_from_ics)
+            .unwrap_or_default();
+
+        let categories: Vec<String> = self
+            .properties
+            .iter()
+            .filter(|p| p.name == "CATEGORIES")
+            .flat_map(|p| split_text_list(&p.value))
+            .collect();
+        import.add_tags(&mut reminder, categories.iter().map(String::as_str));
+        Ok(reminder)
+    }
+}
+
+/// One content line: `NAME;PARAM=value:value`
+#[derive(Debug, Clone)]
+struct Property {
+    /// Upper-cased property name
+    name: String,
+    /// Upper-cased parameter names, with unquoted values
+    params: Vec<(String, String)>,
+    value: String,
+}
+
+impl Property {
+    fn parse(line: &str) -> Option<Self> {
+        // The value starts at the first colon outside a quoted parameter value
+        let mut quoted = false;
+        let colon = line.char_indices().find_map(|(i, c)| match c {
+            '"' => {
+                quoted = !quoted;
+                None
+            }
+            ':' if !quoted => Some(i),
+            _ => None,
+        })?;
+        let (head, value) = (&line[..colon], &line[colon + 1..]);
+
+        let mut parts = Vec::new();
+        let mut start = 0;
+        let mut quoted = false;
+        for (i, c) in head.char_indices() {
+            match c {
+                '"' => quoted = !quoted,
+                ';' if !quoted => {
+                    parts.push(&head[start..i]);
+                    start = i + 1;
+                }
+                _ => {}
+            }
+        }
+        parts.push(&head[start..]);
+
+        let name = parts.first()?.trim().to_ascii_uppercase();
+        if name.is_empty() {
+            return None;
+        }
+        let params = parts[1..]
+            .iter()
+            .filter_map(|param| param.split_once('='))
+            .map(|(key, value)| (key.trim().to_ascii_uppercase(), value.trim().trim_matches('"').to_string()))
+            .collect();
+        Some(Self {
+            name,
+            params,
+            value: value.to_string(),
+        })
+    }
+
+    fn param(&self, name: &str) -> Option<&str> {
+        self.params.iter().find(|(key, _)| key == name).map(|(_, value)| value.as_str())
+    }
+}
+
+/// Join folded lines (a line break followed by a space or tab continues the line)
+fn unfold(text: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in text.split('\n') {
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(continuation), Some(last)) => last.push_str(continuation),
+            _ => lines.push(line.to_string()),
+        }
+    }
+    lines
+}
+
+/// Fold a content line into lines of at most `MAX_LINE_OCTETS`, each ending in CRLF
+///
+/// Lines are only broken between characters, never inside a UTF-8 sequence.
+fn fold(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len() + 8);
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > MAX_LINE_OCTETS {
+            folded.push_str("\r\n ");
+            width = 1;
+        }
+        folded.push(c);
+        width += c.len_utf8();
+    }
+    folded.push_str("\r\n");
+    folded
+}
+
+/// Escape a TEXT value
+fn escape_text(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.replace("\r\n", "\n").chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            ';' => escaped.push_str("\\;"),
+            ',' => escaped.push_str("\\,"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Unescape a TEXT value
+fn unescape_text(text: &str) -> String {
+    split_text_list(text).join(",")
+}
+
+/// Split a comma-separated TEXT list (e.g., `CATEGORIES`), unescaping each item
+fn split_text_list(text: &str) -> Vec<String> {
+    let mut items = Vec::new();
+    let mut current = String::new();
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some('n' | 'N') => current.push('\n'),
+                Some(escaped) => current.push(escaped),
+                None => current.push('\\'),
+            },
+            ',' => items.push(std::mem::take(&mut current)),
+            _ => current.push(c),
+        }
+    }
+    items.push(current);
+    items
+}
+
+/// A UTC date-time value (e.g., `20240131T080000Z`)
+fn format_utc(timestamp: Timestamp) -> String {
+    format!("{}Z", timestamp.to_utc().format(DATE_TIME_FORMAT))
+}
+
+/// Read a UTC (or floating, taken as device-local) date-time value
+fn parse_utc(value: &str) -> Option<Timestamp> {
+    let value = value.trim();
+    match value.strip_suffix(['Z', 'z']) {
+        Some(utc) => NaiveDateTime::parse_from_str(utc, DATE_TIME_FORMAT)
+            .ok()
+            .map(|naive| Timestamp::from_millis(naive.and_utc().timestamp_millis())),
+        None => NaiveDateTime::parse_from_str(value, DATE_TIME_FORMAT)
+            .ok()
+            .map(|naive| LocalDateTime::floating(naive).to_timestamp()),
+    }
+}
+
+/// Parameters and value of a reminder's due date (e.g., `;TZID=Europe/Paris:20240131T090000`)
+///
+/// Zoned due dates keep their zone, pinned ones are written in UTC and
+/// floating ones stay floating.
+fn format_due(reminder: &Reminder) -> Option<String> {
+    let due = reminder.due_date?;
+    Some(match (reminder.time_zone, due.offset()) {
+        (Some(tz), _) => format!(";TZID={}:{}", tz.name(), due.wall_clock_in(&tz).format(DATE_TIME_FORMAT)),
+        (None, Some(_)) => format!(":{}", format_utc(due.to_timestamp())),
+        (None, None) => format!(":{}", due.naive().format(DATE_TIME_FORMAT)),
+    })
+}
+
+/// Read a due date, with the IANA zone named by its `TZID` if any
+fn parse_due(property: &Property) -> Option<(LocalDateTime, Option<Tz>)> {
+    let value = property.value.trim();
+    if let Ok(date) = NaiveDate::parse_from_str(value, DATE_FORMAT) {
+        return Some((LocalDateTime::floating(date.and_time(ALL_DAY_TIME)), None));
+    }
+    if let Some(utc) = value.strip_suffix(['Z', 'z']) {
+        let naive = NaiveDateTime::parse_from_str(utc, DATE_TIME_FORMAT).ok()?;
+        return Some((LocalDateTime::with_offset(naive, FixedOffset::east_opt(0)?), None));
+    }
+    let naive = NaiveDateTime::parse_from_str(value, DATE_TIME_FORMAT).ok()?;
+    Some((LocalDateTime::floating(naive), property.param("TZID").and_then(resolve_tzid)))
+}
+
+/// The IANA zone a `TZID` names, also accepting prefixed forms such as
+/// `/mozilla.org/20050126_1/Europe/Berlin`
+fn resolve_tzid(tzid: &str) -> Option<Tz> {
+    tzid.parse()
+        .ok()
+        .or_else(|| tzid.match_indices('/').find_map(|(i, _)| tzid[i + 1..].parse().ok()))
+}
+
+/// `RRULE` value for `rule` (e.g., `FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,WE`)
+fn format_rrule(rule: &Recurrence, reminder: &Reminder) -> String {
+    let mut parts = vec![format!("FREQ={}", rule.frequency.as_str().to_ascii_uppercase())];
+    if rule.interval > 1 {
+        parts.push(format!("INTERVAL={}", rule.interval));
+    }
+    if !rule.by_weekday.is_empty() {
+        let days: Vec<&str> = rule.by_weekday.iter().map(Weekday::as_str).collect();
+        parts.push(format!("BYDAY={}", days.join(",")));
+    }
+    if let Some(count) = rule.count {
+        parts.push(format!("COUNT={}", count));
+    }
+    if let Some(until) = rule.until {
+        // UNTIL must match DTSTART's form: UTC for zoned and pinned starts,
+        // floating otherwise. The last day is included up to its end.
+        let end_of_day = until.and_hms_opt(23, 59, 59).unwrap_or_else(|| until.and_time(NaiveTime::MIN));
+        let value = match (reminder.time_zone, reminder.due_date.and_then(|d| d.offset())) {
+            (Some(tz), _) => format_utc(LocalDateTime::floating(end_of_day).to_timestamp_in(&tz)),
+            (None, Some(offset)) => format_utc(LocalDateTime::with_offset(end_of_day, offset).to_timestamp()),
+            (None, None) => end_of_day.format(DATE_TIME_FORMAT).to_string(),
+        };
+        parts.push(format!("UNTIL={}", value));
+    }
+    parts.join(";")
+}
+
+/// Read an `RRULE`; rules this app can't represent (e.g., hourly) are dropped
+///
+/// Parts without an equivalent (such as `BYMONTHDAY`) are ignored, and
+/// `BYDAY` is only kept for daily and weekly rules.
+fn parse_rrule(value: &str) -> Option<Recurrence> {
+    let mut frequency = None;
+    let mut rule = Recurrence::new(RecurrenceFrequency::Daily);
+    let mut by_weekday = Vec::new();
+    for part in value.trim().split(';') {
+        let Some((key, value)) = part.split_once('=') else {
+            continue;
+        };
+        match key.trim().to_ascii_uppercase().as_str() {
+            "FREQ" => frequency = RecurrenceFrequency::parse(&value.trim().to_ascii_lowercase()),
+            "INTERVAL" => rule.interval = value.trim().parse().unwrap_or(1).max(1),
+            "COUNT" => rule.count = value.trim().parse().ok(),
+            "UNTIL" => rule.until = value.trim().get(..8).and_then(|d| NaiveDate::parse_from_str(d, DATE_FORMAT).ok()),
+            "BYDAY" => by_weekday = value.split(',').filter_map(|day| Weekday::parse(day.trim())).collect(),
+            _ => {}
+        }
+    }
+    rule.frequency = frequency?;
+    if matches!(rule.frequency, RecurrenceFrequency::Daily | RecurrenceFrequency::Weekly) {
+        rule.by_weekday = by_weekday;
+    }
+    Some(rule)
+}
+
+/// RFC 5545 priority (1 highest, 9 lowest), or `None` for no priority
+fn priority_to_ics(priority: Priority) -> Option<u8> {
+    match priority {
+        Priority::None => None,
+        Priority::Urgent => Some(1),
+        Priority::High => Some(3),
+        Priority::Medium => Some(5),
+        Priority::Low => Some(9),
+    }
+}
+
+fn priority_from_ics(priority: u8) -> Priority {
+    match priority {
+        1 => Priority::Urgent,
+        2..=4 => Priority::High,
+        5 => Priority::Medium,
+        6..=9 => Priority::Low,
+        _ => Priority::None,
+    }
+}
+
+/// Alarm trigger relative to the due date (e.g., `-PT10M`)
+fn format_lead_time(lead_time: LeadTime) -> String {
+    match lead_time.minutes {
+        0 => "PT0S".to_string(),
+        minutes if minutes % (24 * 60) == 0 => format!("-P{}D", minutes / (24 * 60)),
+        minutes => format!("-PT{}M", minutes),
+    }
+}
+
+/// Read an alarm trigger duration; alarms after the due date are dropped
+fn parse_lead_time(value: &str) -> Option<LeadTime> {
+    let value = value.trim();
+    let (negative, duration) = match value.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, value.strip_prefix('+').unwrap_or(value)),
+    };
+    let duration = duration.strip_prefix(['P', 'p'])?;
+
+    let mut seconds: u64 = 0;
+    let mut number = String::new();
+    let mut in_time = false;
+    for c in duration.chars() {
+        match c.to_ascii_uppercase() {
+            'T' => in_time = true,
+            digit @ '0'..='9' => number.push(digit),
+            unit => {
+                let n: u64 = number.parse().ok()?;
+                number.clear();
+                seconds += n * match (unit, in_time) {
+                    ('W', false) => 7 * 86_400,
+                    ('D', false) => 86_400,
+                    ('H', true) => 3_600,
+                    ('M', true) => 60,
+                    ('S', true) => 1,
+                    _ => return None,
+                };
+            }
+        }
+    }
+    if !number.is_empty() || (!negative && seconds > 0) {
+        return None;
+    }
+    Some(LeadTime::from_minutes(u32::try_from(seconds / 60).ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{datetime, due_reminder, reminder, tag, JAN_15_MS};
+
+    fn import(text: &str, tags: &[Tag]) -> ReminderImport {
+        import_ics(text, tags).unwrap()
+    }
+
+    /// A calendar holding `components`, with CRLF line ends
+    fn calendar(components: &str) -> String {
+        format!("BEGIN:VCALENDAR\nVERSION:2.0\n{}END:VCALENDAR\n", components).replace('\n', "\r\n")
+    }
+
+    #[test]
+    fn long_lines_fold_at_75_octets_without_splitting_characters() {
+        let line = format!("DESCRIPTION:{}", "日本語のテキスト, with ASCII; ".repeat(10));
+        let folded = fold(&line);
+
+        assert!(folded.ends_with("\r\n"));
+        let physical: Vec<&str> = folded.trim_end_matches("\r\n").split("\r\n").collect();
+        assert!(physical.len() > 1);
+        assert!(physical.iter().all(|l| l.len() <= MAX_LINE_OCTETS), "{physical:?}");
+        assert!(physical[1..].iter().all(|l| l.starts_with(' ')));
+        assert_eq!(unfold(&folded), [line, String::new()]);
+
+        // Short lines are left alone; tabs continue a line too, and bare LF works
+        assert_eq!(fold("SUMMARY:Short"), "SUMMARY:Short\r\n");
+        assert_eq!(unfold("SUMMARY:Split\n\t across\nUID:1"), ["SUMMARY:Split across", "UID:1"]);
+    }
+
+    #[test]
+    fn text_values_escape_and_unescape() {
+        let text = "Back\\slash; semi, comma\r\nnew line\nend";
+        let escaped = escape_text(text);
+        assert_eq!(escaped, r"Back\\slash\; semi\, comma\nnew line\nend");
+        assert_eq!(unescape_text(&escaped), "Back\\slash; semi, comma\nnew line\nend");
+        assert_eq!(unescape_text("Upper\\Ncase and trailing\\"), "Upper\ncase and trailing\\");
+        assert_eq!(split_text_list("Work,Home\\, garden,"), ["Work", "Home, garden", ""]);
+    }
+
+    #[test]
+    fn exported_reminders_import_unchanged() {
+        let tags = [tag("work", "Work"), tag("garden", "Home, garden")];
+        let mut zoned = due_reminder("zoned@example.com", datetime(2024, 3, 31, 9, 30));
+        zoned.title = "Call Anne; bring notes, slides".to_string();
+        zoned.description = format!("Line one\nLine two with a \\ backslash. {}", "Long text ".repeat(12));
+        zoned.time_zone = Some(chrono_tz::Europe::Paris);
+        zoned.updated_at = Some(Timestamp::from_millis(JAN_15_MS + 3_600_000));
+        zoned.priority = Priority::High;
+        zoned.tag_ids = vec!["garden".to_string(), "work".to_string()];
+        zoned.lead_times = vec![LeadTime::AT_DUE, LeadTime::from_minutes(10), LeadTime::from_minutes(24 * 60)];
+        zoned.recurrence = Some(Recurrence {
+            interval: 2,
+            by_weekday: vec![Weekday::Mo, Weekday::We],
+            count: Some(5),
+            ..Recurrence::new(RecurrenceFrequency::Weekly)
+        });
+
+        let mut floating = due_reminder("floating", datetime(2024, 1, 20, 18, 0));
+        floating.time_zone = None;
+        floating.completed = true;
+        floating.priority = Priority::Urgent;
+        floating.recurrence = Some(Recurrence {
+            until: NaiveDate::from_ymd_opt(2024, 2, 29),
+            ..Recurrence::new(RecurrenceFrequency::Monthly)
+        });
+
+        let mut pinned = reminder("pinned", "Pinned");
+        let offset = FixedOffset::east_opt(0).unwrap();
+        pinned.due_date = Some(LocalDateTime::with_offset(datetime(2024, 1, 16, 7, 45), offset));
+        pinned.priority = Priority::Low;
+
+        let undated = Reminder { priority: Priority::Medium, ..reminder("undated", "No date") };
+
+        let reminders = vec![zoned, floating, pinned, undated];
+        let ics = export_ics(&reminders, &tags);
+        assert!(ics.lines().all(|line| line.len() <= MAX_LINE_OCTETS + 1));
+        assert!(ics.contains("DUE;TZID=Europe/Paris:20240331T093000\r\n"));
+        assert!(ics.contains("CATEGORIES:Home\\, garden,Work\r\n"));
+
+        let imported = import(&ics, &tags);
+        assert!(imported.errors.is_empty(), "{:?}", imported.errors);
+        assert!(imported.new_tags.is_empty());
+        assert_eq!(imported.reminders, reminders);
+    }
+
+    #[test]
+    fn categories_without_a_matching_tag_become_new_tags() {
+        let ics = calendar("BEGIN:VTODO\nUID:1\nSUMMARY:Tagged\nCATEGORIES:work,New tag\nCATEGORIES:new-tag\nEND:VTODO\n");
+        let imported = import(&ics, &[tag("work", "Work")]);
+        assert_eq!(imported.new_tags.len(), 1);
+        assert_eq!(imported.new_tags[0].name, "New tag");
+        assert_eq!(imported.reminders[0].tag_ids, ["work".to_string(), imported.new_tags[0].id.clone()]);
+    }
+
+    #[test]
+    fn events_all_day_dates_and_time_zone_ids_are_read() {
+        let ics = calendar(
+            "BEGIN:VEVENT\nUID:day\nSUMMARY:All day\nDTSTART;VALUE=DATE:20240202\nEND:VEVENT\n\
+             BEGIN:VEVENT\nUID:windows\nSUMMARY:Windows zone\nDTSTART;TZID=W. Europe Standard Time:20240202T100000\nEND:VEVENT\n\
+             BEGIN:VEVENT\nUID:mozilla\nSUMMARY:Prefixed zone\nDTSTART;TZID=\"/mozilla.org/20050126_1/Europe/Berlin\":20240202T100000\nEND:VEVENT\n\
+             BEGIN:VTODO\nUID:done\nSUMMARY:Done\nCOMPLETED:20240101T000000Z\nEND:VTODO\n",
+        );
+        let imported = import(&ics, &[]);
+        assert!(imported.errors.is_empty());
+        let [day, windows, mozilla, done] = &imported.reminders[..] else {
+            panic!("expected four reminders");
+        };
+        assert_eq!(day.due_date, Some(LocalDateTime::floating(datetime(2024, 2, 2, 9, 0))));
+        assert_eq!(windows.due_date, Some(LocalDateTime::floating(datetime(2024, 2, 2, 10, 0))));
+        assert_eq!(windows.time_zone, None);
+        assert_eq!(mozilla.time_zone, Some(chrono_tz::Europe::Berlin));
+        assert!(done.completed);
+        assert_eq!(done.due_date, None);
+    }
+
+    #[test]
+    fn alarms_become_lead_times_and_those_after_the_due_date_are_dropped() {
+        let ics = calendar(
+            "BEGIN:VTODO\nUID:1\nSUMMARY:Alarms\nDUE:20240131T090000\n\
+             BEGIN:VALARM\nTRIGGER:-PT1H30M\nEND:VALARM\n\
+             BEGIN:VALARM\nTRIGGER;RELATED=END:-P1W\nEND:VALARM\n\
+             BEGIN:VALARM\nTRIGGER:PT5M\nEND:VALARM\n\
+             BEGIN:VALARM\nTRIGGER;VALUE=DATE-TIME:20240131T080000Z\nEND:VALARM\n\
+             BEGIN:VALARM\nTRIGGER:-PT90M\nEND:VALARM\n\
+             END:VTODO\n",
+        );
+        let imported = import(&ics, &[]);
+        assert_eq!(imported.reminders[0].lead_times, [LeadTime::from_minutes(90), LeadTime::from_minutes(7 * 24 * 60)]);
+
+        assert_eq!(parse_lead_time("PT0S"), Some(LeadTime::AT_DUE));
+        assert_eq!(parse_lead_time("-P1DT2H"), Some(LeadTime::from_minutes(26 * 60)));
+        assert_eq!(parse_lead_time("-PT10"), None);
+        assert_eq!(parse_lead_time("-P1H"), None);
+        assert_eq!(format_lead_time(LeadTime::from_minutes(2 * 24 * 60)), "-P2D");
+    }
+
+    #[test]
+    fn components_that_cant_be_imported_are_row_errors() {
+        let ics = calendar(
+            "BEGIN:VEVENT\nUID:no-start\nSUMMARY:No start\nEND:VEVENT\n\
+             BEGIN:VTODO\nUID:kept\nSUMMARY:Kept\nEND:VTODO\n\
+             BEGIN:VTODO\nUID:kept\nSUMMARY:Again\nEND:VTODO\n\
+             BEGIN:VEVENT\nUID:kept\nRECURRENCE-ID:20240201T090000\nDTSTART:20240201T100000\nEND:VEVENT\n",
+        );
+        let imported = import(&ics, &[]);
+        assert_eq!(imported.reminders.len(), 1);
+        assert_eq!(imported.reminders[0].title, "Kept");
+        let rows: Vec<(usize, &str)> = imported.errors.iter().map(|e| (e.row, e.reason.as_str())).collect();
+        assert_eq!(
+            rows,
+            [
+                (3, "event has no readable start"),
+                (11, "repeated UID kept"),
+                (15, "changes a single occurrence of a repeating item"),
+            ]
+        );
+    }
+
+    #[test]
+    fn files_that_cant_be_read_are_errors() {
+        assert_eq!(import_ics("SUMMARY:Nothing\r\n", &[]), Err(IcsError::NotCalendar));
+        assert_eq!(import_ics("", &[]), Err(IcsError::NotCalendar));
+        assert_eq!(
+            import_ics(&calendar("BEGIN:VTODO\nno colon here\nEND:VTODO\n"), &[]),
+            Err(IcsError::Malformed { line: 4, reason: "expected NAME:value".to_string() })
+        );
+        assert!(matches!(
+            import_ics(&calendar("BEGIN:VTODO\nEND:VEVENT\n"), &[]),
+            Err(IcsError::Malformed { line: 4, .. })
+        ));
+        assert!(matches!(
+            import_ics("BEGIN:VCALENDAR\r\nBEGIN:VTODO\r\n", &[]),
+            Err(IcsError::Malformed { reason, .. }) if reason == "VTODO is never closed"
+        ));
+    }
+
+    #[test]
+    fn rules_priorities_and_values_map_to_the_app() {
+        assert_eq!(parse_rrule("FREQ=HOURLY"), None);
+        let rule = parse_rrule("freq=monthly;interval=0;BYDAY=MO;BYMONTHDAY=3;UNTIL=20241231T235959Z").unwrap();
+        assert_eq!(rule.frequency, RecurrenceFrequency::Monthly);
+        assert_eq!(rule.interval, 1);
+        assert!(rule.by_weekday.is_empty());
+        assert_eq!(rule.until, NaiveDate::from_ymd_opt(2024, 12, 31));
+
+        for (ics, priority) in [(0, Priority::None), (2, Priority::High), (5, Priority::Medium), (7, Priority::Low)] {
+            assert_eq!(priority_from_ics(ics), priority);
+        }
+
+        let property = Property::parse("X-NOTE;LANG=\"en:us\";ALTREP=\"cid:a;b\":value: with colon").unwrap();
+        assert_eq!(property.name, "X-NOTE");
+        assert_eq!(property.param("LANG"), Some("en:us"));
+        assert_eq!(property.param("ALTREP"), Some("cid:a;b"));
+        assert_eq!(property.value, "value: with colon");
+        assert!(Property::parse(":no name").is_none());
+    }
+}
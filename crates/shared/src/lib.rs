@@ -2,9 +2,24 @@
 //! 
 //! This crate contains business logic, models, and utilities that work on all platforms.
 
+pub mod datetime;
 pub mod models;
+pub mod checklist;
+pub mod recurrence;
+pub mod notifications;
+pub mod snooze;
+pub mod query;
+pub mod ical;
+pub mod csv;
+pub mod markdown;
+pub mod todotxt;
+pub mod transfer;
 pub mod storage;
+pub mod sync;
 pub mod utils;
 pub mod i18n;
 pub mod router;
 
+#[cfg(test)]
+mod test_support;
+
@@ -0,0 +1,264 @@
+//! Markdown task list import and export
+//!
+//! Each reminder is a GitHub-flavoured Markdown task, with its due date and
+//! tags written inline:
+//!
+//! ```text
+//! - [ ] Renew passport @due(2024-01-31T09:00) #errands #travel
+//!   Bring the old passport and two photos.
+//!   - [x] Take photos
+//!   - [ ] Fill in the form
+//! ```
+//!
+//! Lines indented under a task are its description, and nested tasks are its
+//! checklist. Tag names are written with `-` for spaces. On import, other
+//! lines (headings, paragraphs, plain list items) are ignored, and a task
+//! with an unreadable `@due(…)` is reported and left out.
+
+use crate::checklist::new_item;
+use crate::datetime::LocalDateTime;
+use crate::models::{Reminder, Tag};
+use crate::transfer::{format_due, ImportBuilder, ReminderImport};
+
+/// MIME type of `.md` files
+pub const MARKDOWN_MIME_TYPE: &str = "text/markdown";
+
+/// Indentation of description lines and checklist items
+const INDENT: &str = "  ";
+
+/// Write `reminders` as a Markdown task list, naming tags from `tags`
+pub fn export_markdown(reminders: &[Reminder], tags: &[Tag]) -> String {
+    let mut out = String::new();
+    for reminder in reminders {
+        let mut line = format!("- [{}] {}", if reminder.completed { "x" } else { " " }, reminder.title.trim());
+        if let Some(due) = format_due(reminder) {
+            line.push_str(&format!(" @due({})", due));
+        }
+        for tag in reminder.tag_ids.iter().filter_map(|id| tags.iter().find(|t| &t.id == id)) {
+            line.push_str(" #");
+            line.push_str(&tag.name.split_whitespace().collect::<Vec<_>>().join("-"));
+        }
+        out.push_str(&line);
+        out.push('\n');
+
+        for description_line in reminder.description.lines() {
+            if !description_line.trim().is_empty() {
+                out.push_str(INDENT);
+                out.push_str(description_line);
+            }
+            out.push('\n');
+        }
+        for item in &reminder.checklist {
+            out.push_str(&format!("{}- [{}] {}\n", INDENT, if item.completed { "x" } else { " " }, item.text));
+        }
+    }
+    out
+}
+
+/// Read the tasks in a Markdown document
+///
+/// Row numbers in errors are line numbers.
+pub fn import_markdown(text: &str, tags: &[Tag]) -> ReminderImport {
+    let mut import = ImportBuilder::new(tags);
+    // The task being read, with its indentation; `None` inside a left-out task
+    let mut current: Option<(Reminder, usize)> = None;
+    let mut skipping: Option<usize> = None;
+    let mut blank_lines = 0;
+
+    for (index, line) in text.lines().enumerate() {
+        let indent = line.len() - line.trim_start().len();
+        if line.trim().is_empty() {
+            blank_lines += 1;
+            continue;
+        }
+        let parent_indent = current.as_ref().map(|(_, indent)| *indent).or(skipping);
+        let nested = parent_indent.is_some_and(|parent| indent > parent);
+
+        match parse_task(line.trim_start()) {
+            Some((completed, text)) if nested => {
+                if let Some((reminder, _)) = current.as_mut() {
+                    let mut item = new_item(&reminder.checklist, text);
+                    item.completed = completed;
+                    reminder.checklist.push(item);
+                }
+            }
+            Some((completed, text)) => {
+                if let Some((reminder, _)) = current.take() {
+                    import.push(reminder);
+                }
+                skipping = None;
+                match read_task(&mut import, text) {
+                    Ok(mut reminder) => {
+                        reminder.completed = completed;
+                        current = Some((reminder, indent));
+                    }
+                    Err(reason) => {
+                        import.error(index + 1, reason);
+                        skipping = Some(indent);
+                    }
+                }
+            }
+            None if nested => {
+                if let Some((reminder, _)) = current.as_mut() {
+                    if !reminder.description.is_empty() {
+                        reminder.description.push_str(&"\n".repeat(blank_lines + 1));
+                    }
+                    reminder.description.push_str(line.trim());
+                }
+            }
+            None => {
+                if let Some((reminder, _)) = current.take() {
+                    import.push(reminder);
+                }
+                skipping = None;
+            }
+        }
+        blank_lines = 0;
+    }
+    if let Some((reminder, _)) = current {
+        import.push(reminder);
+    }
+    import.finish()
+}
+
+/// Checkbox state and text of a task list item (`- [ ] text`), if it is one
+fn parse_task(line: &str) -> Option<(bool, &str)> {
+    let rest = line.strip_prefix(['-', '*', '+'])?.strip_prefix(' ')?.trim_start();
+    let completed = match rest.get(..3)? {
+        "[ ]" => false,
+        "[x]" | "[X]" => true,
+        _ => return None,
+    };
+    let text = &rest[3..];
+    if !text.is_empty() && !text.starts_with(char::is_whitespace) {
+        return None;
+    }
+    Some((completed, text.trim()))
+}
+
+/// A reminder from a task's text, taking out its `@due(…)` and `#tags`
+fn read_task(import: &mut ImportBuilder, text: &str) -> Result<Reminder, String> {
+    let mut text = text.to_string();
+    let mut due_date = None;
+    if let Some(start) = text.find("@due(") {
+        let end = text[start..].find(')').map(|i| start + i).ok_or("@due( is never closed")?;
+        let value = text[start + "@due(".len()..end].trim();
+        due_date = Some(value.parse::<LocalDateTime>().map_err(|e| e.to_string())?);
+        text.replace_range(start..=end, "");
+    }
+
+    // `#` followed by a digit is left alone, so "Fix issue #42" keeps its number
+    let (tag_words, title_words): (Vec<&str>, Vec<&str>) = text
+        .split_whitespace()
+        .partition(|word| word.len() > 1 && word.starts_with('#') && !word[1..].starts_with(|c: char| c.is_ascii_digit()));
+    let title = title_words.join(" ");
+    if title.is_empty() {
+        return Err("the task has no title".to_string());
+    }
+
+    let mut reminder = import.reminder(title);
+    reminder.due_date = due_date;
+    import.add_tags(&mut reminder, tag_words.iter().map(|word| &word[1..]));
+    Ok(reminder)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{datetime, due_reminder, reminder, tag};
+
+    fn checklist(reminder: &Reminder) -> Vec<(&str, bool)> {
+        reminder.checklist.iter().map(|item| (item.text.as_str(), item.completed)).collect()
+    }
+
+    #[test]
+    fn exported_tasks_import_unchanged() {
+        let tags = [tag("errands", "Errands"), tag("garden", "Home garden")];
+        let mut passport = due_reminder("passport", datetime(2024, 1, 31, 9, 0));
+        passport.time_zone = None;
+        passport.title = "Renew passport for trip #42".to_string();
+        passport.description = "Bring the old passport.\n\nAnd two photos.".to_string();
+        passport.tag_ids = vec!["errands".to_string(), "garden".to_string()];
+        let mut photos = new_item(&[], "Take photos");
+        photos.completed = true;
+        let form = new_item(std::slice::from_ref(&photos), "Fill in the form");
+        passport.checklist = vec![photos, form];
+        let mut zoned = due_reminder("zoned", datetime(2024, 7, 1, 18, 30));
+        zoned.time_zone = Some(chrono_tz::Europe::Paris);
+        zoned.completed = true;
+        let reminders = [passport, zoned, reminder("plain", "Plain")];
+
+        let markdown = export_markdown(&reminders, &tags);
+        assert!(markdown.starts_with(
+            "- [ ] Renew passport for trip #42 @due(2024-01-31T09:00) #Errands #Home-garden\n  Bring the old passport.\n\n"
+        ));
+        let imported = import_markdown(&markdown, &tags);
+        assert!(imported.errors.is_empty(), "{:?}", imported.errors);
+        assert!(imported.new_tags.is_empty());
+        assert_eq!(imported.reminders.len(), 3);
+        for (original, read) in reminders.iter().zip(&imported.reminders) {
+            assert_eq!(read.title, original.title);
+            assert_eq!(read.description, original.description);
+            assert_eq!(read.completed, original.completed);
+            assert_eq!(read.tag_ids, original.tag_ids);
+            assert_eq!(read.due_timestamp(), original.due_timestamp());
+            assert_eq!(checklist(read), checklist(original));
+        }
+        assert_eq!(imported.reminders[0].due_date, reminders[0].due_date);
+    }
+
+    #[test]
+    fn due_dates_and_tags_are_taken_out_of_the_title() {
+        let text = "# Groceries\n\
+                    \n\
+                    * [X] Buy #milk @due( 2024-02-01 ) and #bread #7\n\
+                    + [ ] #new-tag Ring #Milk\n\
+                    - [ ]No space is not a task\n\
+                    - plain list item\n";
+        let imported = import_markdown(text, &[tag("milk", "milk")]);
+        assert!(imported.errors.is_empty());
+        let [buy, ring] = &imported.reminders[..] else {
+            panic!("expected two reminders");
+        };
+        assert_eq!(buy.title, "Buy and #7");
+        assert!(buy.completed);
+        assert_eq!(buy.due_date, Some("2024-02-01".parse().unwrap()));
+        assert_eq!(buy.tag_ids[0], "milk");
+        assert_eq!(ring.title, "Ring");
+        assert!(!ring.completed);
+        assert_eq!(ring.tag_ids.len(), 2);
+        assert_eq!(ring.tag_ids[1], "milk");
+        let new_names: Vec<&str> = imported.new_tags.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(new_names, ["bread", "new-tag"]);
+        assert_eq!(buy.tag_ids[1], imported.new_tags[0].id);
+    }
+
+    #[test]
+    fn unreadable_tasks_are_reported_with_their_lines() {
+        let text = "- [ ] Good\n\
+                    - [ ] Never closed @due(2024-01-31\n  \
+                      - [ ] nested under the bad task\n  \
+                      its description\n\
+                    - [ ] Bad date @due(someday)\n\
+                    - [ ] #only #tags\n\
+                    - [ ] Also good\n  \
+                      - [x] step\n";
+        let imported = import_markdown(text, &[]);
+        let titles: Vec<&str> = imported.reminders.iter().map(|r| r.title.as_str()).collect();
+        assert_eq!(titles, ["Good", "Also good"]);
+        assert!(imported.reminders[0].checklist.is_empty());
+        assert!(imported.reminders[0].description.is_empty());
+        assert_eq!(checklist(&imported.reminders[1]), [("step", true)]);
+        let rows: Vec<usize> = imported.errors.iter().map(|e| e.row).collect();
+        assert_eq!(rows, [2, 5, 6]);
+        assert_eq!(imported.errors[0].reason, "@due( is never closed");
+        assert_eq!(imported.errors[2].reason, "the task has no title");
+    }
+
+    #[test]
+    fn a_non_task_line_ends_the_task() {
+        let imported = import_markdown("- [ ] Task\n  details\nParagraph\n  not a description\n", &[]);
+        assert_eq!(imported.reminders.len(), 1);
+        assert_eq!(imported.reminders[0].description, "details");
+    }
+}
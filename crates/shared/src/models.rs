@@ -2,10 +2,17 @@
 //! 
 //! This module contains all shared data structures used across platforms.
 
+use std::collections::BTreeMap;
+
+use chrono::{NaiveDate, NaiveDateTime};
+use chrono_tz::Tz;
 use serde::{Deserialize, Serialize};
 
+use crate::datetime::{LocalDateTime, Timestamp};
+
 /// Filter type for reminders
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum ReminderFilter {
     All,
     Active,
@@ -32,12 +39,56 @@ impl ReminderFilter {
     }
 }
 
+/// Priority filter for reminders (applied alongside `ReminderFilter`)
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub enum PriorityFilter {
+    #[default]
+    All,
+    Only(Priority),
+}
+
+impl PriorityFilter {
+    /// Convert filter to string representation
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PriorityFilter::All => "all",
+            PriorityFilter::Only(priority) => priority.as_str(),
+        }
+    }
+
+    /// Create filter from string (unknown values mean `All`)
+    pub fn parse(s: &str) -> Self {
+        match Priority::parse(s) {
+            Some(priority) => PriorityFilter::Only(priority),
+            None => PriorityFilter::All,
+        }
+    }
+}
+
+impl From<String> for PriorityFilter {
+    fn from(s: String) -> Self {
+        PriorityFilter::parse(&s)
+    }
+}
+
+impl From<PriorityFilter> for String {
+    fn from(filter: PriorityFilter) -> Self {
+        filter.as_str().to_string()
+    }
+}
+
 /// Sort type for reminders
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum ReminderSort {
     Date,
     Title,
     Status,
+    /// Highest priority first, then by due date
+    Priority,
+    /// Overdue first, then highest priority, then by due date
+    Smart,
 }
 
 impl ReminderSort {
@@ -47,6 +98,8 @@ impl ReminderSort {
             ReminderSort::Date => "date",
             ReminderSort::Title => "title",
             ReminderSort::Status => "status",
+            ReminderSort::Priority => "priority",
+            ReminderSort::Smart => "smart",
         }
     }
 
@@ -55,11 +108,52 @@ impl ReminderSort {
         match s {
             "title" => ReminderSort::Title,
             "status" => ReminderSort::Status,
+            "priority" => ReminderSort::Priority,
+            "smart" => ReminderSort::Smart,
             _ => ReminderSort::Date,
         }
     }
 }
 
+/// How important a reminder is (ordered from `None` to `Urgent`)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Priority {
+    #[default]
+    None,
+    Low,
+    Medium,
+    High,
+    Urgent,
+}
+
+impl Priority {
+    /// All priorities, lowest first
+    pub const ALL: [Priority; 5] = [
+        Priority::None,
+        Priority::Low,
+        Priority::Medium,
+        Priority::High,
+        Priority::Urgent,
+    ];
+
+    /// Convert priority to string representation
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Priority::None => "none",
+            Priority::Low => "low",
+            Priority::Medium => "medium",
+            Priority::High => "high",
+            Priority::Urgent => "urgent",
+        }
+    }
+
+    /// Parse priority from its string representation (`None` for unknown values)
+    pub fn parse(s: &str) -> Option<Self> {
+        Priority::ALL.into_iter().find(|p| p.as_str() == s)
+    }
+}
+
 /// Reminder data structure
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Reminder {
@@ -69,15 +163,287 @@ pub struct Reminder {
     pub title: String,
     /// Description/details of the reminder
     pub description: String,
-    /// Due date in ISO 8601 format
-    pub due_date: String,
+    /// Due date (`None` if the reminder has no due date)
+    #[serde(default, deserialize_with = "crate::datetime::deserialize_optional")]
+    pub due_date: Option<LocalDateTime>,
+    /// IANA time zone of the due date (`None` = floating, follows the device's zone)
+    #[serde(default, deserialize_with = "crate::datetime::deserialize_optional")]
+    pub time_zone: Option<Tz>,
     /// Whether the reminder is completed
     pub completed: bool,
-    /// Creation timestamp in ISO 8601 format
-    pub created_at: String,
+    /// Creation timestamp
+    pub created_at: Timestamp,
+    /// Last change through the `ReminderRepository` (`None` if unchanged since creation)
+    #[serde(default)]
+    pub updated_at: Option<Timestamp>,
     /// List of tag IDs associated with this reminder
     #[serde(default)]
     pub tag_ids: Vec<String>,
+    /// How important the reminder is
+    #[serde(default)]
+    pub priority: Priority,
+    /// Recurrence rule for repeating reminders (`None` for one-off reminders)
+    #[serde(default)]
+    pub recurrence: Option<Recurrence>,
+    /// When to notify relative to the due date (empty = notify at the due time)
+    #[serde(default)]
+    pub lead_times: Vec<LeadTime>,
+    /// Snoozed until this time; not overdue or notified before then
+    #[serde(default)]
+    pub snoozed_until: Option<Timestamp>,
+    /// Every snooze applied to this reminder, oldest first
+    #[serde(default)]
+    pub snooze_log: Vec<SnoozeEntry>,
+    /// Ordered checklist items (subtasks)
+    #[serde(default)]
+    pub checklist: Vec<ChecklistItem>,
+    /// Complete the reminder automatically once every checklist item is done
+    #[serde(default)]
+    pub auto_complete: bool,
+    /// `key:value` pairs from other tools (e.g. todo.txt) that the app
+    /// doesn't use, kept so they can be written back on export
+    #[serde(default)]
+    pub extensions: BTreeMap<String, String>,
+}
+
+impl Reminder {
+    /// The instant this reminder is due, honouring its time zone
+    pub fn due_timestamp(&self) -> Option<Timestamp> {
+        let due = self.due_date?;
+        Some(match self.time_zone {
+            Some(tz) => due.to_timestamp_in(&tz),
+            None => due.to_timestamp(),
+        })
+    }
+
+    /// Due date as wall-clock time in the device's time zone
+    pub fn due_device_local(&self) -> Option<NaiveDateTime> {
+        match self.time_zone {
+            Some(_) => self.due_timestamp().map(|t| t.to_local().naive()),
+            None => self.due_date.map(|d| d.to_device_local()),
+        }
+    }
+
+    /// Checklist progress as (completed items, total items)
+    pub fn checklist_progress(&self) -> (usize, usize) {
+        let done = self.checklist.iter().filter(|item| item.completed).count();
+        (done, self.checklist.len())
+    }
+
+    /// Due date as wall-clock time in the reminder's own time zone
+    pub fn due_zone_local(&self) -> Option<NaiveDateTime> {
+        match self.time_zone {
+            Some(tz) => self.due_date.map(|d| d.wall_clock_in(&tz)),
+            None => self.due_device_local(),
+        }
+    }
+}
+
+/// A checklist item (subtask) inside a reminder
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ChecklistItem {
+    /// Unique identifier for the item (within its reminder)
+    pub id: String,
+    /// Text of the item
+    pub text: String,
+    /// Whether the item is done
+    #[serde(default)]
+    pub completed: bool,
+}
+
+/// A single snooze of a reminder
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SnoozeEntry {
+    /// When the reminder was snoozed
+    pub snoozed_at: Timestamp,
+    /// Time the reminder was snoozed until
+    pub until: Timestamp,
+}
+
+/// How long before the due date a notification fires
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct LeadTime {
+    /// Minutes before the due date (0 = at the due time)
+    pub minutes: u32,
+}
+
+impl LeadTime {
+    /// Notify at the due time
+    pub const AT_DUE: LeadTime = LeadTime { minutes: 0 };
+
+    /// Lead times offered in the reminder forms
+    pub const PRESETS: [LeadTime; 4] = [
+        LeadTime::AT_DUE,
+        LeadTime { minutes: 10 },
+        LeadTime { minutes: 60 },
+        LeadTime { minutes: 24 * 60 },
+    ];
+
+    /// Create a lead time of `minutes` before the due date
+    pub fn from_minutes(minutes: u32) -> Self {
+        Self { minutes }
+    }
+
+    /// Lead time in milliseconds
+    pub fn as_millis(&self) -> i64 {
+        self.minutes as i64 * 60_000
+    }
+}
+
+/// Recurrence frequency (RRULE `FREQ`)
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RecurrenceFrequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+impl RecurrenceFrequency {
+    /// Convert frequency to string representation
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RecurrenceFrequency::Daily => "daily",
+            RecurrenceFrequency::Weekly => "weekly",
+            RecurrenceFrequency::Monthly => "monthly",
+            RecurrenceFrequency::Yearly => "yearly",
+        }
+    }
+
+    /// Parse frequency from its string representation (`None` for unknown values)
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "daily" => Some(RecurrenceFrequency::Daily),
+            "weekly" => Some(RecurrenceFrequency::Weekly),
+            "monthly" => Some(RecurrenceFrequency::Monthly),
+            "yearly" => Some(RecurrenceFrequency::Yearly),
+            _ => None,
+        }
+    }
+}
+
+/// Day of the week (RRULE `BYDAY` codes)
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum Weekday {
+    Mo,
+    Tu,
+    We,
+    Th,
+    Fr,
+    Sa,
+    Su,
+}
+
+impl Weekday {
+    /// All weekdays, Monday first
+    pub const ALL: [Weekday; 7] = [
+        Weekday::Mo,
+        Weekday::Tu,
+        Weekday::We,
+        Weekday::Th,
+        Weekday::Fr,
+        Weekday::Sa,
+        Weekday::Su,
+    ];
+
+    /// Convert weekday to its RRULE code (e.g. "MO")
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Weekday::Mo => "MO",
+            Weekday::Tu => "TU",
+            Weekday::We => "WE",
+            Weekday::Th => "TH",
+            Weekday::Fr => "FR",
+            Weekday::Sa => "SA",
+            Weekday::Su => "SU",
+        }
+    }
+
+    /// Parse weekday from its RRULE code (case-insensitive)
+    pub fn parse(s: &str) -> Option<Self> {
+        Weekday::ALL
+            .into_iter()
+            .find(|d| d.as_str().eq_ignore_ascii_case(s))
+    }
+}
+
+/// RRULE-style recurrence rule
+///
+/// The rule is anchored on the reminder's current `due_date`. When an
+/// occurrence is completed the reminder rolls forward to the next one
+/// (see `recurrence::advance`).
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Recurrence {
+    /// How often the reminder repeats
+    pub frequency: RecurrenceFrequency,
+    /// Repeat every `interval` days/weeks/months/years (minimum 1)
+    #[serde(default = "default_interval")]
+    pub interval: u32,
+    /// Restrict occurrences to these weekdays (weekly and daily rules only)
+    #[serde(default)]
+    pub by_weekday: Vec<Weekday>,
+    /// Remaining number of occurrences, including the current due date
+    #[serde(default)]
+    pub count: Option<u32>,
+    /// Last date (inclusive) on which an occurrence may fall
+    #[serde(default)]
+    pub until: Option<NaiveDate>,
+}
+
+fn default_interval() -> u32 {
+    1
+}
+
+impl Recurrence {
+    /// Create a rule repeating every period of `frequency`
+    pub fn new(frequency: RecurrenceFrequency) -> Self {
+        Self {
+            frequency,
+            interval: 1,
+            by_weekday: Vec::new(),
+            count: None,
+            until: None,
+        }
+    }
+}
+
+/// A named preset of the reminder list's filter, search, sort and view
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SavedView {
+    /// Unique identifier for the saved view
+    pub id: String,
+    /// Name shown in the view switcher (e.g., "Work, overdue first")
+    pub name: String,
+    pub filter: ReminderFilter,
+    #[serde(default)]
+    pub priority_filter: PriorityFilter,
+    /// Search query (see `crate::query`)
+    #[serde(default)]
+    pub search_query: String,
+    pub sort: ReminderSort,
+    /// Layout: "list", "card", "folder" or "calendar"
+    pub view: String,
+}
+
+impl SavedView {
+    /// Whether the given list settings are exactly this view's
+    pub fn is_applied(
+        &self,
+        filter: &ReminderFilter,
+        priority_filter: &PriorityFilter,
+        search_query: &str,
+        sort: &ReminderSort,
+        view: &str,
+    ) -> bool {
+        self.filter == *filter
+            && self.priority_filter == *priority_filter
+            && self.search_query == search_query
+            && self.sort == *sort
+            && self.view == view
+    }
 }
 
 /// Tag data structure
@@ -102,5 +468,43 @@ pub struct Statistics {
     pub completed: usize,
     /// Number of overdue reminders
     pub overdue: usize,
+    /// Number of reminders currently snoozed
+    pub snoozed: usize,
+    /// Total number of snoozes across all reminders
+    pub snooze_count: usize,
+    /// Number of checklist items across all reminders
+    pub checklist_items: usize,
+    /// Number of completed checklist items across all reminders
+    pub checklist_completed: usize,
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::saved_view;
+
+    #[test]
+    fn a_saved_view_is_applied_only_when_every_setting_matches() {
+        let view = SavedView {
+            filter: ReminderFilter::Active,
+            priority_filter: PriorityFilter::Only(Priority::High),
+            search_query: "tag:work dentist".to_string(),
+            sort: ReminderSort::Smart,
+            view: "card".to_string(),
+            ..saved_view("v", "Work")
+        };
+        let high = PriorityFilter::Only(Priority::High);
+        assert!(view.is_applied(&ReminderFilter::Active, &high, "tag:work dentist", &ReminderSort::Smart, "card"));
+
+        let differing = [
+            view.is_applied(&ReminderFilter::All, &high, "tag:work dentist", &ReminderSort::Smart, "card"),
+            view.is_applied(&ReminderFilter::Active, &PriorityFilter::All, "tag:work dentist", &ReminderSort::Smart, "card"),
+            view.is_applied(&ReminderFilter::Active, &high, "tag:home dentist", &ReminderSort::Smart, "card"),
+            view.is_applied(&ReminderFilter::Active, &high, "tag:work", &ReminderSort::Smart, "card"),
+            view.is_applied(&ReminderFilter::Active, &high, "tag:work dentist", &ReminderSort::Date, "card"),
+            view.is_applied(&ReminderFilter::Active, &high, "tag:work dentist", &ReminderSort::Smart, "list"),
+        ];
+        assert_eq!(differing, [false; 6]);
+    }
+}
@@ -0,0 +1,569 @@
+//! Notification scheduling for due reminders
+//!
+//! The scheduler computes fire times from each reminder's `due_date` and
+//! `lead_times`, and hands notifications that have come due to a
+//! `NotificationPlatform`. Platform implementations:
+//! - `WebNotifier` (wasm32): Notification API, with a service-worker fallback (`assets/sw.js`)
+//! - `MobileNotifier` (iOS/Android): unavailable until there is a native bridge
+//! - `LoggingNotifier` (other native targets): records notifications, for tests and tools
+//!
+//! `PlatformNotifier` is an alias for the implementation of the current target.
+
+use std::cell::{Cell, RefCell};
+
+use crate::models::{LeadTime, Reminder};
+use crate::datetime::Timestamp;
+
+/// Notification error type
+#[derive(Debug, Clone)]
+pub enum NotificationError {
+    /// Notifications are not supported on this platform/browser
+    Unavailable,
+    /// The user has not granted notification permission
+    PermissionDenied,
+    /// The platform failed to show the notification
+    ShowFailed,
+}
+
+/// A notification computed for one reminder and lead time
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScheduledNotification {
+    /// ID of the reminder this notification belongs to
+    pub reminder_id: String,
+    /// Notification title (the reminder title)
+    pub title: String,
+    /// Notification body (the reminder description)
+    pub body: String,
+    /// Lead time this notification was scheduled for
+    pub lead_time: LeadTime,
+    /// Due date of the reminder (epoch milliseconds)
+    pub due_at_ms: i64,
+    /// When the notification fires (epoch milliseconds)
+    pub fire_at_ms: i64,
+}
+
+impl ScheduledNotification {
+    /// Stable identifier, used as the notification tag so re-showing replaces it
+    pub fn key(&self) -> String {
+        format!("{}@{}", self.reminder_id, self.fire_at_ms)
+    }
+}
+
+/// Source of the current time, so scheduling can be driven by a fake clock
+pub trait Clock {
+    /// Current time in epoch milliseconds
+    fn now_ms(&self) -> i64;
+}
+
+/// Clock backed by the system time
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now_ms(&self) -> i64 {
+        Timestamp::now().as_millis()
+    }
+}
+
+/// Manually advanced clock for tests and simulations
+#[derive(Debug, Default)]
+pub struct ManualClock {
+    now_ms: Cell<i64>,
+}
+
+impl ManualClock {
+    /// Create a clock stopped at `now_ms`
+    pub fn new(now_ms: i64) -> Self {
+        Self { now_ms: Cell::new(now_ms) }
+    }
+
+    /// Move the clock to `now_ms`
+    pub fn set(&self, now_ms: i64) {
+        self.now_ms.set(now_ms);
+    }
+
+    /// Move the clock forward by `ms`
+    pub fn advance(&self, ms: i64) {
+        self.now_ms.set(self.now_ms.get() + ms);
+    }
+}
+
+impl Clock for ManualClock {
+    fn now_ms(&self) -> i64 {
+        self.now_ms.get()
+    }
+}
+
+/// Platform notification backend
+pub trait NotificationPlatform {
+    /// Whether this platform can show notifications at all, given permission
+    fn is_available(&self) -> bool {
+        true
+    }
+
+    /// Ask the user for permission to show notifications (no-op where not needed)
+    fn request_permission(&self) {}
+
+    /// Whether notifications can currently be shown
+    fn is_permitted(&self) -> bool;
+
+    /// Show a notification
+    fn show(&self, notification: &ScheduledNotification) -> Result<(), NotificationError>;
+}
+
+/// All notifications firing strictly after `after_ms`, ordered by fire time.
+///
+/// Completed reminders and reminders without a due date are skipped.
+/// Recurring reminders are scheduled for their current occurrence only; they
+/// roll forward when that occurrence is completed. A snooze that ends after
+/// `after_ms` pushes back the fire times before its end to that end, where
+/// they fire once; later fire times, and every fire time of a snooze that
+/// has already ended, are kept.
+pub fn upcoming_notifications(reminders: &[Reminder], after_ms: i64) -> Vec<ScheduledNotification> {
+    let mut upcoming: Vec<ScheduledNotification> = reminders
+        .iter()
+        .filter(|r| !r.completed)
+        .filter_map(|r| r.due_timestamp().map(|due| (r, due.as_millis())))
+        .flat_map(|(r, due_ms)| {
+            let snoozed_until = r.snoozed_until.map(Timestamp::as_millis).filter(|until| *until > after_ms);
+            let mut fire_times: Vec<(LeadTime, i64)> = if r.lead_times.is_empty() {
+                vec![(LeadTime::AT_DUE, due_ms)]
+            } else {
+                r.lead_times
+                    .iter()
+                    .map(|lead_time| (*lead_time, due_ms - lead_time.as_millis()))
+                    .collect()
+            };
+            if let Some(until) = snoozed_until {
+                for (_, fire_at_ms) in &mut fire_times {
+                    *fire_at_ms = (*fire_at_ms).max(until);
+                }
+                // Fire times moved to the snooze's end fire once, as the lead time nearest the due date
+                fire_times.sort_by_key(|(lead_time, fire_at_ms)| (*fire_at_ms, *lead_time));
+                fire_times.dedup_by_key(|(_, fire_at_ms)| *fire_at_ms);
+            }
+            fire_times.into_iter().map(move |(lead_time, fire_at_ms)| ScheduledNotification {
+                reminder_id: r.id.clone(),
+                title: r.title.clone(),
+                body: r.description.clone(),
+                lead_time,
+                due_at_ms: due_ms,
+                fire_at_ms,
+            })
+        })
+        .filter(|n| n.fire_at_ms > after_ms)
+        .collect();
+
+    upcoming.sort_by_key(|n| n.fire_at_ms);
+    upcoming
+}
+
+/// Fires reminder notifications as their fire times pass.
+///
+/// Call `tick` periodically (for example after sleeping until
+/// `next_fire_at`). Each call fires every notification whose fire time lies
+/// between the previous tick and now, so nothing fires twice and
+/// notifications that came due before the scheduler was created are not
+/// replayed.
+pub struct NotificationScheduler<P: NotificationPlatform, C: Clock = SystemClock> {
+    platform: P,
+    clock: C,
+    last_tick_ms: i64,
+}
+
+impl<P: NotificationPlatform, C: Clock> NotificationScheduler<P, C> {
+    /// Create a scheduler that starts firing from the clock's current time
+    pub fn new(platform: P, clock: C) -> Self {
+        let last_tick_ms = clock.now_ms();
+        Self {
+            platform,
+            clock,
+            last_tick_ms,
+        }
+    }
+
+    /// The platform notifications are delivered to
+    pub fn platform(&self) -> &P {
+        &self.platform
+    }
+
+    /// The clock driving this scheduler
+    pub fn clock(&self) -> &C {
+        &self.clock
+    }
+
+    /// Fire time of the next notification after the last tick, if any
+    pub fn next_fire_at(&self, reminders: &[Reminder]) -> Option<i64> {
+        upcoming_notifications(reminders, self.last_tick_ms)
+            .first()
+            .map(|n| n.fire_at_ms)
+    }
+
+    /// Show every notification that came due since the last tick.
+    ///
+    /// Returns the notifications that came due, including any the platform
+    /// failed to show, so callers can surface them in-app as well.
+    pub fn tick(&mut self, reminders: &[Reminder]) -> Vec<ScheduledNotification> {
+        let now_ms = self.clock.now_ms();
+        let due: Vec<ScheduledNotification> = upcoming_notifications(reminders, self.last_tick_ms)
+            .into_iter()
+            .take_while(|n| n.fire_at_ms <= now_ms)
+            .collect();
+
+        for notification in &due {
+            if let Err(_e) = self.platform.show(notification) {
+                // Log error but don't block (the caller still gets the notification)
+                #[cfg(debug_assertions)]
+                eprintln!("Failed to show notification {}: {:?}", notification.key(), _e);
+            }
+        }
+
+        self.last_tick_ms = self.last_tick_ms.max(now_ms);
+        due
+    }
+}
+
+/// Notification stand-in that records every notification it shows
+///
+/// Debug builds also print them to stderr.
+#[derive(Debug, Default)]
+pub struct LoggingNotifier {
+    delivered: RefCell<Vec<ScheduledNotification>>,
+}
+
+impl LoggingNotifier {
+    /// Notifications shown so far, in firing order
+    pub fn delivered(&self) -> Vec<ScheduledNotification> {
+        self.delivered.borrow().clone()
+    }
+}
+
+impl NotificationPlatform for LoggingNotifier {
+    fn is_permitted(&self) -> bool {
+        true
+    }
+
+    fn show(&self, notification: &ScheduledNotification) -> Result<(), NotificationError> {
+        #[cfg(debug_assertions)]
+        eprintln!(
+            "[notification] {} ({} min before due) {}",
+            notification.title,
+            notification.lead_time.minutes,
+            notification.key()
+        );
+        self.delivered.borrow_mut().push(notification.clone());
+        Ok(())
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+mod web_notifier_impl {
+    use super::*;
+    use js_sys::{Array, Function, Object, Reflect};
+    use wasm_bindgen::{JsCast, JsValue};
+
+    /// Browser notifications via the Notification API
+    ///
+    /// Falls back to posting a `show-notification` message to the active service
+    /// worker (`assets/sw.js`) where the page cannot construct notifications
+    /// itself (e.g. Chrome on Android).
+    #[derive(Debug, Default)]
+    pub struct WebNotifier;
+
+    fn notification_constructor() -> Option<Function> {
+        let window = web_sys::window()?;
+        Reflect::get(&window, &JsValue::from_str("Notification"))
+            .ok()?
+            .dyn_into::<Function>()
+            .ok()
+    }
+
+    fn show_via_service_worker(notification: &ScheduledNotification, options: &Object) -> Result<(), NotificationError> {
+        let window = web_sys::window().ok_or(NotificationError::Unavailable)?;
+        let controller = Reflect::get(&window, &JsValue::from_str("navigator"))
+            .and_then(|navigator| Reflect::get(&navigator, &JsValue::from_str("serviceWorker")))
+            .and_then(|service_worker| Reflect::get(&service_worker, &JsValue::from_str("controller")))
+            .ok()
+            .filter(|controller| controller.is_object())
+            .ok_or(NotificationError::Unavailable)?;
+        let post_message = Reflect::get(&controller, &JsValue::from_str("postMessage"))
+            .ok()
+            .and_then(|f| f.dyn_into::<Function>().ok())
+            .ok_or(NotificationError::Unavailable)?;
+
+        let message = Object::new();
+        let _ = Reflect::set(&message, &JsValue::from_str("type"), &JsValue::from_str("show-notification"));
+        let _ = Reflect::set(&message, &JsValue::from_str("title"), &JsValue::from_str(&notification.title));
+        let _ = Reflect::set(&message, &JsValue::from_str("options"), options);
+
+        post_message
+            .call1(&controller, &message)
+            .map(|_| ())
+            .map_err(|_| NotificationError::ShowFailed)
+    }
+
+    impl NotificationPlatform for WebNotifier {
+        fn request_permission(&self) {
+            let Some(constructor) = notification_constructor() else {
+                return;
+            };
+            if let Some(request) = Reflect::get(&constructor, &JsValue::from_str("requestPermission"))
+                .ok()
+                .and_then(|f| f.dyn_into::<Function>().ok())
+            {
+                let _ = request.call0(&constructor);
+            }
+        }
+
+        fn is_permitted(&self) -> bool {
+            notification_constructor()
+                .and_then(|constructor| Reflect::get(&constructor, &JsValue::from_str("permission")).ok())
+                .and_then(|permission| permission.as_string())
+                .is_some_and(|permission| permission == "granted")
+        }
+
+        fn show(&self, notification: &ScheduledNotification) -> Result<(), NotificationError> {
+            if !self.is_permitted() {
+                return Err(NotificationError::PermissionDenied);
+            }
+
+            let options = Object::new();
+            let _ = Reflect::set(&options, &JsValue::from_str("body"), &JsValue::from_str(&notification.body));
+            let _ = Reflect::set(&options, &JsValue::from_str("tag"), &JsValue::from_str(&notification.key()));
+
+            if let Some(constructor) = notification_constructor() {
+                let args = Array::of2(&JsValue::from_str(&notification.title), &options);
+                if Reflect::construct(&constructor, &args).is_ok() {
+                    return Ok(());
+                }
+            }
+
+            show_via_service_worker(notification, &options)
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+pub use web_notifier_impl::WebNotifier;
+
+#[cfg(target_arch = "wasm32")]
+pub type PlatformNotifier = WebNotifier;
+
+#[cfg(any(target_os = "ios", target_os = "android"))]
+mod mobile_notifier_impl {
+    use super::*;
+
+    /// Mobile notifications
+    ///
+    /// dioxus-mobile does not expose a local-notification bridge yet, so
+    /// system notifications are unavailable and `show` fails; fired reminders
+    /// only reach the user in-app (the app surfaces everything returned by
+    /// `NotificationScheduler::tick`).
+    #[derive(Debug, Default)]
+    pub struct MobileNotifier;
+
+    impl NotificationPlatform for MobileNotifier {
+        fn is_available(&self) -> bool {
+            false
+        }
+
+        fn is_permitted(&self) -> bool {
+            false
+        }
+
+        fn show(&self, _notification: &ScheduledNotification) -> Result<(), NotificationError> {
+            Err(NotificationError::Unavailable)
+        }
+    }
+}
+
+#[cfg(any(target_os = "ios", target_os = "android"))]
+pub use mobile_notifier_impl::MobileNotifier;
+
+#[cfg(any(target_os = "ios", target_os = "android"))]
+pub type PlatformNotifier = MobileNotifier;
+
+#[cfg(not(any(target_arch = "wasm32", target_os = "ios", target_os = "android")))]
+pub type PlatformNotifier = LoggingNotifier;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::datetime::Timestamp;
+    use crate::test_support::{datetime, due_reminder};
+
+    const MINUTE: i64 = 60_000;
+
+    fn at(hour: u32, minute: u32) -> i64 {
+        due_reminder("", datetime(2024, 1, 15, hour, minute)).due_timestamp().unwrap().as_millis()
+    }
+
+    fn scheduler(now_ms: i64) -> NotificationScheduler<LoggingNotifier, ManualClock> {
+        NotificationScheduler::new(LoggingNotifier::default(), ManualClock::new(now_ms))
+    }
+
+    fn fired_ids(fired: &[ScheduledNotification]) -> Vec<(&str, u32)> {
+        fired.iter().map(|n| (n.reminder_id.as_str(), n.lead_time.minutes)).collect()
+    }
+
+    #[test]
+    fn fires_at_each_lead_time_before_due() {
+        let mut reminder = due_reminder("a", datetime(2024, 1, 15, 10, 0));
+        reminder.lead_times = vec![LeadTime::from_minutes(60), LeadTime::AT_DUE, LeadTime::from_minutes(10)];
+        let reminders = [reminder];
+        let mut scheduler = scheduler(at(8, 0));
+
+        assert_eq!(scheduler.next_fire_at(&reminders), Some(at(9, 0)));
+        assert!(scheduler.tick(&reminders).is_empty());
+
+        scheduler.clock().set(at(9, 0));
+        assert_eq!(fired_ids(&scheduler.tick(&reminders)), [("a", 60)]);
+        assert_eq!(scheduler.next_fire_at(&reminders), Some(at(9, 50)));
+
+        scheduler.clock().set(at(9, 50));
+        assert_eq!(fired_ids(&scheduler.tick(&reminders)), [("a", 10)]);
+
+        scheduler.clock().set(at(10, 0));
+        let fired = scheduler.tick(&reminders);
+        assert_eq!(fired_ids(&fired), [("a", 0)]);
+        assert_eq!(fired[0].due_at_ms, at(10, 0));
+        assert_eq!(scheduler.next_fire_at(&reminders), None);
+        assert_eq!(scheduler.platform().delivered().len(), 3);
+    }
+
+    #[test]
+    fn no_lead_times_fires_at_due_time() {
+        let reminders = [due_reminder("a", datetime(2024, 1, 15, 10, 0))];
+        let mut scheduler = scheduler(at(9, 0));
+
+        scheduler.clock().set(at(10, 0) - 1);
+        assert!(scheduler.tick(&reminders).is_empty());
+        scheduler.clock().advance(1);
+        assert_eq!(fired_ids(&scheduler.tick(&reminders)), [("a", 0)]);
+    }
+
+    #[test]
+    fn missed_ticks_fire_everything_in_order() {
+        let mut first = due_reminder("a", datetime(2024, 1, 15, 10, 0));
+        first.lead_times = vec![LeadTime::from_minutes(30), LeadTime::AT_DUE];
+        let second = due_reminder("b", datetime(2024, 1, 15, 9, 45));
+        let reminders = [first, second];
+        let mut scheduler = scheduler(at(9, 0));
+
+        // The app slept through every fire time
+        scheduler.clock().set(at(11, 0));
+        let fired = scheduler.tick(&reminders);
+        assert_eq!(fired_ids(&fired), [("a", 30), ("b", 0), ("a", 0)]);
+        assert_eq!(scheduler.platform().delivered(), fired);
+    }
+
+    #[test]
+    fn notifications_fire_once() {
+        let reminders = [due_reminder("a", datetime(2024, 1, 15, 10, 0))];
+        let mut scheduler = scheduler(at(9, 0));
+
+        scheduler.clock().set(at(10, 0));
+        assert_eq!(scheduler.tick(&reminders).len(), 1);
+        assert!(scheduler.tick(&reminders).is_empty());
+        scheduler.clock().advance(MINUTE);
+        assert!(scheduler.tick(&reminders).is_empty());
+
+        // A clock that jumps back doesn't replay them either
+        scheduler.clock().set(at(9, 30));
+        assert!(scheduler.tick(&reminders).is_empty());
+        scheduler.clock().set(at(10, 0));
+        assert!(scheduler.tick(&reminders).is_empty());
+        assert_eq!(scheduler.platform().delivered().len(), 1);
+    }
+
+    #[test]
+    fn past_notifications_are_not_replayed_on_start() {
+        let reminders = [due_reminder("a", datetime(2024, 1, 15, 10, 0))];
+        let mut scheduler = scheduler(at(12, 0));
+
+        assert!(scheduler.tick(&reminders).is_empty());
+        assert_eq!(scheduler.next_fire_at(&reminders), None);
+    }
+
+    #[test]
+    fn skips_completed_and_undated_reminders() {
+        let mut completed = due_reminder("done", datetime(2024, 1, 15, 10, 0));
+        completed.completed = true;
+        let mut undated = due_reminder("undated", datetime(2024, 1, 15, 10, 0));
+        undated.due_date = None;
+
+        assert!(upcoming_notifications(&[completed, undated], at(0, 0)).is_empty());
+    }
+
+    #[test]
+    fn snoozed_reminder_fires_once_when_the_snooze_ends() {
+        let mut reminder = due_reminder("a", datetime(2024, 1, 15, 10, 0));
+        reminder.lead_times = vec![LeadTime::from_minutes(10), LeadTime::AT_DUE];
+        reminder.snoozed_until = Some(Timestamp::from_millis(at(10, 15)));
+        let reminders = [reminder];
+        let mut scheduler = scheduler(at(9, 0));
+
+        scheduler.clock().set(at(10, 14));
+        assert!(scheduler.tick(&reminders).is_empty());
+        scheduler.clock().set(at(10, 15));
+        let fired = scheduler.tick(&reminders);
+        assert_eq!(fired_ids(&fired), [("a", 0)]);
+        assert_eq!(fired[0].fire_at_ms, at(10, 15));
+    }
+
+    #[test]
+    fn a_snooze_before_the_due_date_keeps_the_later_notifications() {
+        let mut reminder = due_reminder("a", datetime(2024, 1, 15, 10, 0));
+        reminder.lead_times = vec![LeadTime::from_minutes(60), LeadTime::from_minutes(30), LeadTime::AT_DUE];
+        reminder.snoozed_until = Some(Timestamp::from_millis(at(9, 5)));
+        let reminders = [reminder];
+        let mut scheduler = scheduler(at(8, 55));
+
+        // The 09:00 notification waits for the snooze; 09:30 and 10:00 are untouched
+        scheduler.clock().set(at(9, 0));
+        assert!(scheduler.tick(&reminders).is_empty());
+        scheduler.clock().set(at(9, 5));
+        assert_eq!(fired_ids(&scheduler.tick(&reminders)), [("a", 60)]);
+        scheduler.clock().set(at(10, 0));
+        assert_eq!(fired_ids(&scheduler.tick(&reminders)), [("a", 30), ("a", 0)]);
+
+        // A five-minute snooze the day before changes nothing
+        let mut tomorrow = due_reminder("b", datetime(2024, 1, 16, 10, 0));
+        tomorrow.snoozed_until = Some(Timestamp::from_millis(at(9, 5)));
+        let upcoming = upcoming_notifications(&[tomorrow.clone()], at(9, 0));
+        assert_eq!(upcoming.len(), 1);
+        assert_eq!(upcoming[0].fire_at_ms, tomorrow.due_timestamp().unwrap().as_millis());
+    }
+
+    #[test]
+    fn an_expired_snooze_is_ignored() {
+        let mut reminder = due_reminder("a", datetime(2024, 1, 15, 10, 0));
+        reminder.lead_times = vec![LeadTime::from_minutes(10), LeadTime::AT_DUE];
+        reminder.snoozed_until = Some(Timestamp::from_millis(at(8, 0)));
+        let reminders = [reminder];
+        let mut scheduler = scheduler(at(9, 0));
+
+        assert_eq!(scheduler.next_fire_at(&reminders), Some(at(9, 50)));
+        scheduler.clock().set(at(10, 0));
+        assert_eq!(fired_ids(&scheduler.tick(&reminders)), [("a", 10), ("a", 0)]);
+        assert_eq!(scheduler.next_fire_at(&reminders), None);
+    }
+
+    #[test]
+    fn failed_deliveries_are_still_returned() {
+        struct Failing;
+        impl NotificationPlatform for Failing {
+            fn is_permitted(&self) -> bool {
+                false
+            }
+            fn show(&self, _: &ScheduledNotification) -> Result<(), NotificationError> {
+                Err(NotificationError::PermissionDenied)
+            }
+        }
+
+        let reminders = [due_reminder("a", datetime(2024, 1, 15, 10, 0))];
+        let mut scheduler = NotificationScheduler::new(Failing, ManualClock::new(at(9, 0)));
+        scheduler.clock().set(at(10, 0));
+        assert_eq!(fired_ids(&scheduler.tick(&reminders)), [("a", 0)]);
+    }
+}
@@ -0,0 +1,666 @@
+//! Search query language
+//!
+//! The search box accepts a small query syntax. Terms are separated by
+//! whitespace and must all match:
+//!
+//! - `word`: fuzzy, case-insensitive match in the title, description,
+//!   checklist or tag names (tolerates small typos)
+//! - `"exact phrase"`: literal, case-insensitive match in the same fields
+//! - `tag:work` / `tag:"deep work"`: has a tag with this name
+//! - `due:2026-11-01`, `due:<2026-11-01`, `due:>=today`, `due:none`:
+//!   due date (in the device's time zone) compared with a date
+//! - `is:overdue`, `is:active`, `is:completed`, `is:snoozed`, `is:recurring`
+//! - `priority:high`, `priority:>=medium`
+//! - `-term`: excludes reminders matching `term`
+//!
+//! Queries are parsed once with [`Query::parse`] and evaluated per reminder
+//! with [`Query::matches_at`], which takes the current time so results do not
+//! depend on the system clock.
+
+use std::fmt;
+
+use chrono::{Duration, NaiveDate};
+
+use crate::datetime::Timestamp;
+use crate::models::{Priority, Reminder, Tag};
+use crate::snooze::is_snoozed_at;
+use crate::utils::{fuzzy_match_reminder, is_overdue_at, SearchMatch, SearchWord};
+
+/// Comparison operator for `due:` and `priority:` terms
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Comparison {
+    Eq,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl Comparison {
+    /// Split a leading operator (`<`, `<=`, `>`, `>=`, `=`) off `value`
+    fn split(value: &str) -> (Self, &str) {
+        if let Some(rest) = value.strip_prefix("<=") {
+            (Comparison::Le, rest)
+        } else if let Some(rest) = value.strip_prefix(">=") {
+            (Comparison::Ge, rest)
+        } else if let Some(rest) = value.strip_prefix('<') {
+            (Comparison::Lt, rest)
+        } else if let Some(rest) = value.strip_prefix('>') {
+            (Comparison::Gt, rest)
+        } else if let Some(rest) = value.strip_prefix('=') {
+            (Comparison::Eq, rest)
+        } else {
+            (Comparison::Eq, value)
+        }
+    }
+
+    fn holds<T: Ord>(self, left: T, right: T) -> bool {
+        match self {
+            Comparison::Eq => left == right,
+            Comparison::Lt => left < right,
+            Comparison::Le => left <= right,
+            Comparison::Gt => left > right,
+            Comparison::Ge => left >= right,
+        }
+    }
+}
+
+/// Date a `due:` term compares against
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DueDate {
+    /// A calendar date
+    On(NaiveDate),
+    /// The current date, resolved at evaluation time
+    Today,
+    /// The day after the current date
+    Tomorrow,
+}
+
+/// Reminder state tested by an `is:` term
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum State {
+    Overdue,
+    Active,
+    Completed,
+    Snoozed,
+    Recurring,
+}
+
+impl State {
+    fn parse(s: &str) -> Option<Self> {
+        match s {
+            "overdue" => Some(State::Overdue),
+            "active" | "open" => Some(State::Active),
+            "completed" | "done" => Some(State::Completed),
+            "snoozed" => Some(State::Snoozed),
+            "recurring" => Some(State::Recurring),
+            _ => None,
+        }
+    }
+}
+
+/// A single query term
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Term {
+    /// A search word, matched fuzzily
+    Text(String),
+    /// A quoted phrase, matched literally
+    Phrase(String),
+    /// Has a tag with this name (lowercased)
+    Tag(String),
+    /// Due date compared with a date
+    Due(Comparison, DueDate),
+    /// Has no due date
+    NoDueDate,
+    /// Reminder is in this state
+    Is(State),
+    /// Priority compared with a level
+    Priority(Comparison, Priority),
+}
+
+/// A term, possibly negated with `-`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Clause {
+    pub negated: bool,
+    pub term: Term,
+}
+
+/// A parsed search query: all clauses must match
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Query {
+    pub clauses: Vec<Clause>,
+}
+
+/// Error for a malformed query, with the character position it was found at
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum QueryError {
+    /// A `"` without a closing quote
+    UnterminatedQuote { position: usize },
+    /// `key:` without a value
+    MissingValue { key: String, position: usize },
+    /// `-` not followed by a term
+    MissingTerm { position: usize },
+    /// `key:` that is not a known filter
+    UnknownFilter { key: String, position: usize },
+    /// `due:` value that is not a date
+    InvalidDate { value: String, position: usize },
+    /// `is:` value that is not a known state
+    InvalidState { value: String, position: usize },
+    /// `priority:` value that is not a known level
+    InvalidPriority { value: String, position: usize },
+}
+
+impl QueryError {
+    /// Stable identifier of the error kind (used as a translation key)
+    pub fn code(&self) -> &'static str {
+        match self {
+            QueryError::UnterminatedQuote { .. } => "unterminated_quote",
+            QueryError::MissingValue { .. } => "missing_value",
+            QueryError::MissingTerm { .. } => "missing_term",
+            QueryError::UnknownFilter { .. } => "unknown_filter",
+            QueryError::InvalidDate { .. } => "invalid_date",
+            QueryError::InvalidState { .. } => "invalid_state",
+            QueryError::InvalidPriority { .. } => "invalid_priority",
+        }
+    }
+
+    /// Character position (0-based) of the offending term
+    pub fn position(&self) -> usize {
+        match self {
+            QueryError::UnterminatedQuote { position }
+            | QueryError::MissingTerm { position }
+            | QueryError::MissingValue { position, .. }
+            | QueryError::UnknownFilter { position, .. }
+            | QueryError::InvalidDate { position, .. }
+            | QueryError::InvalidState { position, .. }
+            | QueryError::InvalidPriority { position, .. } => *position,
+        }
+    }
+
+    /// The offending key or value, if any
+    pub fn subject(&self) -> Option<&str> {
+        match self {
+            QueryError::UnterminatedQuote { .. } | QueryError::MissingTerm { .. } => None,
+            QueryError::MissingValue { key, .. } | QueryError::UnknownFilter { key, .. } => Some(key),
+            QueryError::InvalidDate { value, .. }
+            | QueryError::InvalidState { value, .. }
+            | QueryError::InvalidPriority { value, .. } => Some(value),
+        }
+    }
+}
+
+impl fmt::Display for QueryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let column = self.position() + 1;
+        match self {
+            QueryError::UnterminatedQuote { .. } => write!(f, "unterminated quote at column {}", column),
+            QueryError::MissingValue { key, .. } => write!(f, "`{}:` needs a value (column {})", key, column),
+            QueryError::MissingTerm { .. } => write!(f, "`-` must be followed by a term (column {})", column),
+            QueryError::UnknownFilter { key, .. } => write!(f, "unknown filter `{}:` at column {}", key, column),
+            QueryError::InvalidDate { value, .. } => {
+                write!(f, "`{}` is not a date (use YYYY-MM-DD, today, tomorrow or none) at column {}", value, column)
+            }
+            QueryError::InvalidState { value, .. } => write!(
+                f,
+                "`{}` is not a state (use overdue, active, completed, snoozed or recurring) at column {}",
+                value, column
+            ),
+            QueryError::InvalidPriority { value, .. } => write!(
+                f,
+                "`{}` is not a priority (use none, low, medium, high or urgent) at column {}",
+                value, column
+            ),
+        }
+    }
+}
+
+impl std::error::Error for QueryError {}
+
+/// A raw token: optional `key:`, value, and whether the value was quoted
+struct Token {
+    position: usize,
+    negated: bool,
+    key: Option<String>,
+    value: String,
+    quoted: bool,
+}
+
+/// Read a quoted string starting after the opening quote at `chars[*i - 1]`
+fn read_quoted(chars: &[char], i: &mut usize, start: usize) -> Result<String, QueryError> {
+    let mut value = String::new();
+    while *i < chars.len() {
+        if chars[*i] == '"' {
+            *i += 1;
+            return Ok(value);
+        }
+        value.push(chars[*i]);
+        *i += 1;
+    }
+    Err(QueryError::UnterminatedQuote { position: start })
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, QueryError> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        if chars[i].is_whitespace() {
+            i += 1;
+            continue;
+        }
+
+        let position = i;
+        let negated = chars[i] == '-';
+        if negated {
+            i += 1;
+            if i == chars.len() || chars[i].is_whitespace() {
+                return Err(QueryError::MissingTerm { position });
+            }
+        }
+
+        if chars[i] == '"' {
+            let quote = i;
+            i += 1;
+            let value = read_quoted(&chars, &mut i, quote)?;
+            tokens.push(Token { position, negated, key: None, value, quoted: true });
+            continue;
+        }
+
+        // A quote glued to a word (e.g. `foo"bar`) starts a new token.
+        let mut word = String::new();
+        while i < chars.len() && !chars[i].is_whitespace() && chars[i] != '"' {
+            word.push(chars[i]);
+            i += 1;
+        }
+
+        // `key:value` where key is alphabetic; anything else (e.g. "10:30") is text.
+        let split = word
+            .split_once(':')
+            .filter(|(key, _)| !key.is_empty() && key.chars().all(|c| c.is_ascii_alphabetic()));
+        match split {
+            Some((key, value)) => {
+                let key = key.to_ascii_lowercase();
+                let (value, quoted) = if value.is_empty() && i < chars.len() && chars[i] == '"' {
+                    let quote = i;
+                    i += 1;
+                    (read_quoted(&chars, &mut i, quote)?, true)
+                } else {
+                    (value.to_string(), false)
+                };
+                tokens.push(Token { position, negated, key: Some(key), value, quoted });
+            }
+            None => {
+                tokens.push(Token { position, negated, key: None, value: word, quoted: false });
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+fn parse_due(value: &str, position: usize) -> Result<Term, QueryError> {
+    let (comparison, date) = Comparison::split(value);
+    let invalid = || QueryError::InvalidDate { value: value.to_string(), position };
+    let date = match date.to_ascii_lowercase().as_str() {
+        "none" if comparison == Comparison::Eq => return Ok(Term::NoDueDate),
+        "today" => DueDate::Today,
+        "tomorrow" => DueDate::Tomorrow,
+        other => DueDate::On(NaiveDate::parse_from_str(other, "%Y-%m-%d").map_err(|_| invalid())?),
+    };
+    Ok(Term::Due(comparison, date))
+}
+
+fn parse_term(token: &Token) -> Result<Term, QueryError> {
+    let position = token.position;
+    let Some(key) = token.key.as_deref() else {
+        return Ok(if token.quoted {
+            Term::Phrase(token.value.clone())
+        } else {
+            Term::Text(token.value.clone())
+        });
+    };
+    if token.value.is_empty() && !token.quoted {
+        return Err(QueryError::MissingValue { key: key.to_string(), position });
+    }
+    let value = token.value.as_str();
+
+    match key {
+        "tag" => Ok(Term::Tag(value.to_lowercase())),
+        "due" => parse_due(value, position),
+        "is" => State::parse(&value.to_ascii_lowercase())
+            .map(Term::Is)
+            .ok_or_else(|| QueryError::InvalidState { value: value.to_string(), position }),
+        "priority" => {
+            let (comparison, level) = Comparison::split(value);
+            Priority::parse(&level.to_ascii_lowercase())
+                .map(|priority| Term::Priority(comparison, priority))
+                .ok_or_else(|| QueryError::InvalidPriority { value: value.to_string(), position })
+        }
+        _ => Err(QueryError::UnknownFilter { key: key.to_string(), position }),
+    }
+}
+
+impl Query {
+    /// Parse a query string
+    pub fn parse(input: &str) -> Result<Self, QueryError> {
+        let clauses = tokenize(input)?
+            .iter()
+            .map(|token| {
+                parse_term(token).map(|term| Clause {
+                    negated: token.negated,
+                    term,
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Query { clauses })
+    }
+
+    /// A query matching `text` as a plain substring (no query syntax)
+    pub fn text(text: &str) -> Self {
+        let text = text.trim();
+        if text.is_empty() {
+            return Query::default();
+        }
+        Query {
+            clauses: vec![Clause {
+                negated: false,
+                term: Term::Phrase(text.to_string()),
+            }],
+        }
+    }
+
+    /// Parse `input`, treating it as plain text if it is not a valid query
+    pub fn parse_lenient(input: &str) -> Self {
+        Query::parse(input).unwrap_or_else(|_| Query::text(input))
+    }
+
+    /// Search words and phrases that must match (negated ones excluded)
+    fn search_words(&self) -> Vec<SearchWord<'_>> {
+        self.clauses
+            .iter()
+            .filter(|clause| !clause.negated)
+            .filter_map(|clause| match &clause.term {
+                Term::Text(text) => Some(SearchWord { text, exact: false }),
+                Term::Phrase(text) => Some(SearchWord { text, exact: true }),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Whether the query contains words or phrases to rank results by
+    pub fn has_search_words(&self) -> bool {
+        !self.search_words().is_empty()
+    }
+
+    /// Relevance and highlight ranges of `reminder` for this query's words.
+    ///
+    /// Returns `None` if the query has no search words or they do not match.
+    pub fn search_match(&self, reminder: &Reminder, tags: &[Tag]) -> Option<SearchMatch> {
+        let words = self.search_words();
+        if words.is_empty() {
+            return None;
+        }
+        fuzzy_match_reminder(reminder, tags, &words)
+    }
+
+    /// Whether the query has no clauses (matches everything)
+    pub fn is_empty(&self) -> bool {
+        self.clauses.is_empty()
+    }
+
+    /// Whether `reminder` matches every clause at time `now`
+    pub fn matches_at(&self, reminder: &Reminder, tags: &[Tag], now: Timestamp) -> bool {
+        self.clauses
+            .iter()
+            .all(|clause| term_matches(clause, reminder, tags, now) != clause.negated)
+    }
+
+    /// Whether `reminder` matches every clause now
+    pub fn matches(&self, reminder: &Reminder, tags: &[Tag]) -> bool {
+        self.matches_at(reminder, tags, Timestamp::now())
+    }
+}
+
+fn term_matches(clause: &Clause, reminder: &Reminder, tags: &[Tag], now: Timestamp) -> bool {
+    match &clause.term {
+        // Exclusions are literal so `-word` does not also hide near-misses.
+        Term::Text(text) | Term::Phrase(text) => {
+            let word = SearchWord {
+                text,
+                exact: clause.negated || matches!(clause.term, Term::Phrase(_)),
+            };
+            fuzzy_match_reminder(reminder, tags, &[word]).is_some()
+        }
+        Term::Tag(name) => tags
+            .iter()
+            .filter(|tag| reminder.tag_ids.contains(&tag.id))
+            .any(|tag| tag.name.to_lowercase() == *name),
+        Term::Due(comparison, date) => {
+            let today = now.to_local().naive().date();
+            let target = match date {
+                DueDate::On(date) => *date,
+                DueDate::Today => today,
+                DueDate::Tomorrow => today + Duration::days(1),
+            };
+            reminder
+                .due_device_local()
+                .is_some_and(|due| comparison.holds(due.date(), target))
+        }
+        Term::NoDueDate => reminder.due_date.is_none(),
+        Term::Is(state) => match state {
+            State::Overdue => is_overdue_at(reminder, now),
+            State::Active => !reminder.completed,
+            State::Completed => reminder.completed,
+            State::Snoozed => !reminder.completed && is_snoozed_at(reminder, now),
+            State::Recurring => reminder.recurrence.is_some(),
+        },
+        Term::Priority(comparison, priority) => comparison.holds(reminder.priority, *priority),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::datetime::LocalDateTime;
+    use crate::models::{Recurrence, RecurrenceFrequency};
+    use crate::test_support::{datetime, reminder, tag};
+
+    fn clause(negated: bool, term: Term) -> Clause {
+        Clause { negated, term }
+    }
+
+    fn due_on(id: &str, year: i32, month: u32, day: u32) -> Reminder {
+        Reminder {
+            due_date: Some(LocalDateTime::floating(datetime(year, month, day, 9, 0))),
+            ..reminder(id, id)
+        }
+    }
+
+    /// Noon on 2024-01-15 in the device's time zone
+    fn noon_jan_15() -> Timestamp {
+        LocalDateTime::floating(datetime(2024, 1, 15, 12, 0)).to_timestamp()
+    }
+
+    fn matching_ids<'a>(query: &str, reminders: &'a [Reminder], tags: &[Tag]) -> Vec<&'a str> {
+        let query = Query::parse(query).unwrap();
+        reminders
+            .iter()
+            .filter(|r| query.matches_at(r, tags, noon_jan_15()))
+            .map(|r| r.id.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn parses_every_kind_of_term() {
+        let query = Query::parse(r#"milk "oat milk" tag:work tag:"deep work" due:<=2024-01-31 due:none is:done priority:>high -later"#)
+            .unwrap();
+        assert_eq!(
+            query.clauses,
+            [
+                clause(false, Term::Text("milk".into())),
+                clause(false, Term::Phrase("oat milk".into())),
+                clause(false, Term::Tag("work".into())),
+                clause(false, Term::Tag("deep work".into())),
+                clause(false, Term::Due(Comparison::Le, DueDate::On(NaiveDate::from_ymd_opt(2024, 1, 31).unwrap()))),
+                clause(false, Term::NoDueDate),
+                clause(false, Term::Is(State::Completed)),
+                clause(false, Term::Priority(Comparison::Gt, Priority::High)),
+                clause(true, Term::Text("later".into())),
+            ]
+        );
+    }
+
+    #[test]
+    fn keys_are_case_insensitive_and_need_letters() {
+        assert_eq!(Query::parse("TAG:Home").unwrap().clauses, [clause(false, Term::Tag("home".into()))]);
+        assert_eq!(Query::parse("IS:Overdue").unwrap().clauses, [clause(false, Term::Is(State::Overdue))]);
+        // Times and URLs-like words without an alphabetic key stay text
+        assert_eq!(Query::parse("10:30").unwrap().clauses, [clause(false, Term::Text("10:30".into()))]);
+        assert_eq!(Query::parse(":x").unwrap().clauses, [clause(false, Term::Text(":x".into()))]);
+    }
+
+    #[test]
+    fn quotes_split_words_and_keep_spaces() {
+        assert_eq!(
+            Query::parse(r#"foo"bar baz" -"not this""#).unwrap().clauses,
+            [
+                clause(false, Term::Text("foo".into())),
+                clause(false, Term::Phrase("bar baz".into())),
+                clause(true, Term::Phrase("not this".into())),
+            ]
+        );
+        assert_eq!(Query::parse("").unwrap(), Query::default());
+        assert_eq!(Query::parse("   ").unwrap(), Query::default());
+    }
+
+    #[test]
+    fn operators_bind_to_their_value() {
+        for (input, comparison) in [
+            ("priority:low", Comparison::Eq),
+            ("priority:=low", Comparison::Eq),
+            ("priority:<low", Comparison::Lt),
+            ("priority:<=low", Comparison::Le),
+            ("priority:>low", Comparison::Gt),
+            ("priority:>=low", Comparison::Ge),
+        ] {
+            assert_eq!(
+                Query::parse(input).unwrap().clauses,
+                [clause(false, Term::Priority(comparison, Priority::Low))],
+                "{}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn every_error_has_a_code_position_and_subject() {
+        let cases = [
+            (r#"a "open"#, QueryError::UnterminatedQuote { position: 2 }, "unterminated_quote", None),
+            (r#"tag:"open"#, QueryError::UnterminatedQuote { position: 4 }, "unterminated_quote", None),
+            ("a due:", QueryError::MissingValue { key: "due".into(), position: 2 }, "missing_value", Some("due")),
+            ("a - b", QueryError::MissingTerm { position: 2 }, "missing_term", None),
+            ("a -", QueryError::MissingTerm { position: 2 }, "missing_term", None),
+            ("color:red", QueryError::UnknownFilter { key: "color".into(), position: 0 }, "unknown_filter", Some("color")),
+            ("due:soon", QueryError::InvalidDate { value: "soon".into(), position: 0 }, "invalid_date", Some("soon")),
+            ("due:<none", QueryError::InvalidDate { value: "<none".into(), position: 0 }, "invalid_date", Some("<none")),
+            ("is:late", QueryError::InvalidState { value: "late".into(), position: 0 }, "invalid_state", Some("late")),
+            (
+                "x priority:>=max",
+                QueryError::InvalidPriority { value: ">=max".into(), position: 2 },
+                "invalid_priority",
+                Some(">=max"),
+            ),
+        ];
+        for (input, expected, code, subject) in cases {
+            let error = Query::parse(input).unwrap_err();
+            assert_eq!(error, expected, "{}", input);
+            assert_eq!(error.code(), code);
+            assert_eq!(error.position(), expected.position());
+            assert_eq!(error.subject(), subject);
+            assert!(error.to_string().contains(&format!("column {}", expected.position() + 1)));
+        }
+    }
+
+    #[test]
+    fn invalid_queries_fall_back_to_plain_text() {
+        assert_eq!(Query::parse_lenient("color:red"), Query::text("color:red"));
+        assert_eq!(Query::parse_lenient("  "), Query::default());
+        assert_eq!(
+            Query::parse_lenient("tag:work").clauses,
+            [clause(false, Term::Tag("work".into()))]
+        );
+    }
+
+    #[test]
+    fn all_clauses_must_match_and_negation_applies_to_one_term() {
+        let tags = [tag("w", "Work"), tag("h", "Deep work")];
+        let mut report = Reminder {
+            tag_ids: vec!["w".into()],
+            ..reminder("report", "Quarterly report")
+        };
+        report.priority = Priority::High;
+        let essay = Reminder {
+            tag_ids: vec!["h".into()],
+            ..reminder("essay", "Essay draft")
+        };
+        let groceries = reminder("groceries", "Buy groceries");
+        let reminders = [report, essay, groceries];
+
+        assert_eq!(matching_ids("tag:work", &reminders, &tags), ["report"]);
+        assert_eq!(matching_ids(r#"tag:"deep work""#, &reminders, &tags), ["essay"]);
+        assert_eq!(matching_ids("-tag:work", &reminders, &tags), ["essay", "groceries"]);
+        assert_eq!(matching_ids("-tag:work -groceries", &reminders, &tags), ["essay"]);
+        assert_eq!(matching_ids("tag:work priority:>=high", &reminders, &tags), ["report"]);
+        assert_eq!(matching_ids("tag:work priority:urgent", &reminders, &tags), Vec::<&str>::new());
+        assert_eq!(matching_ids("priority:none", &reminders, &tags), ["essay", "groceries"]);
+        // Fuzzy words tolerate typos, exclusions and phrases don't
+        assert_eq!(matching_ids("grocries", &reminders, &tags), ["groceries"]);
+        assert_eq!(matching_ids("-grocries", &reminders, &tags), ["report", "essay", "groceries"]);
+        assert_eq!(matching_ids(r#""buy grocries""#, &reminders, &tags), Vec::<&str>::new());
+    }
+
+    #[test]
+    fn date_keywords_use_the_given_clock() {
+        let mut done = due_on("done", 2024, 1, 10);
+        done.completed = true;
+        let reminders = [
+            due_on("past", 2024, 1, 10),
+            due_on("today", 2024, 1, 15),
+            due_on("tomorrow", 2024, 1, 16),
+            due_on("later", 2024, 2, 1),
+            reminder("undated", "Undated"),
+            done,
+        ];
+
+        assert_eq!(matching_ids("due:today", &reminders, &[]), ["today"]);
+        assert_eq!(matching_ids("due:tomorrow", &reminders, &[]), ["tomorrow"]);
+        assert_eq!(matching_ids("due:<today", &reminders, &[]), ["past", "done"]);
+        assert_eq!(matching_ids("due:>=tomorrow", &reminders, &[]), ["tomorrow", "later"]);
+        assert_eq!(matching_ids("due:<=2024-01-16 due:>today", &reminders, &[]), ["tomorrow"]);
+        assert_eq!(matching_ids("due:none", &reminders, &[]), ["undated"]);
+        assert_eq!(matching_ids("-due:none is:active", &reminders, &[]), ["past", "today", "tomorrow", "later"]);
+        assert_eq!(matching_ids("is:overdue", &reminders, &[]), ["past", "today"]);
+        assert_eq!(matching_ids("is:completed", &reminders, &[]), ["done"]);
+    }
+
+    #[test]
+    fn state_terms() {
+        let recurring = Reminder {
+            recurrence: Some(Recurrence::new(RecurrenceFrequency::Weekly)),
+            ..due_on("recurring", 2024, 1, 20)
+        };
+        let snoozed = Reminder {
+            snoozed_until: Some(noon_jan_15().add_millis(3_600_000)),
+            ..due_on("snoozed", 2024, 1, 14)
+        };
+        let reminders = [recurring, snoozed];
+
+        assert_eq!(matching_ids("is:recurring", &reminders, &[]), ["recurring"]);
+        assert_eq!(matching_ids("is:snoozed", &reminders, &[]), ["snoozed"]);
+        // A snoozed reminder isn't overdue until the snooze ends
+        assert_eq!(matching_ids("is:overdue", &reminders, &[]), Vec::<&str>::new());
+        let later = noon_jan_15().add_millis(2 * 3_600_000);
+        assert!(Query::parse("is:overdue").unwrap().matches_at(&reminders[1], &[], later));
+    }
+}
@@ -0,0 +1,274 @@
+//! Recurrence calculations for repeating reminders
+//!
+//! This module computes occurrences of a `Recurrence` rule. All arithmetic is
+//! done on the wall-clock time of the due date, so a reminder due at 09:00
+//! stays at 09:00 across DST changes.
+
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime};
+
+use crate::datetime::LocalDateTime;
+use crate::models::{Recurrence, RecurrenceFrequency, Reminder, Weekday};
+
+/// Upper bound on the number of occurrences visited while expanding a rule.
+const MAX_EXPANSION_STEPS: usize = 10_000;
+
+impl Weekday {
+    fn from_chrono(day: chrono::Weekday) -> Self {
+        Weekday::ALL[day.num_days_from_monday() as usize]
+    }
+}
+
+impl Recurrence {
+    fn allows_weekday(&self, date: NaiveDate) -> bool {
+        self.by_weekday.is_empty() || self.by_weekday.contains(&Weekday::from_chrono(date.weekday()))
+    }
+
+    /// Next occurrence strictly after `current`, ignoring `count` and `until`.
+    fn step(&self, current: NaiveDateTime) -> Option<NaiveDateTime> {
+        let interval = self.interval.max(1);
+        let time = current.time();
+        let date = current.date();
+
+        let next_date = match self.frequency {
+            RecurrenceFrequency::Daily => {
+                let mut candidate = date;
+                loop {
+                    candidate = candidate.checked_add_signed(Duration::days(interval as i64))?;
+                    if self.allows_weekday(candidate) {
+                        break Some(candidate);
+                    }
+                    if candidate - date > Duration::days(7 * interval as i64) {
+                        break None;
+                    }
+                }
+            }
+            RecurrenceFrequency::Weekly if !self.by_weekday.is_empty() => {
+                let week_start = |d: NaiveDate| d - Duration::days(d.weekday().num_days_from_monday() as i64);
+                let anchor = week_start(date);
+                (1..=7 * (interval as i64 + 1))
+                    .filter_map(|offset| date.checked_add_signed(Duration::days(offset)))
+                    .find(|candidate| {
+                        let weeks = (week_start(*candidate) - anchor).num_weeks();
+                        weeks % interval as i64 == 0 && self.allows_weekday(*candidate)
+                    })
+            }
+            RecurrenceFrequency::Weekly => date.checked_add_signed(Duration::weeks(interval as i64)),
+            RecurrenceFrequency::Monthly => {
+                // Months without this day (e.g. the 31st) are skipped, as in RFC 5545.
+                (1..=48).find_map(|k| {
+                    let months = date.month0() as i64 + k * interval as i64;
+                    let year = date.year() + (months / 12) as i32;
+                    NaiveDate::from_ymd_opt(year, (months % 12) as u32 + 1, date.day())
+                })
+            }
+            RecurrenceFrequency::Yearly => {
+                // February 29th only recurs in leap years.
+                (1..=8).find_map(|k| {
+                    NaiveDate::from_ymd_opt(date.year() + k * interval as i32, date.month(), date.day())
+                })
+            }
+        }?;
+
+        Some(next_date.and_time(time))
+    }
+
+    /// Next occurrence after `current`, honouring `until` and `count`.
+    ///
+    /// `current` is assumed to be an occurrence itself, so a rule with a
+    /// remaining `count` of 1 has no next occurrence.
+    pub fn next_after(&self, current: NaiveDateTime) -> Option<NaiveDateTime> {
+        if matches!(self.count, Some(n) if n <= 1) {
+            return None;
+        }
+        let next = self.step(current)?;
+        match self.until {
+            Some(until) if next.date() > until => None,
+            _ => Some(next),
+        }
+    }
+
+    /// All occurrences starting at `start` whose date falls within `from..=to`.
+    pub fn occurrences_between(&self, start: NaiveDateTime, from: NaiveDate, to: NaiveDate) -> Vec<NaiveDateTime> {
+        let mut occurrences = Vec::new();
+        let mut rule = self.clone();
+        let mut current = start;
+
+        for _ in 0..MAX_EXPANSION_STEPS {
+            if current.date() > to {
+                break;
+            }
+            if current.date() >= from {
+                occurrences.push(current);
+            }
+            let Some(next) = rule.next_after(current) else {
+                break;
+            };
+            rule.count = rule.count.map(|n| n - 1);
+            current = next;
+        }
+
+        occurrences
+    }
+}
+
+/// Roll a recurring reminder forward to its next occurrence.
+///
+/// Returns `true` if the reminder was moved to a new due date (and decrements
+/// the remaining `count` and resets its checklist). Returns `false` for one-off reminders, reminders
+/// without a parseable due date, and rules that have run out of occurrences;
+/// callers should then mark the reminder as completed instead.
+pub fn advance(reminder: &mut Reminder) -> bool {
+    let Some(rule) = reminder.recurrence.as_mut() else {
+        return false;
+    };
+    let Some(due) = reminder.due_date else {
+        return false;
+    };
+    let Some(next) = rule.next_after(due.naive()) else {
+        return false;
+    };
+
+    rule.count = rule.count.map(|n| n - 1);
+    reminder.due_date = Some(due.with_naive(next));
+    reminder.completed = false;
+    reminder.snoozed_until = None;
+    for item in &mut reminder.checklist {
+        item.completed = false;
+    }
+    true
+}
+
+/// Due dates of every occurrence of `reminder` falling within `from..=to`.
+///
+/// One-off reminders yield their own due date if it is in range.
+pub fn occurrence_dates(reminder: &Reminder, from: NaiveDate, to: NaiveDate) -> Vec<LocalDateTime> {
+    let Some(due) = reminder.due_date else {
+        return Vec::new();
+    };
+
+    match &reminder.recurrence {
+        Some(rule) if !reminder.completed => rule
+            .occurrences_between(due.naive(), from, to)
+            .into_iter()
+            .map(|naive| due.with_naive(naive))
+            .collect(),
+        _ if (from..=to).contains(&due.naive().date()) => vec![due],
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checklist::new_item;
+    use crate::datetime::Timestamp;
+    use crate::test_support::{datetime, due_reminder, reminder};
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    fn rule(frequency: RecurrenceFrequency) -> Recurrence {
+        Recurrence::new(frequency)
+    }
+
+    fn dates(occurrences: &[NaiveDateTime]) -> Vec<NaiveDate> {
+        occurrences.iter().map(NaiveDateTime::date).collect()
+    }
+
+    #[test]
+    fn monthly_rules_skip_months_without_the_day() {
+        let monthly = rule(RecurrenceFrequency::Monthly);
+        let occurrences = monthly.occurrences_between(datetime(2024, 1, 31, 9, 0), date(2024, 1, 1), date(2024, 8, 31));
+        assert_eq!(
+            dates(&occurrences),
+            [date(2024, 1, 31), date(2024, 3, 31), date(2024, 5, 31), date(2024, 7, 31), date(2024, 8, 31)]
+        );
+        assert!(occurrences.iter().all(|o| o.time() == datetime(2024, 1, 31, 9, 0).time()));
+
+        let every_other = Recurrence { interval: 2, ..monthly };
+        assert_eq!(every_other.next_after(datetime(2024, 11, 30, 9, 0)), Some(datetime(2025, 1, 30, 9, 0)));
+    }
+
+    #[test]
+    fn yearly_rules_on_february_29th_wait_for_leap_years() {
+        let yearly = rule(RecurrenceFrequency::Yearly);
+        assert_eq!(yearly.next_after(datetime(2024, 2, 29, 8, 0)), Some(datetime(2028, 2, 29, 8, 0)));
+        assert_eq!(yearly.next_after(datetime(2023, 3, 1, 8, 0)), Some(datetime(2024, 3, 1, 8, 0)));
+    }
+
+    #[test]
+    fn weekday_rules_honour_the_interval() {
+        let fortnightly = Recurrence {
+            interval: 2,
+            by_weekday: vec![Weekday::Mo, Weekday::Fr],
+            ..rule(RecurrenceFrequency::Weekly)
+        };
+        // 2024-01-01 is a Monday
+        let occurrences = fortnightly.occurrences_between(datetime(2024, 1, 1, 9, 0), date(2024, 1, 1), date(2024, 1, 31));
+        assert_eq!(
+            dates(&occurrences),
+            [date(2024, 1, 1), date(2024, 1, 5), date(2024, 1, 15), date(2024, 1, 19), date(2024, 1, 29)]
+        );
+
+        let weekdays = Recurrence {
+            by_weekday: Weekday::ALL[..5].to_vec(),
+            ..rule(RecurrenceFrequency::Daily)
+        };
+        assert_eq!(weekdays.next_after(datetime(2024, 1, 5, 9, 0)), Some(datetime(2024, 1, 8, 9, 0)));
+    }
+
+    #[test]
+    fn count_and_until_end_the_rule() {
+        let three_times = Recurrence {
+            count: Some(3),
+            ..rule(RecurrenceFrequency::Daily)
+        };
+        let occurrences = three_times.occurrences_between(datetime(2024, 1, 1, 9, 0), date(2024, 1, 1), date(2024, 12, 31));
+        assert_eq!(dates(&occurrences), [date(2024, 1, 1), date(2024, 1, 2), date(2024, 1, 3)]);
+        let last = Recurrence { count: Some(1), ..three_times };
+        assert_eq!(last.next_after(datetime(2024, 1, 3, 9, 0)), None);
+        // `step` alone ignores the limits
+        assert_eq!(last.step(datetime(2024, 1, 3, 9, 0)), Some(datetime(2024, 1, 4, 9, 0)));
+
+        let until = Recurrence {
+            until: Some(date(2024, 1, 15)),
+            ..rule(RecurrenceFrequency::Weekly)
+        };
+        let occurrences = until.occurrences_between(datetime(2024, 1, 1, 9, 0), date(2024, 1, 5), date(2024, 12, 31));
+        assert_eq!(dates(&occurrences), [date(2024, 1, 8), date(2024, 1, 15)]);
+        assert_eq!(until.next_after(datetime(2024, 1, 15, 9, 0)), None);
+        assert_eq!(until.step(datetime(2024, 1, 15, 9, 0)), Some(datetime(2024, 1, 22, 9, 0)));
+    }
+
+    #[test]
+    fn completing_a_recurring_reminder_rolls_it_forward() {
+        let mut monthly = due_reminder("monthly", datetime(2024, 1, 31, 9, 0));
+        monthly.time_zone = Some(chrono_tz::Europe::Paris);
+        monthly.recurrence = Some(Recurrence {
+            count: Some(2),
+            ..rule(RecurrenceFrequency::Monthly)
+        });
+        monthly.completed = true;
+        monthly.snoozed_until = Some(Timestamp::from_millis(1));
+        let mut item = new_item(&[], "Step");
+        item.completed = true;
+        monthly.checklist = vec![item];
+
+        assert!(advance(&mut monthly));
+        assert_eq!(monthly.due_date, Some(LocalDateTime::floating(datetime(2024, 3, 31, 9, 0))));
+        assert_eq!(monthly.time_zone, Some(chrono_tz::Europe::Paris));
+        assert_eq!(monthly.recurrence.as_ref().unwrap().count, Some(1));
+        assert!(!monthly.completed);
+        assert_eq!(monthly.snoozed_until, None);
+        assert!(!monthly.checklist[0].completed);
+
+        // The last occurrence is completed instead
+        assert!(!advance(&mut monthly));
+        assert_eq!(monthly.due_date, Some(LocalDateTime::floating(datetime(2024, 3, 31, 9, 0))));
+        assert!(!advance(&mut due_reminder("once", datetime(2024, 1, 31, 9, 0))));
+        let mut undated = reminder("undated", "Undated");
+        undated.recurrence = Some(rule(RecurrenceFrequency::Daily));
+        assert!(!advance(&mut undated));
+    }
+}
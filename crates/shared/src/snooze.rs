@@ -0,0 +1,167 @@
+//! Snoozing reminders
+//!
+//! A snoozed reminder is not considered overdue and is not notified until its
+//! `snoozed_until` time passes. Every snooze is recorded in `snooze_log` so
+//! statistics can report how often reminders get pushed back.
+
+use chrono::Local;
+
+use crate::datetime::{LocalDateTime, Timestamp};
+use crate::models::{Reminder, SnoozeEntry};
+
+/// Hour of day (local time) that "tomorrow morning" snoozes until
+const MORNING_HOUR: u32 = 9;
+
+/// How long to snooze a reminder for
+#[derive(Clone, Debug, PartialEq)]
+pub enum SnoozeOption {
+    /// Five minutes from now
+    FiveMinutes,
+    /// One hour from now
+    OneHour,
+    /// 09:00 local time tomorrow
+    TomorrowMorning,
+    /// A specific wall-clock time
+    Until(LocalDateTime),
+}
+
+impl SnoozeOption {
+    /// Convert option to string representation (custom times are "custom")
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SnoozeOption::FiveMinutes => "5m",
+            SnoozeOption::OneHour => "1h",
+            SnoozeOption::TomorrowMorning => "tomorrow",
+            SnoozeOption::Until(_) => "custom",
+        }
+    }
+
+    /// Time this option snoozes until, relative to `now` in time zone `tz`.
+    ///
+    /// Returns `None` if the resulting time is not after `now`.
+    pub fn resolve_in<Tz: chrono::TimeZone>(&self, now: Timestamp, tz: &Tz) -> Option<Timestamp> {
+        let until = match self {
+            SnoozeOption::FiveMinutes => now.add_millis(5 * 60_000),
+            SnoozeOption::OneHour => now.add_millis(60 * 60_000),
+            SnoozeOption::TomorrowMorning => {
+                let today = now.to_utc().with_timezone(tz).date_naive();
+                let morning = today.succ_opt()?.and_hms_opt(MORNING_HOUR, 0, 0)?;
+                LocalDateTime::floating(morning).to_timestamp_in(tz)
+            }
+            SnoozeOption::Until(until) => until.to_timestamp_in(tz),
+        };
+        (until > now).then_some(until)
+    }
+
+    /// Time this option snoozes until, relative to `now` in the device's time zone
+    pub fn resolve(&self, now: Timestamp) -> Option<Timestamp> {
+        self.resolve_in(now, &Local)
+    }
+}
+
+/// Whether `reminder` is snoozed at `now`
+pub fn is_snoozed_at(reminder: &Reminder, now: Timestamp) -> bool {
+    reminder.snoozed_until.is_some_and(|until| until > now)
+}
+
+/// Whether `reminder` is currently snoozed
+pub fn is_snoozed(reminder: &Reminder) -> bool {
+    is_snoozed_at(reminder, Timestamp::now())
+}
+
+/// Snooze `reminder` and record it in the snooze log.
+///
+/// Returns `false` (leaving the reminder unchanged) if the option does not
+/// resolve to a time in the future.
+pub fn snooze(reminder: &mut Reminder, option: &SnoozeOption) -> bool {
+    let now = Timestamp::now();
+    let Some(until) = option.resolve(now) else {
+        return false;
+    };
+    reminder.snooze_log.push(SnoozeEntry {
+        snoozed_at: now,
+        until,
+    });
+    reminder.snoozed_until = Some(until);
+    true
+}
+
+/// Cancel an active snooze (the snooze log is kept)
+pub fn clear_snooze(reminder: &mut Reminder) {
+    reminder.snoozed_until = None;
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::FixedOffset;
+
+    use super::*;
+    use crate::test_support::{datetime, reminder};
+
+    fn utc(year: i32, month: u32, day: u32, hour: u32, minute: u32) -> Timestamp {
+        LocalDateTime::floating(datetime(year, month, day, hour, minute)).to_timestamp_in(&chrono_tz::UTC)
+    }
+
+    #[test]
+    fn options_resolve_relative_to_now() {
+        let now = utc(2024, 1, 15, 10, 0);
+        let tz = chrono_tz::UTC;
+        assert_eq!(SnoozeOption::FiveMinutes.resolve_in(now, &tz), Some(utc(2024, 1, 15, 10, 5)));
+        assert_eq!(SnoozeOption::OneHour.resolve_in(now, &tz), Some(utc(2024, 1, 15, 11, 0)));
+
+        let later = SnoozeOption::Until(LocalDateTime::floating(datetime(2024, 1, 15, 18, 30)));
+        assert_eq!(later.resolve_in(now, &tz), Some(utc(2024, 1, 15, 18, 30)));
+        assert_eq!(later.as_str(), "custom");
+        for past in [datetime(2024, 1, 15, 9, 59), datetime(2024, 1, 15, 10, 0)] {
+            assert_eq!(SnoozeOption::Until(LocalDateTime::floating(past)).resolve_in(now, &tz), None);
+        }
+    }
+
+    #[test]
+    fn tomorrow_morning_follows_the_local_date() {
+        let morning = |now: Timestamp, tz: &FixedOffset| SnoozeOption::TomorrowMorning.resolve_in(now, tz);
+        let utc_zone = FixedOffset::east_opt(0).unwrap();
+        assert_eq!(morning(utc(2024, 1, 15, 23, 59), &utc_zone), Some(utc(2024, 1, 16, 9, 0)));
+        assert_eq!(morning(utc(2024, 1, 16, 0, 1), &utc_zone), Some(utc(2024, 1, 17, 9, 0)));
+
+        // 20:00 UTC is already 05:00 the next day in UTC+9, and 15:00 the same day in UTC-5
+        let tokyo = FixedOffset::east_opt(9 * 3600).unwrap();
+        assert_eq!(morning(utc(2024, 1, 15, 20, 0), &tokyo), Some(utc(2024, 1, 17, 0, 0)));
+        let new_york = FixedOffset::west_opt(5 * 3600).unwrap();
+        assert_eq!(morning(utc(2024, 1, 15, 20, 0), &new_york), Some(utc(2024, 1, 16, 14, 0)));
+    }
+
+    #[test]
+    fn snoozing_is_logged() {
+        let mut snoozed = reminder("a", "A");
+        let until = LocalDateTime::floating(datetime(2100, 1, 1, 9, 0));
+        assert!(snooze(&mut snoozed, &SnoozeOption::Until(until)));
+        assert!(snooze(&mut snoozed, &SnoozeOption::FiveMinutes));
+        assert_eq!(snoozed.snooze_log.len(), 2);
+        assert_eq!(snoozed.snooze_log[0].until, until.to_timestamp());
+        assert_eq!(snoozed.snoozed_until, Some(snoozed.snooze_log[1].until));
+        assert!(snoozed.snooze_log[1].snoozed_at < snoozed.snooze_log[1].until);
+        assert!(is_snoozed(&snoozed));
+
+        // A time in the past changes nothing
+        let before = snoozed.clone();
+        let past = SnoozeOption::Until(LocalDateTime::floating(datetime(2000, 1, 1, 9, 0)));
+        assert!(!snooze(&mut snoozed, &past));
+        assert_eq!(snoozed, before);
+
+        clear_snooze(&mut snoozed);
+        assert_eq!(snoozed.snoozed_until, None);
+        assert_eq!(snoozed.snooze_log.len(), 2);
+    }
+
+    #[test]
+    fn a_snooze_ends_at_its_time() {
+        let mut snoozed = reminder("a", "A");
+        let until = utc(2024, 1, 15, 10, 0);
+        assert!(!is_snoozed_at(&snoozed, until));
+        snoozed.snoozed_until = Some(until);
+        assert!(is_snoozed_at(&snoozed, until.add_millis(-1)));
+        assert!(!is_snoozed_at(&snoozed, until));
+        assert!(!is_snoozed_at(&snoozed, until.add_millis(1)));
+    }
+}
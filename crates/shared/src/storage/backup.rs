@@ -0,0 +1,613 @@
+//! Backups: every reminder, tag and setting in one versioned JSON file
+//!
+//! A backup records its format version, the storage schema version of the
+//! data in it and a checksum of that data, so a damaged or hand-edited file
+//! is rejected before anything is imported.
+//!
+//! Importing takes two steps: `Backup::preview` works out what the chosen
+//! `ImportMode` would change, listing every item that exists both locally
+//! and in the backup with different contents, and `ImportPreview::apply`
+//! saves exactly that result.
+
+use std::collections::HashSet;
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::{
+    fnv1a, Repository, StorageError, REMINDERS_V2_KEY, SAVED_VIEWS_V1_KEY, SCHEMA_VERSION, TAGS_V1_KEY,
+};
+use crate::datetime::Timestamp;
+use crate::models::{Reminder, SavedView, Tag};
+
+/// Marker identifying a backup file
+pub const BACKUP_FORMAT: &str = "remind-me-backup";
+
+/// Backup format version written by this build
+pub const BACKUP_VERSION: u32 = 1;
+
+/// Everything a backup holds
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BackupData {
+    pub reminders: Vec<Reminder>,
+    pub tags: Vec<Tag>,
+    #[serde(default)]
+    pub settings: BackupSettings,
+}
+
+/// Settings included in a backup
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BackupSettings {
+    #[serde(default)]
+    pub saved_views: Vec<SavedView>,
+    /// Interface language (e.g., "zh-Hans"), if the platform keeps one
+    #[serde(default)]
+    pub locale: Option<String>,
+}
+
+/// A backup file's contents
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Backup {
+    /// Always `BACKUP_FORMAT`
+    pub format: String,
+    /// Backup format version (see `BACKUP_VERSION`)
+    pub version: u32,
+    /// Storage schema version of `data` (see `SCHEMA_VERSION`)
+    pub schema_version: u32,
+    pub exported_at: Timestamp,
+    /// Checksum of `data`, checked by `Backup::parse`
+    pub checksum: String,
+    pub data: BackupData,
+}
+
+/// Error reading a backup or importing it
+#[derive(Debug, Clone)]
+pub enum BackupError {
+    /// The file is not a backup, or is not valid JSON
+    InvalidFormat(String),
+    /// The backup was made by a newer version of the app
+    UnsupportedVersion(u32),
+    /// The data doesn't match the checksum (damaged or edited by hand)
+    ChecksumMismatch,
+    /// Reading or saving the app's data failed
+    Storage(StorageError),
+}
+
+impl fmt::Display for BackupError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BackupError::InvalidFormat(reason) => write!(f, "not a valid backup: {}", reason),
+            BackupError::UnsupportedVersion(version) => {
+                write!(f, "backup uses version {}, newer than this app supports", version)
+            }
+            BackupError::ChecksumMismatch => write!(f, "backup is damaged (checksum mismatch)"),
+            BackupError::Storage(error) => error.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for BackupError {}
+
+impl From<StorageError> for BackupError {
+    fn from(error: StorageError) -> Self {
+        BackupError::Storage(error)
+    }
+}
+
+/// How a backup is combined with the data already stored
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportMode {
+    /// Stored data is replaced by the backup
+    Replace,
+    /// Backup items replace stored items with the same id; others are kept
+    MergeById,
+    /// Like `MergeById`, but a reminder is only replaced by a newer one
+    /// (tags and saved views have no modification time, so stored ones win)
+    KeepNewest,
+}
+
+impl ImportMode {
+    pub const ALL: [ImportMode; 3] = [ImportMode::Replace, ImportMode::MergeById, ImportMode::KeepNewest];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ImportMode::Replace => "replace",
+            ImportMode::MergeById => "merge",
+            ImportMode::KeepNewest => "keep_newest",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        ImportMode::ALL.into_iter().find(|mode| mode.as_str() == s)
+    }
+}
+
+/// Kind of item in an `ImportConflict`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BackupItemKind {
+    Reminder,
+    Tag,
+    SavedView,
+}
+
+/// Which copy of a conflicting item is kept
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resolution {
+    UseBackup,
+    KeepLocal,
+}
+
+/// An item stored both locally and in the backup, with different contents
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportConflict {
+    pub kind: BackupItemKind,
+    pub id: String,
+    /// Title or name of the stored item
+    pub local_name: String,
+    /// Title or name of the backup's item
+    pub backup_name: String,
+    pub resolution: Resolution,
+}
+
+/// What importing a backup will do; nothing is saved until `apply`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportPreview {
+    pub mode: ImportMode,
+    /// Items only in the backup
+    pub added: usize,
+    /// Stored items the import removes (`ImportMode::Replace` only)
+    pub removed: usize,
+    pub conflicts: Vec<ImportConflict>,
+    /// Locale to switch to (`ImportMode::Replace` only)
+    pub locale: Option<String>,
+    reminders: Vec<Reminder>,
+    tags: Vec<Tag>,
+    saved_views: Vec<SavedView>,
+}
+
+impl Backup {
+    /// Back up everything stored in `repository`, with the current `locale`
+    ///
+    /// Fails if anything could not be loaded, rather than write a backup
+    /// that is silently missing items.
+    pub fn create(repository: &Repository, locale: Option<&str>) -> Result<Self, StorageError> {
+        let reminders = repository.load_reminders();
+        let tags = repository.load_tags();
+        let views = repository.load_saved_views();
+        if let Some(error) = reminders.error.or(tags.error).or(views.error) {
+            return Err(error);
+        }
+
+        let data = BackupData {
+            reminders: reminders.items,
+            tags: tags.items,
+            settings: BackupSettings {
+                saved_views: views.items,
+                locale: locale.map(str::to_string),
+            },
+        };
+        let value = serde_json::to_value(&data).map_err(|_| StorageError::SerializationFailed)?;
+        Ok(Self {
+            format: BACKUP_FORMAT.to_string(),
+            version: BACKUP_VERSION,
+            schema_version: SCHEMA_VERSION,
+            exported_at: Timestamp::now(),
+            checksum: checksum(&value),
+            data,
+        })
+    }
+
+    /// Read a backup file, checking its format, versions and checksum
+    pub fn parse(json: &str) -> Result<Self, BackupError> {
+        let value: Value = serde_json::from_str(json).map_err(|e| BackupError::InvalidFormat(e.to_string()))?;
+        if value.get("format").and_then(Value::as_str) != Some(BACKUP_FORMAT) {
+            return Err(BackupError::InvalidFormat("missing backup marker".to_string()));
+        }
+        for (field, supported) in [("version", BACKUP_VERSION), ("schema_version", SCHEMA_VERSION)] {
+            let version = value
+                .get(field)
+                .and_then(Value::as_u64)
+                .ok_or_else(|| BackupError::InvalidFormat(format!("missing {}", field)))?;
+            if version > supported as u64 {
+                return Err(BackupError::UnsupportedVersion(version.min(u32::MAX as u64) as u32));
+            }
+        }
+        let data = value
+            .get("data")
+            .ok_or_else(|| BackupError::InvalidFormat("missing data".to_string()))?;
+        if value.get("checksum").and_then(Value::as_str) != Some(checksum(data).as_str()) {
+            return Err(BackupError::ChecksumMismatch);
+        }
+        serde_json::from_value(value).map_err(|e| BackupError::InvalidFormat(e.to_string()))
+    }
+
+    /// The backup as pretty-printed JSON
+    pub fn to_json(&self) -> Result<String, StorageError> {
+        serde_json::to_string_pretty(self).map_err(|_| StorageError::SerializationFailed)
+    }
+
+    /// Suggested file name (e.g., `remind-me-backup-2024-01-31.json`)
+    pub fn file_name(&self) -> String {
+        format!("remind-me-backup-{}.json", self.exported_at.to_local().naive().format("%Y-%m-%d"))
+    }
+
+    /// Work out what importing this backup into `repository` with `mode` would do
+    pub fn preview(&self, repository: &Repository, mode: ImportMode) -> Result<ImportPreview, StorageError> {
+        let reminders = repository.load_reminders();
+        let tags = repository.load_tags();
+        let views = repository.load_saved_views();
+        if let Some(error) = reminders.error.or(tags.error).or(views.error) {
+            return Err(error);
+        }
+
+        let mut preview = ImportPreview {
+            mode,
+            added: 0,
+            removed: 0,
+            conflicts: Vec::new(),
+            locale: None,
+            reminders: Vec::new(),
+            tags: Vec::new(),
+            saved_views: Vec::new(),
+        };
+        preview.reminders = preview.combine(reminders.items, &self.data.reminders);
+        preview.tags = preview.combine(tags.items, &self.data.tags);
+        preview.saved_views = preview.combine(views.items, &self.data.settings.saved_views);
+        if mode == ImportMode::Replace {
+            preview.locale = self.data.settings.locale.clone();
+        }
+        Ok(preview)
+    }
+}
+
+impl ImportPreview {
+    /// Save the previewed result, all or nothing
+    ///
+    /// Everything is encoded before anything is written. Tags are saved
+    /// before reminders, so reminders never refer to a tag that hasn't been
+    /// saved yet. If a save fails, the keys already written get their
+    /// previous values back.
+    pub fn apply(&self, repository: &Repository) -> Result<(), StorageError> {
+        let staged = [
+            (TAGS_V1_KEY, encode(&self.tags)?),
+            (REMINDERS_V2_KEY, encode(&self.reminders)?),
+            (SAVED_VIEWS_V1_KEY, encode(&self.saved_views)?),
+        ];
+        let storage = repository.storage();
+        let mut previous = Vec::with_capacity(staged.len());
+        for (key, value) in &staged {
+            let result = storage.get(key).and_then(|old| {
+                previous.push((key, old));
+                storage.set(key, value)
+            });
+            if let Err(error) = result {
+                // Best effort: the error reported is the one that stopped the import
+                for (key, old) in previous.iter().rev() {
+                    let _ = match old {
+                        Some(old) => storage.set(key, old),
+                        None => storage.remove(key),
+                    };
+                }
+                return Err(error);
+            }
+        }
+        Ok(())
+    }
+
+    /// Combine the stored items with the backup's according to `self.mode`,
+    /// counting what changes
+    fn combine<T: BackupItem>(&mut self, local: Vec<T>, backup: &[T]) -> Vec<T> {
+        let local_ids: HashSet<String> = local.iter().map(|item| item.id().to_string()).collect();
+        self.added += backup.iter().filter(|item| !local_ids.contains(item.id())).count();
+
+        let mut combined = Vec::with_capacity(local.len() + backup.len());
+        for item in local {
+            let Some(incoming) = backup.iter().find(|b| b.id() == item.id()) else {
+                if self.mode == ImportMode::Replace {
+                    self.removed += 1;
+                } else {
+                    combined.push(item);
+                }
+                continue;
+            };
+            if *incoming == item {
+                combined.push(item);
+                continue;
+            }
+            let resolution = match (self.mode, item.modified(), incoming.modified()) {
+                (ImportMode::KeepNewest, Some(local_time), Some(backup_time)) if backup_time > local_time => {
+                    Resolution::UseBackup
+                }
+                (ImportMode::KeepNewest, _, _) => Resolution::KeepLocal,
+                _ => Resolution::UseBackup,
+            };
+            self.conflicts.push(ImportConflict {
+                kind: T::KIND,
+                id: item.id().to_string(),
+                local_name: item.name().to_string(),
+                backup_name: incoming.name().to_string(),
+                resolution,
+            });
+            combined.push(match resolution {
+                Resolution::UseBackup => incoming.clone(),
+                Resolution::KeepLocal => item,
+            });
+        }
+
+        // Replacing keeps the backup's order; merging adds new items after the stored ones
+        if self.mode == ImportMode::Replace {
+            return backup.to_vec();
+        }
+        combined.extend(backup.iter().filter(|item| !local_ids.contains(item.id())).cloned());
+        combined
+    }
+}
+
+fn encode<T: Serialize>(items: &[T]) -> Result<String, StorageError> {
+    serde_json::to_string(items).map_err(|_| StorageError::SerializationFailed)
+}
+
+/// Checksum of a backup's `data`
+///
+/// Computed over the parsed JSON value rather than the file's text, so
+/// re-indenting a backup doesn't invalidate it.
+fn checksum(data: &Value) -> String {
+    format!("fnv1a64:{:016x}", fnv1a(&data.to_string()))
+}
+
+/// An item that can be matched up between stored data and a backup
+trait BackupItem: Clone + PartialEq {
+    const KIND: BackupItemKind;
+
+    fn id(&self) -> &str;
+
+    /// Title or name shown in conflicts
+    fn name(&self) -> &str;
+
+    /// When the item last changed, if known
+    fn modified(&self) -> Option<Timestamp> {
+        None
+    }
+}
+
+impl BackupItem for Reminder {
+    const KIND: BackupItemKind = BackupItemKind::Reminder;
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn name(&self) -> &str {
+        &self.title
+    }
+
+    fn modified(&self) -> Option<Timestamp> {
+        Some(self.updated_at.unwrap_or(self.created_at))
+    }
+}
+
+impl BackupItem for Tag {
+    const KIND: BackupItemKind = BackupItemKind::Tag;
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl BackupItem for SavedView {
+    const KIND: BackupItemKind = BackupItemKind::SavedView;
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    use super::*;
+    use crate::storage::{MemoryStorage, PlatformStorage};
+    use crate::test_support::{reminder, saved_view, tag, JAN_15_MS};
+
+    /// Memory storage whose writes to one key can be made to fail
+    #[derive(Default)]
+    struct FailingStorage {
+        inner: MemoryStorage,
+        fail_key: Cell<Option<&'static str>>,
+    }
+
+    impl FailingStorage {
+        /// Every key with its value, sorted by key
+        fn snapshot(&self) -> Vec<(String, Option<String>)> {
+            let mut keys = self.inner.keys().unwrap();
+            keys.sort();
+            keys.into_iter().map(|key| (key.clone(), self.inner.get(&key).unwrap())).collect()
+        }
+    }
+
+    impl PlatformStorage for FailingStorage {
+        fn get(&self, key: &str) -> Result<Option<String>, StorageError> {
+            self.inner.get(key)
+        }
+
+        fn set(&self, key: &str, value: &str) -> Result<(), StorageError> {
+            if self.fail_key.get() == Some(key) {
+                return Err(StorageError::SaveFailed);
+            }
+            self.inner.set(key, value)
+        }
+
+        fn remove(&self, key: &str) -> Result<(), StorageError> {
+            self.inner.remove(key)
+        }
+
+        fn keys(&self) -> Result<Vec<String>, StorageError> {
+            self.inner.keys()
+        }
+
+        fn clear(&self) -> Result<(), StorageError> {
+            self.inner.clear()
+        }
+    }
+
+    fn edited(id: &str, title: &str, minutes_later: i64) -> Reminder {
+        Reminder {
+            updated_at: Some(Timestamp::from_millis(JAN_15_MS + minutes_later * 60_000)),
+            ..reminder(id, title)
+        }
+    }
+
+    /// Repository holding reminders a, b; tag work; view today
+    fn local() -> Repository {
+        let repository = Repository::in_memory();
+        repository.save_reminders(&[edited("a", "A local", 10), reminder("b", "B")]).unwrap();
+        repository.save_tags(&[tag("work", "Work")]).unwrap();
+        repository.save_saved_views(&[saved_view("today", "Today")]).unwrap();
+        repository
+    }
+
+    /// Backup holding reminders a (edited at `a_minutes`), c; tags work (renamed), home; no views
+    fn backup(a_minutes: i64) -> Backup {
+        let source = Repository::in_memory();
+        source.save_reminders(&[edited("a", "A backup", a_minutes), reminder("c", "C")]).unwrap();
+        source.save_tags(&[tag("work", "Office"), tag("home", "Home")]).unwrap();
+        Backup::create(&source, Some("zh-Hans")).unwrap()
+    }
+
+    fn ids<T: BackupItem>(items: &[T]) -> Vec<&str> {
+        items.iter().map(BackupItem::id).collect()
+    }
+
+    #[test]
+    fn backups_round_trip_through_json() {
+        let backup = backup(20);
+        assert_eq!(backup.format, BACKUP_FORMAT);
+        assert_eq!((backup.version, backup.schema_version), (BACKUP_VERSION, SCHEMA_VERSION));
+        assert_eq!(backup.data.settings.locale.as_deref(), Some("zh-Hans"));
+
+        assert_eq!(Backup::parse(&backup.to_json().unwrap()).unwrap(), backup);
+        // The checksum covers the data, not the file's layout
+        assert_eq!(Backup::parse(&serde_json::to_string(&backup).unwrap()).unwrap(), backup);
+        assert!(backup.file_name().starts_with("remind-me-backup-"));
+    }
+
+    #[test]
+    fn damaged_edited_and_newer_backups_are_rejected() {
+        let json = backup(20).to_json().unwrap();
+
+        let edited = json.replace("A backup", "A edited");
+        assert!(matches!(Backup::parse(&edited), Err(BackupError::ChecksumMismatch)));
+        let mut value: Value = serde_json::from_str(&json).unwrap();
+        value["checksum"] = Value::from("fnv1a64:0000000000000000");
+        assert!(matches!(Backup::parse(&value.to_string()), Err(BackupError::ChecksumMismatch)));
+
+        assert!(matches!(Backup::parse(&json[..json.len() / 2]), Err(BackupError::InvalidFormat(_))));
+        assert!(matches!(Backup::parse("{\"data\":{}}"), Err(BackupError::InvalidFormat(_))));
+        for field in ["version", "schema_version", "data"] {
+            let mut value: Value = serde_json::from_str(&json).unwrap();
+            value.as_object_mut().unwrap().remove(field);
+            assert!(matches!(Backup::parse(&value.to_string()), Err(BackupError::InvalidFormat(_))), "{field}");
+        }
+
+        for (field, newer) in [("version", BACKUP_VERSION + 1), ("schema_version", SCHEMA_VERSION + 1)] {
+            let mut value: Value = serde_json::from_str(&json).unwrap();
+            value[field] = Value::from(newer);
+            assert!(matches!(Backup::parse(&value.to_string()), Err(BackupError::UnsupportedVersion(v)) if v == newer));
+        }
+    }
+
+    #[test]
+    fn replace_takes_the_backup_as_is() {
+        let preview = backup(20).preview(&local(), ImportMode::Replace).unwrap();
+        assert_eq!((preview.added, preview.removed), (2, 2));
+        assert_eq!(preview.locale.as_deref(), Some("zh-Hans"));
+        assert_eq!(ids(&preview.reminders), ["a", "c"]);
+        assert_eq!(ids(&preview.tags), ["work", "home"]);
+        assert!(preview.saved_views.is_empty());
+
+        let conflicts: Vec<_> = preview.conflicts.iter().map(|c| (c.kind, c.id.as_str(), c.resolution)).collect();
+        assert_eq!(
+            conflicts,
+            [
+                (BackupItemKind::Reminder, "a", Resolution::UseBackup),
+                (BackupItemKind::Tag, "work", Resolution::UseBackup),
+            ]
+        );
+        assert_eq!(
+            (preview.conflicts[1].local_name.as_str(), preview.conflicts[1].backup_name.as_str()),
+            ("Work", "Office")
+        );
+    }
+
+    #[test]
+    fn merging_keeps_local_items_and_adds_new_ones_after_them() {
+        let preview = backup(5).preview(&local(), ImportMode::MergeById).unwrap();
+        assert_eq!((preview.added, preview.removed), (2, 0));
+        assert_eq!(preview.locale, None);
+        assert_eq!(ids(&preview.reminders), ["a", "b", "c"]);
+        assert_eq!(preview.reminders[0].title, "A backup");
+        assert_eq!(ids(&preview.tags), ["work", "home"]);
+        assert_eq!(preview.tags[0].name, "Office");
+        assert_eq!(ids(&preview.saved_views), ["today"]);
+        assert!(preview.conflicts.iter().all(|c| c.resolution == Resolution::UseBackup));
+    }
+
+    #[test]
+    fn keep_newest_compares_reminder_times_and_keeps_local_tags() {
+        let older = backup(5).preview(&local(), ImportMode::KeepNewest).unwrap();
+        assert_eq!(older.reminders[0].title, "A local");
+        assert_eq!(older.tags[0].name, "Work");
+        assert!(older.conflicts.iter().all(|c| c.resolution == Resolution::KeepLocal));
+
+        let newer = backup(20).preview(&local(), ImportMode::KeepNewest).unwrap();
+        assert_eq!(newer.reminders[0].title, "A backup");
+        assert_eq!(newer.conflicts[0].resolution, Resolution::UseBackup);
+        // Tags have no modification time, so the stored one wins
+        assert_eq!(newer.tags[0].name, "Work");
+        assert_eq!(newer.conflicts[1].resolution, Resolution::KeepLocal);
+
+        // Identical items aren't conflicts
+        let same = Backup::create(&local(), None).unwrap();
+        let preview = same.preview(&local(), ImportMode::KeepNewest).unwrap();
+        assert!(preview.conflicts.is_empty());
+        assert_eq!(preview.added, 0);
+    }
+
+    #[test]
+    fn apply_saves_the_previewed_result() {
+        let repository = local();
+        let preview = backup(20).preview(&repository, ImportMode::MergeById).unwrap();
+        preview.apply(&repository).unwrap();
+
+        assert_eq!(repository.load_reminders().items, preview.reminders);
+        assert_eq!(repository.load_tags().items, preview.tags);
+        assert_eq!(repository.load_saved_views().items, preview.saved_views);
+    }
+
+    #[test]
+    fn a_failed_apply_restores_what_it_already_wrote() {
+        for fail_key in [TAGS_V1_KEY, REMINDERS_V2_KEY, SAVED_VIEWS_V1_KEY] {
+            let storage = Rc::new(FailingStorage::default());
+            let repository = Repository::new(storage.clone());
+            // No tags or views stored: restoring them means removing them again
+            repository.save_reminders(&[reminder("a", "A")]).unwrap();
+            let before = storage.snapshot();
+
+            let preview = backup(20).preview(&repository, ImportMode::Replace).unwrap();
+            storage.fail_key.set(Some(fail_key));
+            assert!(matches!(preview.apply(&repository), Err(StorageError::SaveFailed)), "{fail_key}");
+            assert_eq!(storage.snapshot(), before, "{fail_key}");
+        }
+    }
+}
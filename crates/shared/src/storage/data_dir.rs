@@ -0,0 +1,317 @@
+//! Per-platform app data directory for native builds
+//!
+//! - Linux: `$XDG_DATA_HOME/remind-me` (default `~/.local/share/remind-me`)
+//! - macOS: `~/Library/Application Support/remind-me`
+//! - Windows: `%APPDATA%\remind-me`
+//! - iOS: `Library/Application Support` inside the app sandbox
+//! - Android: the app's internal files directory (`/data/data/<package>/files`)
+//!
+//! `REMIND_ME_DATA_DIR` overrides the platform directory, and
+//! `DataDirResolver::with_dir` overrides both (e.g. a temp dir in tests).
+//!
+//! Earlier versions stored data in `<exe dir>/data`.
+//! `DataDirResolver::resolve_and_migrate` moves the data files found there
+//! once, the first time the new directory is used. Only files this app
+//! writes are moved (see `is_data_file`); anything else is left in place.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::migrations::{MIGRATION_BACKUP_PREFIX, SCHEMA_VERSION_KEY};
+use super::sqlite::DATABASE_FILE;
+use super::{QUARANTINE_PREFIX, REMINDERS_V1_KEY, REMINDERS_V2_KEY, SAVED_VIEWS_V1_KEY, TAGS_V1_KEY};
+
+/// Environment variable overriding the data directory
+pub const DATA_DIR_ENV: &str = "REMIND_ME_DATA_DIR";
+
+/// Directory name used under the platform's data location
+#[cfg(not(any(target_os = "ios", target_os = "android")))]
+const APP_DIR_NAME: &str = "remind-me";
+
+/// Finds the data directory, with optional overrides
+#[derive(Clone, Debug, Default)]
+pub struct DataDirResolver {
+    dir: Option<PathBuf>,
+}
+
+impl DataDirResolver {
+    /// Resolver using the environment and platform defaults
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Use `dir` instead of the environment or platform directory
+    pub fn with_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.dir = Some(dir.into());
+        self
+    }
+
+    /// The data directory: the override, `REMIND_ME_DATA_DIR`, the platform
+    /// directory, then the legacy location if the platform has none (and
+    /// `./data` if even the executable's directory is unknown)
+    pub fn resolve(&self) -> PathBuf {
+        self.dir
+            .clone()
+            .or_else(|| std::env::var_os(DATA_DIR_ENV).filter(|dir| !dir.is_empty()).map(PathBuf::from))
+            .or_else(platform_data_dir)
+            .or_else(legacy_data_dir)
+            .unwrap_or_else(|| PathBuf::from("data"))
+    }
+
+    /// Like `resolve`, first moving the data files from the legacy location
+    /// into a new, empty data directory
+    pub fn resolve_and_migrate(&self) -> PathBuf {
+        let dir = self.resolve();
+        if let Some(legacy) = legacy_data_dir() {
+            migrate_legacy_data(&legacy, &dir);
+        }
+        dir
+    }
+}
+
+/// The data directory (see `DataDirResolver`), migrating legacy data if needed
+pub fn app_data_dir() -> PathBuf {
+    DataDirResolver::new().resolve_and_migrate()
+}
+
+/// Where earlier versions stored data
+///
+/// Their `./data` fallback isn't checked: relative to the working directory
+/// it could be any directory named `data`.
+fn legacy_data_dir() -> Option<PathBuf> {
+    std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(|parent| parent.join("data")))
+}
+
+/// Move the data files from `legacy` into `dir` if `dir` is new (missing or empty)
+fn migrate_legacy_data(legacy: &Path, dir: &Path) {
+    if is_empty_dir(dir) && !same_dir(legacy, dir) {
+        // Best effort: on failure the data stays where it was
+        let _ = move_data_files(legacy, dir);
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "freebsd", target_os = "openbsd", target_os = "netbsd"))]
+fn platform_data_dir() -> Option<PathBuf> {
+    let data_home = std::env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_absolute())
+        .or_else(|| home_dir().map(|home| home.join(".local").join("share")))?;
+    Some(data_home.join(APP_DIR_NAME))
+}
+
+#[cfg(target_os = "macos")]
+fn platform_data_dir() -> Option<PathBuf> {
+    Some(home_dir()?.join("Library").join("Application Support").join(APP_DIR_NAME))
+}
+
+#[cfg(target_os = "windows")]
+fn platform_data_dir() -> Option<PathBuf> {
+    Some(PathBuf::from(std::env::var_os("APPDATA")?).join(APP_DIR_NAME))
+}
+
+#[cfg(target_os = "ios")]
+fn platform_data_dir() -> Option<PathBuf> {
+    // HOME is the app's sandbox container
+    Some(home_dir()?.join("Library").join("Application Support"))
+}
+
+#[cfg(target_os = "android")]
+fn platform_data_dir() -> Option<PathBuf> {
+    // The process name is the package name; this matches `Context.getFilesDir()`
+    let cmdline = fs::read("/proc/self/cmdline").ok()?;
+    let package = cmdline.split(|byte| *byte == 0).next()?;
+    let package = std::str::from_utf8(package).ok()?.split(':').next()?;
+    (!package.is_empty()).then(|| PathBuf::from("/data/data").join(package).join("files"))
+}
+
+#[cfg(not(any(
+    target_os = "linux",
+    target_os = "freebsd",
+    target_os = "openbsd",
+    target_os = "netbsd",
+    target_os = "macos",
+    target_os = "windows",
+    target_os = "ios",
+    target_os = "android"
+)))]
+fn platform_data_dir() -> Option<PathBuf> {
+    None
+}
+
+#[cfg(any(
+    target_os = "linux",
+    target_os = "freebsd",
+    target_os = "openbsd",
+    target_os = "netbsd",
+    target_os = "macos",
+    target_os = "ios"
+))]
+fn home_dir() -> Option<PathBuf> {
+    std::env::var_os("HOME").filter(|home| !home.is_empty()).map(PathBuf::from)
+}
+
+/// Whether `dir` is missing or has no entries
+fn is_empty_dir(dir: &Path) -> bool {
+    !fs::read_dir(dir).is_ok_and(|mut entries| entries.next().is_some())
+}
+
+fn same_dir(a: &Path, b: &Path) -> bool {
+    match (fs::canonicalize(a), fs::canonicalize(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+/// Whether `name` is a file earlier versions wrote: a stored value
+/// (`<key>.json`), one of its generations (`<key>.json.<n>`), or the SQLite
+/// database and its journal files
+fn is_data_file(name: &str) -> bool {
+    if let Some(suffix) = name.strip_prefix(DATABASE_FILE) {
+        return ["", "-journal", "-wal", "-shm"].contains(&suffix);
+    }
+    let key = match name.rsplit_once(".json") {
+        Some((key, "")) => key,
+        Some((key, generation)) => match generation.strip_prefix('.') {
+            Some(generation) if !generation.is_empty() && generation.bytes().all(|b| b.is_ascii_digit()) => key,
+            _ => return false,
+        },
+        None => return false,
+    };
+    [REMINDERS_V1_KEY, REMINDERS_V2_KEY, TAGS_V1_KEY, SAVED_VIEWS_V1_KEY, SCHEMA_VERSION_KEY].contains(&key)
+        || [QUARANTINE_PREFIX, MIGRATION_BACKUP_PREFIX]
+            .iter()
+            .any(|prefix| key.len() > prefix.len() && key.starts_with(prefix))
+}
+
+/// Move the data files (see `is_data_file`) from `from` into `to`, copying
+/// when a rename isn't possible
+///
+/// `from` is removed only if nothing else was in it.
+fn move_data_files(from: &Path, to: &Path) -> std::io::Result<()> {
+    let mut files = Vec::new();
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        if entry.file_type()?.is_file() && entry.file_name().to_str().is_some_and(is_data_file) {
+            files.push(entry);
+        }
+    }
+    if files.is_empty() {
+        return Ok(());
+    }
+
+    fs::create_dir_all(to)?;
+    for entry in files {
+        let target = to.join(entry.file_name());
+        if fs::rename(entry.path(), &target).is_err() {
+            // Different file systems: copy, then remove the original
+            fs::copy(entry.path(), &target)?;
+            fs::remove_file(entry.path())?;
+        }
+    }
+    let _ = fs::remove_dir(from);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TempDir;
+
+    fn write(dir: &Path, name: &str) {
+        fs::create_dir_all(dir).unwrap();
+        fs::write(dir.join(name), name).unwrap();
+    }
+
+    fn names(dir: &Path) -> Vec<String> {
+        let mut names: Vec<String> = fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn recognizes_the_files_the_app_writes() {
+        for name in [
+            "reminders.json",
+            "reminders_v2.json",
+            "reminders_v2.json.1",
+            "tags_v1.json.12",
+            "saved_views_v1.json",
+            "schema_version.json",
+            "quarantine_reminders_v2_1a2b.json",
+            "migration_backup_v1.json",
+            "remind_me.sqlite3",
+            "remind_me.sqlite3-wal",
+        ] {
+            assert!(is_data_file(name), "{name}");
+        }
+        for name in [
+            "notes.json",
+            "reminders_v2.json.tmp",
+            "reminders_v2.json.",
+            "reminders_v2.txt",
+            "quarantine_.json",
+            "remind_me.sqlite3.bak",
+            "photo.png",
+        ] {
+            assert!(!is_data_file(name), "{name}");
+        }
+    }
+
+    #[test]
+    fn moves_only_data_files_into_a_new_dir() {
+        let temp = TempDir::new();
+        let (legacy, dir) = (temp.path().join("legacy"), temp.path().join("new"));
+        write(&legacy, "reminders_v2.json");
+        write(&legacy, "reminders_v2.json.1");
+        write(&legacy, "tags_v1.json");
+        write(&legacy, "quarantine_tags_v1_ff.json");
+        write(&legacy, "notes.txt");
+        write(&legacy.join("photos"), "tags_v1.json");
+
+        migrate_legacy_data(&legacy, &dir);
+
+        assert_eq!(
+            names(&dir),
+            ["quarantine_tags_v1_ff.json", "reminders_v2.json", "reminders_v2.json.1", "tags_v1.json"]
+        );
+        assert_eq!(fs::read_to_string(dir.join("tags_v1.json")).unwrap(), "tags_v1.json");
+        // Everything else stays, so the legacy dir is kept
+        assert_eq!(names(&legacy), ["notes.txt", "photos"]);
+        assert_eq!(names(&legacy.join("photos")), ["tags_v1.json"]);
+    }
+
+    #[test]
+    fn removes_the_legacy_dir_once_emptied() {
+        let temp = TempDir::new();
+        let (legacy, dir) = (temp.path().join("legacy"), temp.path().join("new"));
+        write(&legacy, "reminders_v2.json");
+
+        migrate_legacy_data(&legacy, &dir);
+
+        assert!(!legacy.exists());
+        assert_eq!(names(&dir), ["reminders_v2.json"]);
+    }
+
+    #[test]
+    fn leaves_data_alone_when_the_new_dir_is_in_use_or_there_is_nothing_to_move() {
+        let temp = TempDir::new();
+        let (legacy, dir) = (temp.path().join("legacy"), temp.path().join("new"));
+        write(&legacy, "reminders_v2.json");
+        write(&dir, "tags_v1.json");
+
+        migrate_legacy_data(&legacy, &dir);
+        assert_eq!(names(&legacy), ["reminders_v2.json"]);
+        assert_eq!(names(&dir), ["tags_v1.json"]);
+
+        let (unrelated, untouched) = (temp.path().join("unrelated"), temp.path().join("untouched"));
+        write(&unrelated, "notes.json");
+        migrate_legacy_data(&unrelated, &untouched);
+        assert!(!untouched.exists());
+        assert_eq!(names(&unrelated), ["notes.json"]);
+    }
+}
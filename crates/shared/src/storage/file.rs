@@ -0,0 +1,270 @@
+//! File system storage backend
+//!
+//! Each key is stored as `<key>.json` in a data directory. Used by desktop
+//! builds, and on mobile when the SQLite database can't be opened.
+//!
+//! Writes are crash-safe: the value goes to a temporary file that is synced
+//! and then renamed over the old one, so a crash or full disk leaves either
+//! the old or the new value, never a truncated one. The previous values are
+//! kept as numbered generations (`<key>.json.1` is the newest); if the
+//! current file doesn't hold valid JSON, reads fall back to the newest valid
+//! generation.
+
+use std::fs::{self, File};
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+
+use super::{PlatformStorage, StorageError};
+
+/// Extension of the files holding stored values
+const EXTENSION: &str = "json";
+
+/// Extension added to a file while it is being written
+const TEMP_EXTENSION: &str = "tmp";
+
+/// Previous values kept per key unless configured otherwise
+pub const DEFAULT_GENERATIONS: usize = 3;
+
+/// Storage backend keeping one file per key in a directory
+#[derive(Clone, Debug)]
+pub struct FileStorage {
+    dir: PathBuf,
+    generations: usize,
+}
+
+impl FileStorage {
+    /// Storage in `dir` (created on first write)
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            generations: DEFAULT_GENERATIONS,
+        }
+    }
+
+    /// Keep `generations` previous values per key (0 keeps none)
+    pub fn with_generations(mut self, generations: usize) -> Self {
+        self.generations = generations;
+        self
+    }
+
+    /// Directory holding the stored files
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{}.{}", key, EXTENSION))
+    }
+
+    /// Path of a previous value; generation 1 is the newest
+    fn generation_path(&self, key: &str, generation: usize) -> PathBuf {
+        self.dir.join(format!("{}.{}.{}", key, EXTENSION, generation))
+    }
+
+    fn temp_path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{}.{}.{}", key, EXTENSION, TEMP_EXTENSION))
+    }
+
+    /// Shift every generation down by one and copy the current file to generation 1
+    fn rotate(&self, key: &str) -> Result<(), StorageError> {
+        let current = self.path(key);
+        if self.generations == 0 || !current.exists() {
+            return Ok(());
+        }
+        remove_if_exists(&self.generation_path(key, self.generations))?;
+        for generation in (1..self.generations).rev() {
+            rename_if_exists(&self.generation_path(key, generation), &self.generation_path(key, generation + 1))?;
+        }
+        // Link rather than rename so the current file stays in place until
+        // the new one replaces it
+        let newest = self.generation_path(key, 1);
+        fs::hard_link(&current, &newest)
+            .or_else(|_| fs::copy(&current, &newest).map(|_| ()))
+            .map_err(|_| StorageError::SaveFailed)
+    }
+}
+
+/// Whether a stored value survived intact (every value is JSON)
+fn is_valid(content: &str) -> bool {
+    serde_json::from_str::<serde::de::IgnoredAny>(content).is_ok()
+}
+
+fn read_if_exists(path: &Path) -> Result<Option<String>, StorageError> {
+    match fs::read_to_string(path) {
+        Ok(content) => Ok(Some(content)),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+        Err(_) => Err(StorageError::ReadFailed),
+    }
+}
+
+fn remove_if_exists(path: &Path) -> Result<(), StorageError> {
+    match fs::remove_file(path) {
+        Err(e) if e.kind() != ErrorKind::NotFound => Err(StorageError::SaveFailed),
+        _ => Ok(()),
+    }
+}
+
+fn rename_if_exists(from: &Path, to: &Path) -> Result<(), StorageError> {
+    match fs::rename(from, to) {
+        Err(e) if e.kind() != ErrorKind::NotFound => Err(StorageError::SaveFailed),
+        _ => Ok(()),
+    }
+}
+
+/// Write `value` to `path` and wait until it is on disk
+fn write_synced(path: &Path, value: &str) -> std::io::Result<()> {
+    let mut file = File::create(path)?;
+    file.write_all(value.as_bytes())?;
+    file.sync_all()
+}
+
+/// Make renames in `dir` durable (only possible on Unix)
+fn sync_dir(dir: &Path) {
+    #[cfg(unix)]
+    if let Ok(dir) = File::open(dir) {
+        let _ = dir.sync_all();
+    }
+    #[cfg(not(unix))]
+    let _ = dir;
+}
+
+impl Default for FileStorage {
+    /// Storage in the app data directory (see `data_dir`)
+    fn default() -> Self {
+        #[cfg(not(target_arch = "wasm32"))]
+        let dir = super::app_data_dir();
+        #[cfg(target_arch = "wasm32")]
+        let dir = PathBuf::from("data");
+        Self::new(dir)
+    }
+}
+
+impl PlatformStorage for FileStorage {
+    fn get(&self, key: &str) -> Result<Option<String>, StorageError> {
+        let Some(content) = read_if_exists(&self.path(key))? else {
+            return Ok(None);
+        };
+        if is_valid(&content) {
+            return Ok(Some(content));
+        }
+
+        for generation in 1..=self.generations {
+            if let Some(previous) = read_if_exists(&self.generation_path(key, generation))? {
+                if is_valid(&previous) {
+                    return Ok(Some(previous));
+                }
+            }
+        }
+        // Nothing better: hand back the damaged value so it can be quarantined
+        Ok(Some(content))
+    }
+
+    fn set(&self, key: &str, value: &str) -> Result<(), StorageError> {
+        fs::create_dir_all(&self.dir).map_err(|_| StorageError::Unavailable)?;
+        let temp = self.temp_path(key);
+        if write_synced(&temp, value).is_err() {
+            let _ = fs::remove_file(&temp);
+            return Err(StorageError::SaveFailed);
+        }
+        self.rotate(key)?;
+        fs::rename(&temp, self.path(key)).map_err(|_| StorageError::SaveFailed)?;
+        sync_dir(&self.dir);
+        Ok(())
+    }
+
+    fn remove(&self, key: &str) -> Result<(), StorageError> {
+        remove_if_exists(&self.path(key))?;
+        for generation in 1..=self.generations {
+            remove_if_exists(&self.generation_path(key, generation))?;
+        }
+        Ok(())
+    }
+
+    fn keys(&self) -> Result<Vec<String>, StorageError> {
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(_) => return Err(StorageError::ReadFailed),
+        };
+        let mut keys: Vec<String> = entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == EXTENSION))
+            .filter_map(|path| path.file_stem().and_then(|stem| stem.to_str()).map(str::to_string))
+            .collect();
+        keys.sort();
+        Ok(keys)
+    }
+
+    fn clear(&self) -> Result<(), StorageError> {
+        for key in self.keys()? {
+            self.remove(&key)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TempDir;
+
+    fn stored_files(dir: &Path) -> Vec<String> {
+        let mut names: Vec<String> = fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn a_damaged_file_falls_back_to_the_newest_valid_generation() {
+        let dir = TempDir::new();
+        let storage = FileStorage::new(dir.path());
+        for value in ["[1]", "[2]", "[3]", "[4]"] {
+            storage.set("reminders", value).unwrap();
+        }
+        assert_eq!(storage.get("reminders").unwrap().as_deref(), Some("[4]"));
+
+        // A truncated write, with a damaged newest generation
+        fs::write(storage.path("reminders"), "[4, 5").unwrap();
+        fs::write(storage.generation_path("reminders", 1), "").unwrap();
+        assert_eq!(storage.get("reminders").unwrap().as_deref(), Some("[2]"));
+
+        // Nothing valid left: the damaged value is returned so it can be quarantined
+        for generation in 2..=DEFAULT_GENERATIONS {
+            fs::write(storage.generation_path("reminders", generation), "{").unwrap();
+        }
+        assert_eq!(storage.get("reminders").unwrap().as_deref(), Some("[4, 5"));
+        assert_eq!(storage.get("tags").unwrap(), None);
+    }
+
+    #[test]
+    fn rotation_keeps_the_configured_number_of_generations() {
+        let dir = TempDir::new();
+        let storage = FileStorage::new(dir.path()).with_generations(2);
+        for value in ["[1]", "[2]", "[3]", "[4]"] {
+            storage.set("tags", value).unwrap();
+        }
+        assert_eq!(stored_files(dir.path()), ["tags.json", "tags.json.1", "tags.json.2"]);
+        let generation = |n| fs::read_to_string(storage.generation_path("tags", n)).unwrap();
+        assert_eq!((generation(1), generation(2)), ("[3]".to_string(), "[2]".to_string()));
+        assert_eq!(storage.keys().unwrap(), ["tags"]);
+
+        storage.remove("tags").unwrap();
+        assert!(stored_files(dir.path()).is_empty());
+    }
+
+    #[test]
+    fn zero_generations_keeps_no_previous_values() {
+        let dir = TempDir::new();
+        let storage = FileStorage::new(dir.path()).with_generations(0);
+        storage.set("tags", "[1]").unwrap();
+        storage.set("tags", "[2]").unwrap();
+        assert_eq!(stored_files(dir.path()), ["tags.json"]);
+
+        fs::write(storage.path("tags"), "[2").unwrap();
+        assert_eq!(storage.get("tags").unwrap().as_deref(), Some("[2"));
+    }
+}
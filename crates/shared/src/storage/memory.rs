@@ -0,0 +1,47 @@
+//! In-memory storage backend
+//!
+//! Nothing is persisted; useful for previews, tests and as a fallback when no
+//! platform storage is available.
+
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+
+use super::{PlatformStorage, StorageError};
+
+/// Storage backend keeping every value in memory
+#[derive(Debug, Default)]
+pub struct MemoryStorage {
+    values: RefCell<BTreeMap<String, String>>,
+}
+
+impl MemoryStorage {
+    /// An empty in-memory storage
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl PlatformStorage for MemoryStorage {
+    fn get(&self, key: &str) -> Result<Option<String>, StorageError> {
+        Ok(self.values.borrow().get(key).cloned())
+    }
+
+    fn set(&self, key: &str, value: &str) -> Result<(), StorageError> {
+        self.values.borrow_mut().insert(key.to_string(), value.to_string());
+        Ok(())
+    }
+
+    fn remove(&self, key: &str) -> Result<(), StorageError> {
+        self.values.borrow_mut().remove(key);
+        Ok(())
+    }
+
+    fn keys(&self) -> Result<Vec<String>, StorageError> {
+        Ok(self.values.borrow().keys().cloned().collect())
+    }
+
+    fn clear(&self) -> Result<(), StorageError> {
+        self.values.borrow_mut().clear();
+        Ok(())
+    }
+}
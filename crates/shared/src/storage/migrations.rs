@@ -0,0 +1,486 @@
+//! Versioned storage schema and migrations
+//!
+//! The layout of everything the app stores (which keys exist and what their
+//! JSON looks like) has a single schema version, kept under
+//! `SCHEMA_VERSION_KEY`. `MIGRATIONS` is an ordered registry of steps, each
+//! upgrading the stored data from one version to the next.
+//!
+//! `run_migrations` brings storage up to `SCHEMA_VERSION` before anything is
+//! loaded:
+//!
+//! - Steps work on a staged copy of the keys they touch; nothing is written
+//!   unless every pending step succeeds.
+//! - The original value of every key the steps read or change is saved
+//!   under `migration_backup_v{N}` (N being the version migrated from)
+//!   before the new values are written.
+//! - Keys a step retires (such as the v1 `reminders` key) are only removed
+//!   once every new value has been written and read back unchanged; their
+//!   old value stays in the backup.
+//! - The schema version is bumped last, so each step runs exactly once.
+//! - Values a step can't carry over are not lost silently: the records they
+//!   came from are quarantined and `run_migrations` reports them (see
+//!   `StorageError::MigrationDropped`).
+//!
+//! `Repository` runs the migrations once, when it is created.
+//!
+//! To change the schema, bump `SCHEMA_VERSION` and append a `Migration`
+//! whose `from` is the previous version.
+
+use std::cell::RefCell;
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::Deserialize;
+
+use super::{quarantine_key, PlatformStorage, StorageError, REMINDERS_V1_KEY, REMINDERS_V2_KEY, TAGS_V1_KEY};
+use crate::datetime::{LocalDateTime, Timestamp};
+use crate::models::{Priority, Reminder};
+
+/// Key holding the schema version of the stored data
+pub const SCHEMA_VERSION_KEY: &str = "schema_version";
+
+/// Prefix of the keys holding pre-migration backups
+pub const MIGRATION_BACKUP_PREFIX: &str = "migration_backup_v";
+
+/// Schema version written by this build
+pub const SCHEMA_VERSION: u32 = 2;
+
+/// A single step upgrading stored data from `from` to `from + 1`
+pub struct Migration {
+    /// Schema version this step upgrades from
+    pub from: u32,
+    /// What the step changes (for logs and error messages)
+    pub description: &'static str,
+    /// Apply the step, returning a reason on failure
+    pub run: fn(&mut MigrationContext<'_>) -> Result<(), String>,
+}
+
+/// Registered migrations, ordered by `from`
+pub const MIGRATIONS: &[Migration] = &[Migration {
+    from: 1,
+    description: "move reminders from the legacy `reminders` key to `reminders_v2`",
+    run: migrate_v1_reminders,
+}];
+
+/// Staged view of storage that migration steps read and write
+pub struct MigrationContext<'a> {
+    storage: &'a dyn PlatformStorage,
+    /// Keys read or written, backed up before changes are applied
+    touched: RefCell<BTreeSet<String>>,
+    /// New value of each changed key (`None` to remove it)
+    changes: BTreeMap<String, Option<String>>,
+    /// Values left out by the step being run, with the key their records are quarantined under
+    dropped: Vec<(Vec<String>, String)>,
+}
+
+impl<'a> MigrationContext<'a> {
+    fn new(storage: &'a dyn PlatformStorage) -> Self {
+        Self {
+            storage,
+            touched: RefCell::new(BTreeSet::new()),
+            changes: BTreeMap::new(),
+            dropped: Vec::new(),
+        }
+    }
+
+    /// Value of `key`, including changes staged by earlier steps
+    pub fn get(&self, key: &str) -> Result<Option<String>, String> {
+        self.touched.borrow_mut().insert(key.to_string());
+        match self.changes.get(key) {
+            Some(value) => Ok(value.clone()),
+            None => self.storage.get(key).map_err(|e| e.to_string()),
+        }
+    }
+
+    /// Stage a new value for `key`
+    pub fn set(&mut self, key: &str, value: String) {
+        self.touched.borrow_mut().insert(key.to_string());
+        self.changes.insert(key.to_string(), Some(value));
+    }
+
+    /// Stage the removal of `key`, once the other changes are verified
+    pub fn remove(&mut self, key: &str) {
+        self.touched.borrow_mut().insert(key.to_string());
+        self.changes.insert(key.to_string(), None);
+    }
+
+    /// Stage a copy of `records` from `key` under a quarantine key, and
+    /// report the values described in `dropped` as left out
+    pub fn quarantine(&mut self, key: &str, records: String, dropped: Vec<String>) {
+        let quarantine_key = quarantine_key(key, &records);
+        self.set(&quarantine_key, records);
+        self.dropped.push((dropped, quarantine_key));
+    }
+}
+
+/// Schema version of data written before versions were stored
+fn detect_version(storage: &dyn PlatformStorage) -> Result<u32, StorageError> {
+    Ok(if storage.get(REMINDERS_V2_KEY)?.is_some() || storage.get(TAGS_V1_KEY)?.is_some() {
+        2
+    } else if storage.get(REMINDERS_V1_KEY)?.is_some() {
+        1
+    } else {
+        // Nothing stored yet: start at the current schema
+        SCHEMA_VERSION
+    })
+}
+
+/// Stored schema version, or the detected one for unversioned data
+pub fn stored_version(storage: &dyn PlatformStorage) -> Result<u32, StorageError> {
+    match storage.get(SCHEMA_VERSION_KEY)? {
+        Some(raw) => raw.trim().parse().map_err(|_| StorageError::SerializationFailed),
+        None => detect_version(storage),
+    }
+}
+
+/// Upgrade stored data to `SCHEMA_VERSION`, running each pending step once.
+///
+/// Returns a `StorageError::MigrationDropped` for each step that had to leave
+/// values out. On failure nothing but the backup is written, so the next
+/// launch retries from the same data.
+pub fn run_migrations(storage: &dyn PlatformStorage) -> Result<Vec<StorageError>, StorageError> {
+    let versioned = storage.get(SCHEMA_VERSION_KEY)?.is_some();
+    let start = stored_version(storage)?;
+    if start > SCHEMA_VERSION {
+        return Err(StorageError::UnsupportedVersion(start));
+    }
+    if start == SCHEMA_VERSION {
+        if !versioned {
+            storage.set(SCHEMA_VERSION_KEY, &SCHEMA_VERSION.to_string())?;
+        }
+        return Ok(Vec::new());
+    }
+
+    let mut context = MigrationContext::new(storage);
+    let mut dropped = Vec::new();
+    let result = (start..SCHEMA_VERSION).try_for_each(|version| {
+        let migration = MIGRATIONS
+            .iter()
+            .find(|m| m.from == version)
+            .ok_or((version, "no migration registered".to_string()))?;
+        (migration.run)(&mut context).map_err(|reason| (version, format!("{}: {}", migration.description, reason)))?;
+        dropped.extend(context.dropped.drain(..).map(|(values, quarantine_key)| StorageError::MigrationDropped {
+            from: version,
+            dropped: values,
+            quarantine_key,
+        }));
+        Ok(())
+    });
+
+    // Back up the original value (or absence) of every key the steps used
+    let touched = context.touched.take();
+    let backup = touched
+        .iter()
+        .map(|key| Ok((key.as_str(), storage.get(key)?)))
+        .collect::<Result<BTreeMap<&str, Option<String>>, StorageError>>()?;
+    let backup = serde_json::to_string(&backup).map_err(|_| StorageError::SerializationFailed)?;
+    let backup_key = format!("{}{}", MIGRATION_BACKUP_PREFIX, start);
+    storage.set(&backup_key, &backup)?;
+
+    if let Err((from, reason)) = result {
+        return Err(StorageError::MigrationFailed { from, reason, backup_key });
+    }
+    let written: Vec<(&String, &String)> = context
+        .changes
+        .iter()
+        .filter_map(|(key, value)| value.as_ref().map(|value| (key, value)))
+        .collect();
+    for (key, value) in &written {
+        storage.set(key, value)?;
+    }
+    for (key, value) in &written {
+        if !reads_back_as(storage.get(key)?.as_deref(), value) {
+            return Err(StorageError::MigrationFailed {
+                from: start,
+                reason: format!("\"{}\" did not read back as written", key),
+                backup_key,
+            });
+        }
+    }
+    for (key, _) in context.changes.iter().filter(|(_, value)| value.is_none()) {
+        storage.remove(key)?;
+    }
+    storage.set(SCHEMA_VERSION_KEY, &SCHEMA_VERSION.to_string())?;
+    Ok(dropped)
+}
+
+// Legacy Reminder structure (v1)
+//
+// v1 didn't validate dates, so they are read as plain strings: a due date
+// that doesn't parse is left out rather than failing the whole migration, and
+// the record it came from is quarantined and reported.
+#[derive(Deserialize)]
+struct LegacyReminder {
+    id: String,
+    title: String,
+    description: String,
+    #[serde(default)]
+    due_date: Option<String>,
+    completed: bool,
+    #[serde(default)]
+    created_at: Option<String>,
+}
+
+/// v1 → v2: reminders gain tags, priorities, recurrence and the other v2 fields
+fn migrate_v1_reminders(context: &mut MigrationContext<'_>) -> Result<(), String> {
+    let Some(data) = context.get(REMINDERS_V1_KEY)? else {
+        return Ok(());
+    };
+    let records: Vec<serde_json::Value> = serde_json::from_str(&data).map_err(|e| e.to_string())?;
+    let mut unreadable = Vec::new();
+    let mut dropped = Vec::new();
+    let mut migrated = Vec::with_capacity(records.len());
+    for record in records {
+        let r = LegacyReminder::deserialize(&record).map_err(|e| e.to_string())?;
+        let due_date = match r.due_date.as_deref().map(str::trim) {
+            None | Some("") => None,
+            Some(raw) => {
+                let parsed = raw.parse::<LocalDateTime>().ok();
+                if parsed.is_none() {
+                    dropped.push(format!("due date {:?} of \"{}\"", raw, r.title));
+                    unreadable.push(record);
+                }
+                parsed
+            }
+        };
+        migrated.push(Reminder {
+            id: r.id,
+            title: r.title,
+            description: r.description,
+            due_date,
+            time_zone: None,
+            completed: r.completed,
+            created_at: r
+                .created_at
+                .and_then(|s| s.trim().parse::<Timestamp>().ok())
+                .unwrap_or_else(Timestamp::now),
+            updated_at: None,
+            tag_ids: Vec::new(),
+            priority: Priority::None,
+            recurrence: None,
+            lead_times: Vec::new(),
+            snoozed_until: None,
+            snooze_log: Vec::new(),
+            checklist: Vec::new(),
+            auto_complete: false,
+            extensions: BTreeMap::new(),
+        });
+    }
+    if !unreadable.is_empty() {
+        let records = serde_json::to_string(&unreadable).map_err(|e| e.to_string())?;
+        context.quarantine(REMINDERS_V1_KEY, records, dropped);
+    }
+    let json = serde_json::to_string(&migrated).map_err(|e| e.to_string())?;
+    context.set(REMINDERS_V2_KEY, json);
+    context.remove(REMINDERS_V1_KEY);
+    Ok(())
+}
+
+/// Whether `stored` holds the data that was written as `written`
+///
+/// Compared as JSON: backends may encode it differently (SQLite stores list
+/// items as rows and re-encodes them with their keys reordered).
+fn reads_back_as(stored: Option<&str>, written: &str) -> bool {
+    let Some(stored) = stored else {
+        return false;
+    };
+    stored == written
+        || matches!(
+            (serde_json::from_str::<serde_json::Value>(stored), serde_json::from_str::<serde_json::Value>(written)),
+            (Ok(stored), Ok(written)) if stored == written
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    use super::*;
+    use crate::storage::{MemoryStorage, Repository};
+
+    const V1_REMINDERS: &str = r#"[
+        {"id":"a","title":"Old","description":"","due_date":"2024-01-31T09:00","completed":false,"created_at":"2024-01-01T08:00:00Z"},
+        {"id":"b","title":"Bad date","description":"","due_date":"someday","completed":true}
+    ]"#;
+
+    /// Memory storage counting reads of the schema version, and able to drop writes to one key
+    #[derive(Default)]
+    struct TestStorage {
+        inner: MemoryStorage,
+        version_reads: Cell<usize>,
+        drop_writes_to: Option<&'static str>,
+    }
+
+    impl PlatformStorage for TestStorage {
+        fn get(&self, key: &str) -> Result<Option<String>, StorageError> {
+            if key == SCHEMA_VERSION_KEY {
+                self.version_reads.set(self.version_reads.get() + 1);
+            }
+            self.inner.get(key)
+        }
+
+        fn set(&self, key: &str, value: &str) -> Result<(), StorageError> {
+            if self.drop_writes_to == Some(key) {
+                return Ok(());
+            }
+            self.inner.set(key, value)
+        }
+
+        fn remove(&self, key: &str) -> Result<(), StorageError> {
+            self.inner.remove(key)
+        }
+
+        fn keys(&self) -> Result<Vec<String>, StorageError> {
+            self.inner.keys()
+        }
+
+        fn clear(&self) -> Result<(), StorageError> {
+            self.inner.clear()
+        }
+    }
+
+    fn v1_storage() -> MemoryStorage {
+        let storage = MemoryStorage::new();
+        storage.set(REMINDERS_V1_KEY, V1_REMINDERS).unwrap();
+        storage
+    }
+
+    #[test]
+    fn v1_reminders_move_to_v2_and_the_legacy_key_is_removed() {
+        let storage = v1_storage();
+        let dropped = run_migrations(&storage).unwrap();
+
+        let migrated: Vec<Reminder> = serde_json::from_str(&storage.get(REMINDERS_V2_KEY).unwrap().unwrap()).unwrap();
+        assert_eq!(migrated.len(), 2);
+        assert_eq!(migrated[0].due_date.map(|d| d.to_string()).as_deref(), Some("2024-01-31T09:00"));
+        assert_eq!(migrated[0].created_at, "2024-01-01T08:00:00Z".parse().unwrap());
+        // v1 didn't validate dates; an unreadable one is left out rather than failing the migration
+        assert_eq!(migrated[1].due_date, None);
+        assert!(migrated[1].completed);
+
+        // ...and reported, with its record quarantined
+        let [StorageError::MigrationDropped { from: 1, dropped, quarantine_key }] = dropped.as_slice() else {
+            panic!("expected the dropped due date to be reported, got {:?}", dropped);
+        };
+        assert_eq!(dropped, &[r#"due date "someday" of "Bad date""#]);
+        let quarantined: serde_json::Value = serde_json::from_str(&storage.get(quarantine_key).unwrap().unwrap()).unwrap();
+        let original: serde_json::Value = serde_json::from_str(V1_REMINDERS).unwrap();
+        assert_eq!(quarantined, serde_json::json!([original[1]]));
+
+        assert_eq!(storage.get(REMINDERS_V1_KEY).unwrap(), None);
+        assert_eq!(storage.get(SCHEMA_VERSION_KEY).unwrap().as_deref(), Some("2"));
+
+        // The legacy data is archived in the backup
+        let backup: BTreeMap<String, Option<String>> =
+            serde_json::from_str(&storage.get("migration_backup_v1").unwrap().unwrap()).unwrap();
+        assert_eq!(backup[REMINDERS_V1_KEY].as_deref(), Some(V1_REMINDERS));
+        assert_eq!(backup[REMINDERS_V2_KEY], None);
+    }
+
+    #[test]
+    fn values_the_backend_re_encodes_still_read_back() {
+        let dir = crate::test_support::TempDir::new();
+        let storage = crate::storage::SqliteStorage::open(dir.path()).unwrap();
+        storage.set(REMINDERS_V1_KEY, V1_REMINDERS).unwrap();
+        run_migrations(&storage).unwrap();
+
+        let migrated: Vec<Reminder> = serde_json::from_str(&storage.get(REMINDERS_V2_KEY).unwrap().unwrap()).unwrap();
+        assert_eq!(migrated.len(), 2);
+        assert_eq!(storage.get(REMINDERS_V1_KEY).unwrap(), None);
+        assert!(!reads_back_as(Some(r#"{"a":1,"b":3}"#), r#"{"b":2,"a":1}"#));
+        assert!(!reads_back_as(None, "[]"));
+    }
+
+    #[test]
+    fn readable_v1_reminders_report_nothing() {
+        let storage = MemoryStorage::new();
+        let readable = r#"[{"id":"a","title":"A","description":"","due_date":" ","completed":false}]"#;
+        storage.set(REMINDERS_V1_KEY, readable).unwrap();
+        assert_eq!(run_migrations(&storage).unwrap().len(), 0);
+        assert!(storage.keys().unwrap().iter().all(|key| !key.starts_with(crate::storage::QUARANTINE_PREFIX)));
+    }
+
+    #[test]
+    fn migrations_run_once() {
+        let storage = v1_storage();
+        assert_eq!(run_migrations(&storage).unwrap().len(), 1);
+        let migrated = storage.get(REMINDERS_V2_KEY).unwrap();
+
+        // Data written under the legacy key later is left alone
+        storage.set(REMINDERS_V1_KEY, "[]").unwrap();
+        assert!(run_migrations(&storage).unwrap().is_empty());
+        assert_eq!(storage.get(REMINDERS_V2_KEY).unwrap(), migrated);
+        assert_eq!(storage.get(REMINDERS_V1_KEY).unwrap().as_deref(), Some("[]"));
+    }
+
+    #[test]
+    fn fresh_and_unversioned_storage_get_the_current_version() {
+        let fresh = MemoryStorage::new();
+        run_migrations(&fresh).unwrap();
+        assert_eq!(fresh.keys().unwrap(), [SCHEMA_VERSION_KEY]);
+
+        let unversioned = MemoryStorage::new();
+        unversioned.set(TAGS_V1_KEY, "[]").unwrap();
+        run_migrations(&unversioned).unwrap();
+        assert_eq!(unversioned.get(SCHEMA_VERSION_KEY).unwrap().as_deref(), Some("2"));
+    }
+
+    #[test]
+    fn newer_schema_versions_are_refused() {
+        let storage = MemoryStorage::new();
+        storage.set(SCHEMA_VERSION_KEY, "99").unwrap();
+        assert!(matches!(run_migrations(&storage), Err(StorageError::UnsupportedVersion(99))));
+    }
+
+    #[test]
+    fn failed_migration_keeps_the_legacy_data() {
+        let storage = MemoryStorage::new();
+        storage.set(REMINDERS_V1_KEY, "{not json").unwrap();
+
+        let error = run_migrations(&storage).unwrap_err();
+        assert!(matches!(&error, StorageError::MigrationFailed { from: 1, backup_key, .. } if backup_key == "migration_backup_v1"));
+        assert_eq!(storage.get(REMINDERS_V1_KEY).unwrap().as_deref(), Some("{not json"));
+        assert_eq!(storage.get(REMINDERS_V2_KEY).unwrap(), None);
+        assert_eq!(storage.get(SCHEMA_VERSION_KEY).unwrap(), None);
+    }
+
+    #[test]
+    fn legacy_key_is_kept_if_the_new_data_does_not_read_back() {
+        let storage = TestStorage {
+            drop_writes_to: Some(REMINDERS_V2_KEY),
+            ..TestStorage::default()
+        };
+        storage.set(REMINDERS_V1_KEY, V1_REMINDERS).unwrap();
+
+        assert!(matches!(run_migrations(&storage), Err(StorageError::MigrationFailed { .. })));
+        assert_eq!(storage.get(REMINDERS_V1_KEY).unwrap().as_deref(), Some(V1_REMINDERS));
+        assert_eq!(storage.get(SCHEMA_VERSION_KEY).unwrap(), None);
+    }
+
+    #[test]
+    fn repository_migrates_once_when_created() {
+        let storage = Rc::new(TestStorage::default());
+        storage.set(REMINDERS_V1_KEY, V1_REMINDERS).unwrap();
+        let repository = Repository::new(storage.clone());
+        let reads = storage.version_reads.get();
+        assert!(reads > 0);
+
+        assert_eq!(repository.load_reminders().items.len(), 2);
+        assert!(repository.load_tags().error.is_none());
+        assert!(matches!(repository.migration_dropped(), [StorageError::MigrationDropped { .. }]));
+        repository.load_saved_views();
+        assert_eq!(storage.version_reads.get(), reads);
+    }
+
+    #[test]
+    fn repository_reports_a_failed_migration_on_every_load() {
+        let storage = Rc::new(MemoryStorage::new());
+        storage.set(REMINDERS_V1_KEY, "{not json").unwrap();
+        let repository = Repository::new(storage);
+
+        let loaded = repository.load_reminders();
+        assert!(loaded.items.is_empty());
+        assert!(matches!(loaded.error, Some(StorageError::MigrationFailed { .. })));
+        assert!(matches!(repository.load_tags().error, Some(StorageError::MigrationFailed { .. })));
+    }
+}
@@ -0,0 +1,460 @@
+//! Storage abstraction for cross-platform data persistence
+//! 
+//! This module provides a platform-agnostic storage trait and the
+//! `Repository` that loads and saves reminders, tags and saved views over it.
+//! Platform crates only pick the backend and register it with the components
+//! (see `remind_me_components::storage`):
+//!
+//! - `IndexedDbStorage` and `WebStorage` in `crates/web/src/storage.rs`
+//!   (IndexedDB, with localStorage as the fallback)
+//! - `SqliteStorage` (SQLite database, used on mobile; native builds only)
+//! - `FileStorage` (one JSON file per key, used on desktop)
+//! - `MemoryStorage` (in-memory, for previews and tests)
+//!
+//! Stored data is versioned; see `migrations` for how it is upgraded.
+//!
+//! Loading never silently discards data. A blob that fails to decode is
+//! copied to a quarantine key (see `quarantine_key`) and decoded item by
+//! item, so one damaged reminder does not lose the rest; the problem is
+//! reported through `Loaded::error`.
+
+pub mod backup;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod data_dir;
+pub mod file;
+pub mod memory;
+pub mod migrations;
+pub mod reminders;
+pub mod repository;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod sqlite;
+pub mod tags;
+
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::rc::Rc;
+
+pub use backup::{
+    Backup, BackupError, BackupItemKind, ImportConflict, ImportMode, ImportPreview, Resolution, BACKUP_FORMAT,
+    BACKUP_VERSION,
+};
+#[cfg(not(target_arch = "wasm32"))]
+pub use data_dir::{app_data_dir, DataDirResolver, DATA_DIR_ENV};
+pub use file::FileStorage;
+#[cfg(not(target_arch = "wasm32"))]
+pub use sqlite::SqliteStorage;
+pub use memory::MemoryStorage;
+pub use migrations::{run_migrations, SCHEMA_VERSION, SCHEMA_VERSION_KEY};
+pub use reminders::{ReminderChange, ReminderRepository, RepositoryError, SubscriptionId, ToggleOutcome};
+pub use repository::Repository;
+pub use tags::{IntegrityReport, TagDeletion, TagRepository};
+
+/// Storage error type
+#[derive(Debug, Clone)]
+pub enum StorageError {
+    /// Storage is unavailable (e.g., localStorage not supported)
+    Unavailable,
+    /// Failed to serialize/deserialize data
+    SerializationFailed,
+    /// Failed to save data to storage
+    SaveFailed,
+    /// Failed to read data from storage
+    ReadFailed,
+    /// Stored data was written by a newer version of the app
+    UnsupportedVersion(u32),
+    /// Upgrading stored data from schema version `from` failed; the data the
+    /// migration used is kept under `backup_key`
+    MigrationFailed { from: u32, reason: String, backup_key: String },
+    /// Upgrading stored data from schema version `from` left out the values
+    /// described in `dropped`; the records they came from are kept under
+    /// `quarantine_key`
+    MigrationDropped { from: u32, dropped: Vec<String>, quarantine_key: String },
+    /// The data under `key` was damaged; the original is kept under
+    /// `quarantine_key`. `dropped` counts unreadable items, or is `None`
+    /// if the whole blob was unreadable.
+    Corrupted { key: String, quarantine_key: String, dropped: Option<usize> },
+}
+
+impl StorageError {
+    /// Key holding the raw data affected by this error, if it was preserved
+    pub fn raw_data_key(&self) -> Option<&str> {
+        match self {
+            StorageError::MigrationFailed { backup_key, .. } => Some(backup_key),
+            StorageError::MigrationDropped { quarantine_key, .. } | StorageError::Corrupted { quarantine_key, .. } => {
+                Some(quarantine_key)
+            }
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for StorageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StorageError::Unavailable => write!(f, "storage is unavailable"),
+            StorageError::SerializationFailed => write!(f, "stored data could not be read or written"),
+            StorageError::SaveFailed => write!(f, "failed to save data"),
+            StorageError::ReadFailed => write!(f, "failed to read data"),
+            StorageError::UnsupportedVersion(version) => {
+                write!(f, "stored data uses schema version {}, newer than this app supports", version)
+            }
+            StorageError::MigrationFailed { from, reason, .. } => {
+                write!(f, "failed to upgrade stored data from schema version {}: {}", from, reason)
+            }
+            StorageError::MigrationDropped { from, dropped, .. } => write!(
+                f,
+                "upgrading stored data from schema version {} left out {} unreadable value(s): {}",
+                from,
+                dropped.len(),
+                dropped.join(", ")
+            ),
+            StorageError::Corrupted { key, dropped: Some(dropped), .. } => {
+                write!(f, "{} stored item(s) in \"{}\" could not be read", dropped, key)
+            }
+            StorageError::Corrupted { key, dropped: None, .. } => {
+                write!(f, "stored data in \"{}\" could not be read", key)
+            }
+        }
+    }
+}
+
+impl std::error::Error for StorageError {}
+
+/// Platform storage trait for key-value storage
+/// 
+/// Backends are used through `&dyn PlatformStorage` (or a `StorageHandle`),
+/// so methods take `&self` and backends needing mutable state use interior
+/// mutability.
+pub trait PlatformStorage {
+    /// Get a value from storage by key (`None` if the key is not set)
+    fn get(&self, key: &str) -> Result<Option<String>, StorageError>;
+    
+    /// Set a value in storage by key
+    fn set(&self, key: &str, value: &str) -> Result<(), StorageError>;
+    
+    /// Remove a key from storage (removing a missing key is not an error)
+    fn remove(&self, key: &str) -> Result<(), StorageError>;
+    
+    /// All keys currently in storage
+    fn keys(&self) -> Result<Vec<String>, StorageError>;
+    
+    /// Remove every key from storage
+    fn clear(&self) -> Result<(), StorageError>;
+
+    /// Store `value`, a JSON list that differs from the stored one only by
+    /// `changes`.
+    ///
+    /// The default writes the whole value; backends that keep list items as
+    /// separate records can override it to write only the changed items.
+    fn set_list(&self, key: &str, value: &str, changes: &ListChanges) -> Result<(), StorageError> {
+        let _ = changes;
+        self.set(key, value)
+    }
+
+    /// Report writes that fail after the call that made them returned
+    ///
+    /// Backends that write in the background (IndexedDB) call `listener`
+    /// when such a write fails. The others report every failure from the
+    /// call itself, so the default ignores the listener.
+    fn set_error_listener(&self, listener: Box<dyn Fn(StorageError)>) {
+        let _ = listener;
+    }
+}
+
+/// Items changed in a stored JSON list (see `PlatformStorage::set_list`)
+///
+/// Items keep their place in the list; created items are added at its end.
+#[derive(Debug, Clone, Default)]
+pub struct ListChanges {
+    /// `(id, item JSON)` of the created or updated items, in list order
+    pub upserted: Vec<(String, String)>,
+    /// Ids of the removed items
+    pub removed: Vec<String>,
+}
+
+/// Shared handle to a storage backend
+pub type StorageHandle = Rc<dyn PlatformStorage>;
+
+/// Future returned by `AsyncPlatformStorage` methods
+pub type StorageFuture<'a, T> = Pin<Box<dyn Future<Output = Result<T, StorageError>> + 'a>>;
+
+/// Asynchronous variant of `PlatformStorage`, for backends such as IndexedDB
+/// that cannot answer synchronously.
+///
+/// Every `PlatformStorage` is also an `AsyncPlatformStorage` whose futures
+/// complete immediately.
+pub trait AsyncPlatformStorage {
+    /// Get a value from storage by key (`None` if the key is not set)
+    fn get<'a>(&'a self, key: &'a str) -> StorageFuture<'a, Option<String>>;
+    
+    /// Set a value in storage by key
+    fn set<'a>(&'a self, key: &'a str, value: &'a str) -> StorageFuture<'a, ()>;
+    
+    /// Remove a key from storage (removing a missing key is not an error)
+    fn remove<'a>(&'a self, key: &'a str) -> StorageFuture<'a, ()>;
+    
+    /// All keys currently in storage
+    fn keys(&self) -> StorageFuture<'_, Vec<String>>;
+    
+    /// Remove every key from storage
+    fn clear(&self) -> StorageFuture<'_, ()>;
+}
+
+impl<S: PlatformStorage + ?Sized> AsyncPlatformStorage for S {
+    fn get<'a>(&'a self, key: &'a str) -> StorageFuture<'a, Option<String>> {
+        Box::pin(std::future::ready(PlatformStorage::get(self, key)))
+    }
+
+    fn set<'a>(&'a self, key: &'a str, value: &'a str) -> StorageFuture<'a, ()> {
+        Box::pin(std::future::ready(PlatformStorage::set(self, key, value)))
+    }
+
+    fn remove<'a>(&'a self, key: &'a str) -> StorageFuture<'a, ()> {
+        Box::pin(std::future::ready(PlatformStorage::remove(self, key)))
+    }
+
+    fn keys(&self) -> StorageFuture<'_, Vec<String>> {
+        Box::pin(std::future::ready(PlatformStorage::keys(self)))
+    }
+
+    fn clear(&self) -> StorageFuture<'_, ()> {
+        Box::pin(std::future::ready(PlatformStorage::clear(self)))
+    }
+}
+
+// Storage keys
+pub const REMINDERS_V2_KEY: &str = "reminders_v2";
+pub const REMINDERS_V1_KEY: &str = "reminders"; // Legacy key
+pub const TAGS_V1_KEY: &str = "tags_v1";
+pub const SAVED_VIEWS_V1_KEY: &str = "saved_views_v1";
+
+/// Prefix of the keys holding quarantined (undecodable) blobs
+pub const QUARANTINE_PREFIX: &str = "quarantine_";
+
+/// Items loaded from storage, with the problem that occurred if any.
+///
+/// Loading is best-effort: when stored data is damaged, `items` holds what
+/// could be recovered and `error` says what went wrong.
+#[derive(Debug, Clone)]
+pub struct Loaded<T> {
+    pub items: Vec<T>,
+    pub error: Option<StorageError>,
+}
+
+impl<T> Loaded<T> {
+    fn ok(items: Vec<T>) -> Self {
+        Self { items, error: None }
+    }
+
+    fn failed(error: StorageError) -> Self {
+        Self {
+            items: Vec::new(),
+            error: Some(error),
+        }
+    }
+}
+
+/// Key under which a damaged blob from `key` is quarantined.
+///
+/// The key includes a hash of the blob, so quarantining the same data twice
+/// is a no-op and different damaged blobs never overwrite each other.
+pub fn quarantine_key(key: &str, blob: &str) -> String {
+    format!("{}{}_{:016x}", QUARANTINE_PREFIX, key, fnv1a(blob))
+}
+
+/// FNV-1a hash: stable across builds and platforms, unlike `DefaultHasher`
+pub(crate) fn fnv1a(data: &str) -> u64 {
+    data.bytes()
+        .fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| (hash ^ byte as u64).wrapping_mul(0x0000_0100_0000_01b3))
+}
+
+/// Decode a JSON list leniently, returning the readable items and the
+/// number of unreadable ones (`None` if `data` is not a JSON list at all)
+pub fn decode_lenient<T: serde::de::DeserializeOwned>(data: &str) -> (Vec<T>, Option<usize>) {
+    if let Ok(items) = serde_json::from_str::<Vec<T>>(data) {
+        return (items, Some(0));
+    }
+    let Ok(values) = serde_json::from_str::<Vec<serde_json::Value>>(data) else {
+        return (Vec::new(), None);
+    };
+    let total = values.len();
+    let items: Vec<T> = values.into_iter().filter_map(|value| serde_json::from_value(value).ok()).collect();
+    let dropped = total - items.len();
+    (items, Some(dropped))
+}
+
+/// The same checks against every native backend
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+    use crate::models::{Reminder, SavedView};
+    use crate::test_support::{datetime, due_reminder, reminder, saved_view, tag, TempDir};
+
+    fn json<T: serde::Serialize>(items: &[T]) -> String {
+        serde_json::to_string(items).unwrap()
+    }
+
+    fn parsed(value: &str) -> serde_json::Value {
+        serde_json::from_str(value).unwrap()
+    }
+
+    fn sorted_keys(storage: &dyn PlatformStorage) -> Vec<String> {
+        let mut keys = storage.keys().unwrap();
+        keys.sort();
+        keys
+    }
+
+    fn check_values(storage: &dyn PlatformStorage) {
+        assert_eq!(storage.get("missing").unwrap(), None);
+        assert!(storage.keys().unwrap().is_empty());
+
+        storage.set("saved_views_v1", "[]").unwrap();
+        storage.set("schema_version", "2").unwrap();
+        storage.set("schema_version", "3").unwrap();
+        storage.set("note", "\"héllo ✓\"").unwrap();
+        assert_eq!(storage.get("schema_version").unwrap().as_deref(), Some("3"));
+        assert_eq!(storage.get("note").unwrap().as_deref(), Some("\"héllo ✓\""));
+        assert_eq!(sorted_keys(storage), ["note", "saved_views_v1", "schema_version"]);
+
+        storage.remove("note").unwrap();
+        storage.remove("note").unwrap();
+        assert_eq!(storage.get("note").unwrap(), None);
+        assert_eq!(sorted_keys(storage), ["saved_views_v1", "schema_version"]);
+
+        storage.clear().unwrap();
+        assert!(storage.keys().unwrap().is_empty());
+        assert_eq!(storage.get("schema_version").unwrap(), None);
+    }
+
+    fn check_lists(storage: &dyn PlatformStorage) {
+        let item = |reminder: &Reminder| (reminder.id.clone(), serde_json::to_string(reminder).unwrap());
+        let (a, b, c) = (reminder("a", "A"), reminder("b", "B"), reminder("c", "C"));
+
+        // The first write of a list has nothing to apply the changes to
+        let value = json(&[a.clone(), b.clone()]);
+        let changes = ListChanges { upserted: vec![item(&a), item(&b)], removed: Vec::new() };
+        storage.set_list(REMINDERS_V2_KEY, &value, &changes).unwrap();
+        assert_eq!(parsed(&storage.get(REMINDERS_V2_KEY).unwrap().unwrap()), parsed(&value));
+
+        let b = Reminder { title: "B edited".to_string(), ..b };
+        let value = json(&[b.clone(), c.clone()]);
+        let changes = ListChanges { upserted: vec![item(&b), item(&c)], removed: vec!["a".to_string()] };
+        storage.set_list(REMINDERS_V2_KEY, &value, &changes).unwrap();
+        assert_eq!(parsed(&storage.get(REMINDERS_V2_KEY).unwrap().unwrap()), parsed(&value));
+        assert_eq!(sorted_keys(storage), [REMINDERS_V2_KEY]);
+
+        // Keys that aren't split into items are written whole
+        let tags = json(&[tag("t1", "Work")]);
+        storage.set_list(TAGS_V1_KEY, &tags, &ListChanges::default()).unwrap();
+        storage.set_list("saved_views_v1", "[]", &ListChanges::default()).unwrap();
+        assert_eq!(parsed(&storage.get(TAGS_V1_KEY).unwrap().unwrap()), parsed(&tags));
+        assert_eq!(storage.get("saved_views_v1").unwrap().as_deref(), Some("[]"));
+
+        storage.remove(REMINDERS_V2_KEY).unwrap();
+        assert_eq!(storage.get(REMINDERS_V2_KEY).unwrap(), None);
+    }
+
+    fn check_repository(storage: StorageHandle) {
+        let repository = Repository::new(storage.clone());
+        assert_eq!(
+            storage.get(SCHEMA_VERSION_KEY).unwrap().as_deref(),
+            Some(SCHEMA_VERSION.to_string().as_str())
+        );
+        let loaded = repository.load_reminders();
+        assert!(loaded.items.is_empty() && loaded.error.is_none());
+
+        let reminders = vec![due_reminder("a", datetime(2024, 1, 20, 9, 0)), reminder("b", "B")];
+        let tags = vec![tag("t1", "Work"), tag("t2", "Home")];
+        let views = vec![SavedView { search_query: "is:overdue".to_string(), ..saved_view("v1", "Overdue first") }];
+        repository.save_reminders(&reminders).unwrap();
+        repository.save_tags(&tags).unwrap();
+        repository.save_saved_views(&views).unwrap();
+        assert_eq!(repository.load_reminders().items, reminders);
+        assert_eq!(repository.load_tags().items, tags);
+        assert_eq!(repository.load_saved_views().items, views);
+
+        let edited = Reminder { completed: true, ..reminders[1].clone() };
+        let next = vec![edited, reminder("c", "C")];
+        repository.save_reminder_changes(&next, &["b", "c"], &["a".to_string()]).unwrap();
+        let loaded = repository.load_reminders();
+        assert_eq!(loaded.items, next);
+        assert!(loaded.error.is_none());
+
+        // A damaged item is reported and the blob quarantined; the rest loads
+        let good = serde_json::to_string(&reminder("z", "Z")).unwrap();
+        let data = format!(r#"[{good},{{"id":"x","title":"Missing fields"}},{{"id":"y"}}]"#);
+        storage.set(REMINDERS_V2_KEY, &data).unwrap();
+        let loaded = repository.load_reminders();
+        assert_eq!(loaded.items, [reminder("z", "Z")]);
+        let Some(StorageError::Corrupted { quarantine_key, dropped, .. }) = loaded.error else {
+            panic!("expected Corrupted, got {:?}", loaded.error);
+        };
+        assert_eq!(dropped, Some(2));
+        assert_eq!(parsed(&storage.get(&quarantine_key).unwrap().unwrap()), parsed(&data));
+    }
+
+    fn check_legacy_migration(storage: StorageHandle) {
+        storage
+            .set(
+                REMINDERS_V1_KEY,
+                r#"[{"id":"a","title":"Old","description":"","due_date":"2024-01-31T09:00","completed":false}]"#,
+            )
+            .unwrap();
+        let repository = Repository::new(storage.clone());
+        let loaded = repository.load_reminders();
+        assert!(loaded.error.is_none(), "{:?}", loaded.error);
+        assert_eq!(loaded.items.len(), 1);
+        assert_eq!(loaded.items[0].title, "Old");
+        assert_eq!(storage.get(REMINDERS_V1_KEY).unwrap(), None);
+    }
+
+    /// Data written through one handle is there when the backend is opened again
+    fn check_persistence(dir: &Path, open: impl Fn(&Path) -> StorageHandle) {
+        let reminders = vec![reminder("a", "A"), reminder("b", "B")];
+        Repository::new(open(dir)).save_reminders(&reminders).unwrap();
+        let reopened = Repository::new(open(dir));
+        assert_eq!(reopened.load_reminders().items, reminders);
+    }
+
+    fn check_backend(open: impl Fn() -> StorageHandle) {
+        check_values(&*open());
+        check_lists(&*open());
+        check_repository(open());
+        check_legacy_migration(open());
+    }
+
+    #[test]
+    fn memory_storage() {
+        check_backend(|| Rc::new(MemoryStorage::new()));
+    }
+
+    #[test]
+    fn file_storage() {
+        let dirs = std::cell::RefCell::new(Vec::new());
+        // Each check gets a new directory
+        check_backend(|| {
+            let dir = TempDir::new();
+            let storage = Rc::new(FileStorage::new(dir.path()));
+            dirs.borrow_mut().push(dir);
+            storage
+        });
+        let dir = TempDir::new();
+        check_persistence(dir.path(), |dir| Rc::new(FileStorage::new(dir)));
+    }
+
+    #[test]
+    fn sqlite_storage() {
+        let dirs = std::cell::RefCell::new(Vec::new());
+        check_backend(|| {
+            let dir = TempDir::new();
+            let storage = Rc::new(SqliteStorage::open(dir.path()).unwrap());
+            dirs.borrow_mut().push(dir);
+            storage
+        });
+        let dir = TempDir::new();
+        check_persistence(dir.path(), |dir| Rc::new(SqliteStorage::open(dir).unwrap()));
+    }
+}
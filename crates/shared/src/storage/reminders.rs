@@ -0,0 +1,659 @@
+//! Reminder repository: granular reminder operations with change events
+//!
+//! `ReminderRepository` keeps the reminders in memory and applies one
+//! operation at a time. Each operation checks the invariants (unique ids, no
+//! `tag_ids` that don't name a stored tag), saves only what changed (see
+//! `PlatformStorage::set_list`) and then tells subscribers what happened.
+//! A failed save leaves both the stored and the in-memory reminders as they
+//! were.
+
+use std::cell::{Cell, RefCell};
+use std::collections::HashSet;
+use std::fmt;
+use std::rc::Rc;
+
+use super::{Repository, StorageError};
+use crate::datetime::Timestamp;
+use crate::models::Reminder;
+use crate::recurrence::advance;
+use crate::snooze::clear_snooze;
+
+/// A change made through a `ReminderRepository`
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReminderChange {
+    Created(Reminder),
+    Updated(Reminder),
+    Deleted(Reminder),
+}
+
+impl ReminderChange {
+    /// Apply the change to a copy of the reminder list
+    pub fn apply(&self, reminders: &mut Vec<Reminder>) {
+        match self {
+            ReminderChange::Created(reminder) => reminders.push(reminder.clone()),
+            ReminderChange::Updated(reminder) => {
+                if let Some(existing) = reminders.iter_mut().find(|r| r.id == reminder.id) {
+                    *existing = reminder.clone();
+                }
+            }
+            ReminderChange::Deleted(reminder) => reminders.retain(|r| r.id != reminder.id),
+        }
+    }
+}
+
+/// What `ReminderRepository::toggle` did
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ToggleOutcome {
+    Completed,
+    Reactivated,
+    /// A recurring reminder moved on to its next occurrence
+    Rescheduled,
+}
+
+/// Error from a `ReminderRepository` operation
+#[derive(Debug, Clone)]
+pub enum RepositoryError {
+    /// A reminder with this id already exists
+    DuplicateId(String),
+    /// No reminder has this id
+    NotFound(String),
+    /// No tag has this id
+    TagNotFound(String),
+    /// Another tag already has this name
+    TagNameTaken(String),
+    /// The reminder refers to a tag that doesn't exist
+    UnknownTag { reminder_id: String, tag_id: String },
+    /// Saving the change failed
+    Storage(StorageError),
+}
+
+impl fmt::Display for RepositoryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RepositoryError::DuplicateId(id) => write!(f, "a reminder with id \"{}\" already exists", id),
+            RepositoryError::NotFound(id) => write!(f, "no reminder with id \"{}\"", id),
+            RepositoryError::TagNotFound(id) => write!(f, "no tag with id \"{}\"", id),
+            RepositoryError::TagNameTaken(name) => write!(f, "a tag named \"{}\" already exists", name),
+            RepositoryError::UnknownTag { reminder_id, tag_id } => {
+                write!(f, "reminder \"{}\" refers to unknown tag \"{}\"", reminder_id, tag_id)
+            }
+            RepositoryError::Storage(error) => error.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for RepositoryError {}
+
+impl From<StorageError> for RepositoryError {
+    fn from(error: StorageError) -> Self {
+        RepositoryError::Storage(error)
+    }
+}
+
+/// Handle returned by `ReminderRepository::subscribe`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SubscriptionId(usize);
+
+type Subscriber = Rc<dyn Fn(&ReminderChange)>;
+
+/// Reminders with granular, validated operations
+pub struct ReminderRepository {
+    repository: Repository,
+    reminders: RefCell<Vec<Reminder>>,
+    subscribers: RefCell<Vec<(SubscriptionId, Subscriber)>>,
+    next_subscription: Cell<usize>,
+}
+
+impl ReminderRepository {
+    /// Load the stored reminders, with the problem found while loading if any
+    pub fn open(repository: Repository) -> (Self, Option<StorageError>) {
+        let loaded = repository.load_reminders();
+        let reminders = Self {
+            repository,
+            reminders: RefCell::new(loaded.items),
+            subscribers: RefCell::new(Vec::new()),
+            next_subscription: Cell::new(0),
+        };
+        (reminders, loaded.error)
+    }
+
+    /// Re-read the stored reminders after they were replaced outside the
+    /// repository (e.g., by a backup import); subscribers aren't notified
+    pub fn reload(&self) -> Option<StorageError> {
+        let loaded = self.repository.load_reminders();
+        *self.reminders.borrow_mut() = loaded.items;
+        loaded.error
+    }
+
+    /// Every reminder, in stored order
+    pub fn all(&self) -> Vec<Reminder> {
+        self.reminders.borrow().clone()
+    }
+
+    pub fn get_by_id(&self, id: &str) -> Option<Reminder> {
+        self.reminders.borrow().iter().find(|r| r.id == id).cloned()
+    }
+
+    /// Call `subscriber` after every successful change
+    pub fn subscribe(&self, subscriber: impl Fn(&ReminderChange) + 'static) -> SubscriptionId {
+        let id = SubscriptionId(self.next_subscription.get());
+        self.next_subscription.set(id.0 + 1);
+        self.subscribers.borrow_mut().push((id, Rc::new(subscriber)));
+        id
+    }
+
+    pub fn unsubscribe(&self, id: SubscriptionId) {
+        self.subscribers.borrow_mut().retain(|(subscription, _)| *subscription != id);
+    }
+
+    /// Add a new reminder
+    pub fn create(&self, reminder: Reminder) -> Result<(), RepositoryError> {
+        if self.reminders.borrow().iter().any(|r| r.id == reminder.id) {
+            return Err(RepositoryError::DuplicateId(reminder.id));
+        }
+        self.check_tags(reminder.tag_ids.iter().map(|tag_id| (&reminder.id, tag_id)))?;
+
+        let mut reminders = self.all();
+        reminders.push(reminder.clone());
+        self.save(reminders, &[&reminder.id], &[])?;
+        self.notify(&[ReminderChange::Created(reminder)]);
+        Ok(())
+    }
+
+    /// Replace the stored reminder with the same id, stamping `updated_at`
+    pub fn update(&self, reminder: Reminder) -> Result<(), RepositoryError> {
+        self.bulk_update(vec![reminder])
+    }
+
+    /// Replace several reminders at once; nothing changes unless all of them are valid
+    pub fn bulk_update(&self, mut updated: Vec<Reminder>) -> Result<(), RepositoryError> {
+        let now = Timestamp::now();
+        for reminder in &mut updated {
+            reminder.updated_at = Some(now);
+        }
+        self.replace(updated)
+    }
+
+    /// Write back reminders whose stored data was repaired (see
+    /// `TagRepository::repair_references`); unlike `bulk_update` this keeps
+    /// `updated_at`, since nobody edited them
+    pub fn repair(&self, repaired: Vec<Reminder>) -> Result<(), RepositoryError> {
+        self.replace(repaired)
+    }
+
+    /// Replace the stored reminders with the same ids as `updated`, all or nothing
+    fn replace(&self, updated: Vec<Reminder>) -> Result<(), RepositoryError> {
+        let mut reminders = self.all();
+        let mut added_tags = Vec::new();
+        for reminder in &updated {
+            let existing = reminders
+                .iter_mut()
+                .find(|r| r.id == reminder.id)
+                .ok_or_else(|| RepositoryError::NotFound(reminder.id.clone()))?;
+            // Only newly added tags are checked, so a reminder saved before
+            // tags were validated can still be edited
+            added_tags.extend(
+                reminder
+                    .tag_ids
+                    .iter()
+                    .filter(|tag_id| !existing.tag_ids.contains(tag_id))
+                    .map(|tag_id| (&reminder.id, tag_id)),
+            );
+            *existing = reminder.clone();
+        }
+        self.check_tags(added_tags)?;
+
+        let ids: Vec<&str> = updated.iter().map(|r| r.id.as_str()).collect();
+        self.save(reminders, &ids, &[])?;
+        let changes: Vec<ReminderChange> = updated.into_iter().map(ReminderChange::Updated).collect();
+        self.notify(&changes);
+        Ok(())
+    }
+
+    /// Add imported reminders, replacing stored ones with the same id;
+    /// returns how many were added and how many replaced
+    ///
+    /// Within the batch, the last reminder with an id wins. Nothing changes
+    /// unless every reminder is valid.
+    pub fn import(&self, mut imported: Vec<Reminder>) -> Result<(usize, usize), RepositoryError> {
+        let mut seen = HashSet::new();
+        imported.reverse();
+        imported.retain(|r| seen.insert(r.id.clone()));
+        imported.reverse();
+        self.check_tags(imported.iter().flat_map(|r| r.tag_ids.iter().map(move |tag_id| (&r.id, tag_id))))?;
+
+        let now = Timestamp::now();
+        let mut reminders = self.all();
+        let mut changes = Vec::with_capacity(imported.len());
+        for reminder in &mut imported {
+            match reminders.iter_mut().find(|r| r.id == reminder.id) {
+                Some(existing) => {
+                    reminder.updated_at = Some(now);
+                    *existing = reminder.clone();
+                    changes.push(ReminderChange::Updated(reminder.clone()));
+                }
+                None => {
+                    reminders.push(reminder.clone());
+                    changes.push(ReminderChange::Created(reminder.clone()));
+                }
+            }
+        }
+        let replaced = changes.iter().filter(|c| matches!(c, ReminderChange::Updated(_))).count();
+
+        let ids: Vec<&str> = imported.iter().map(|r| r.id.as_str()).collect();
+        self.save(reminders, &ids, &[])?;
+        self.notify(&changes);
+        Ok((changes.len() - replaced, replaced))
+    }
+
+    /// Remove a reminder, returning it
+    pub fn delete(&self, id: &str) -> Result<Reminder, RepositoryError> {
+        let mut reminders = self.all();
+        let index = reminders
+            .iter()
+            .position(|r| r.id == id)
+            .ok_or_else(|| RepositoryError::NotFound(id.to_string()))?;
+        let removed = reminders.remove(index);
+
+        self.save(reminders, &[], std::slice::from_ref(&removed.id))?;
+        self.notify(&[ReminderChange::Deleted(removed.clone())]);
+        Ok(removed)
+    }
+
+    /// Toggle completion; completing a recurring reminder rolls it forward instead
+    pub fn toggle(&self, id: &str) -> Result<ToggleOutcome, RepositoryError> {
+        let mut reminder = self
+            .get_by_id(id)
+            .ok_or_else(|| RepositoryError::NotFound(id.to_string()))?;
+        let outcome = if !reminder.completed && advance(&mut reminder) {
+            ToggleOutcome::Rescheduled
+        } else {
+            reminder.completed = !reminder.completed;
+            if reminder.completed {
+                ToggleOutcome::Completed
+            } else {
+                ToggleOutcome::Reactivated
+            }
+        };
+        if reminder.completed {
+            clear_snooze(&mut reminder);
+        }
+        self.update(reminder)?;
+        Ok(outcome)
+    }
+
+    /// Fail if any `(reminder id, tag id)` pair names a tag that isn't stored
+    fn check_tags<'a>(
+        &self,
+        tag_refs: impl IntoIterator<Item = (&'a String, &'a String)>,
+    ) -> Result<(), RepositoryError> {
+        let mut tag_refs = tag_refs.into_iter().peekable();
+        if tag_refs.peek().is_none() {
+            return Ok(());
+        }
+        let tags = self.repository.load_tags();
+        let known: HashSet<&str> = tags.items.iter().map(|tag| tag.id.as_str()).collect();
+        match tag_refs.find(|(_, tag_id)| !known.contains(tag_id.as_str())) {
+            Some((reminder_id, tag_id)) => Err(RepositoryError::UnknownTag {
+                reminder_id: reminder_id.clone(),
+                tag_id: tag_id.clone(),
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Save `reminders` and make them current
+    fn save(&self, reminders: Vec<Reminder>, changed: &[&str], removed: &[String]) -> Result<(), RepositoryError> {
+        self.repository.save_reminder_changes(&reminders, changed, removed)?;
+        *self.reminders.borrow_mut() = reminders;
+        Ok(())
+    }
+
+    fn notify(&self, changes: &[ReminderChange]) {
+        // Subscribers may call back into the repository, so don't hold the borrow
+        let subscribers: Vec<Subscriber> = self.subscribers.borrow().iter().map(|(_, s)| s.clone()).collect();
+        for change in changes {
+            for subscriber in &subscribers {
+                subscriber(change);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Recurrence, RecurrenceFrequency};
+    use crate::storage::{ListChanges, MemoryStorage, PlatformStorage, REMINDERS_V2_KEY};
+    use crate::test_support::{datetime, due_reminder, reminder, tag, JAN_15_MS};
+
+    /// Memory storage recording every `set_list`, and able to fail writes
+    #[derive(Default)]
+    struct TestStorage {
+        inner: MemoryStorage,
+        lists: RefCell<Vec<ListChanges>>,
+        fail_writes: Cell<bool>,
+    }
+
+    impl PlatformStorage for TestStorage {
+        fn get(&self, key: &str) -> Result<Option<String>, StorageError> {
+            self.inner.get(key)
+        }
+
+        fn set(&self, key: &str, value: &str) -> Result<(), StorageError> {
+            if self.fail_writes.get() {
+                return Err(StorageError::SaveFailed);
+            }
+            self.inner.set(key, value)
+        }
+
+        fn remove(&self, key: &str) -> Result<(), StorageError> {
+            self.inner.remove(key)
+        }
+
+        fn keys(&self) -> Result<Vec<String>, StorageError> {
+            self.inner.keys()
+        }
+
+        fn clear(&self) -> Result<(), StorageError> {
+            self.inner.clear()
+        }
+
+        fn set_list(&self, key: &str, value: &str, changes: &ListChanges) -> Result<(), StorageError> {
+            assert_eq!(key, REMINDERS_V2_KEY);
+            self.set(key, value)?;
+            self.lists.borrow_mut().push(changes.clone());
+            Ok(())
+        }
+    }
+
+    struct Fixture {
+        storage: Rc<TestStorage>,
+        reminders: ReminderRepository,
+        events: Rc<RefCell<Vec<ReminderChange>>>,
+    }
+
+    impl Fixture {
+        /// Repository holding `stored`, with tags `work` and `home`
+        fn new(stored: &[Reminder]) -> Self {
+            let storage = Rc::new(TestStorage::default());
+            let repository = Repository::new(storage.clone());
+            repository.save_tags(&[tag("work", "Work"), tag("home", "Home")]).unwrap();
+            repository.save_reminders(stored).unwrap();
+            let (reminders, error) = ReminderRepository::open(repository);
+            assert!(error.is_none());
+
+            let events = Rc::new(RefCell::new(Vec::new()));
+            let recorded = events.clone();
+            reminders.subscribe(move |change| recorded.borrow_mut().push(change.clone()));
+            Self { storage, reminders, events }
+        }
+
+        fn ids(&self) -> Vec<String> {
+            self.reminders.all().into_iter().map(|r| r.id).collect()
+        }
+
+        /// Reminders as stored, not as held in memory
+        fn stored(&self) -> Vec<Reminder> {
+            serde_json::from_str(&self.storage.get(REMINDERS_V2_KEY).unwrap().unwrap()).unwrap()
+        }
+
+        /// The `(upserted ids, removed ids)` of every list write so far
+        fn writes(&self) -> Vec<(Vec<String>, Vec<String>)> {
+            self.storage
+                .lists
+                .borrow()
+                .iter()
+                .map(|changes| {
+                    let upserted = changes.upserted.iter().map(|(id, _)| id.clone()).collect();
+                    (upserted, changes.removed.clone())
+                })
+                .collect()
+        }
+
+        fn events(&self) -> Vec<ReminderChange> {
+            self.events.borrow().clone()
+        }
+
+        /// Nothing was written or reported, and memory still matches storage
+        fn assert_unchanged(&self, ids: &[&str]) {
+            assert_eq!(self.ids(), ids);
+            assert_eq!(self.stored(), self.reminders.all());
+            assert!(self.writes().is_empty());
+            assert!(self.events().is_empty());
+        }
+    }
+
+    fn strings(ids: &[&str]) -> Vec<String> {
+        ids.iter().map(|id| id.to_string()).collect()
+    }
+
+    fn tagged(id: &str, tag_ids: &[&str]) -> Reminder {
+        Reminder {
+            tag_ids: strings(tag_ids),
+            ..reminder(id, id)
+        }
+    }
+
+    #[test]
+    fn create_appends_and_writes_only_the_new_reminder() {
+        let fixture = Fixture::new(&[reminder("a", "A")]);
+        let created = tagged("b", &["work"]);
+        fixture.reminders.create(created.clone()).unwrap();
+
+        assert_eq!(fixture.ids(), ["a", "b"]);
+        assert_eq!(fixture.stored(), fixture.reminders.all());
+        assert_eq!(fixture.writes(), [(strings(&["b"]), Vec::new())]);
+        let (_, json) = &fixture.storage.lists.borrow()[0].upserted[0];
+        assert_eq!(serde_json::from_str::<Reminder>(json).unwrap(), created);
+        assert_eq!(fixture.events(), [ReminderChange::Created(created)]);
+    }
+
+    #[test]
+    fn create_rejects_duplicate_ids_and_unknown_tags() {
+        let fixture = Fixture::new(&[reminder("a", "A")]);
+
+        let error = fixture.reminders.create(reminder("a", "Again")).unwrap_err();
+        assert!(matches!(&error, RepositoryError::DuplicateId(id) if id == "a"));
+        let error = fixture.reminders.create(tagged("b", &["work", "gym"])).unwrap_err();
+        assert!(matches!(
+            &error,
+            RepositoryError::UnknownTag { reminder_id, tag_id } if reminder_id == "b" && tag_id == "gym"
+        ));
+        assert_eq!(error.to_string(), "reminder \"b\" refers to unknown tag \"gym\"");
+
+        fixture.assert_unchanged(&["a"]);
+        assert_eq!(fixture.reminders.get_by_id("a").unwrap().title, "A");
+    }
+
+    #[test]
+    fn update_stamps_and_writes_only_the_updated_reminder() {
+        let fixture = Fixture::new(&[reminder("a", "A"), reminder("b", "B")]);
+        let edited = Reminder { title: "B edited".to_string(), ..reminder("b", "B") };
+        fixture.reminders.update(edited).unwrap();
+
+        let updated = fixture.reminders.get_by_id("b").unwrap();
+        assert_eq!(updated.title, "B edited");
+        assert!(updated.updated_at.is_some());
+        assert_eq!(fixture.reminders.get_by_id("a").unwrap().updated_at, None);
+        assert_eq!(fixture.stored(), fixture.reminders.all());
+        assert_eq!(fixture.writes(), [(strings(&["b"]), Vec::new())]);
+        assert_eq!(fixture.events(), [ReminderChange::Updated(updated)]);
+    }
+
+    #[test]
+    fn update_rejects_missing_reminders_and_newly_added_unknown_tags() {
+        // "legacy" was saved before tags were checked
+        let fixture = Fixture::new(&[reminder("a", "A"), tagged("legacy", &["gone"])]);
+
+        let error = fixture.reminders.update(reminder("missing", "Missing")).unwrap_err();
+        assert!(matches!(&error, RepositoryError::NotFound(id) if id == "missing"));
+        let error = fixture.reminders.update(tagged("a", &["gone"])).unwrap_err();
+        assert!(matches!(&error, RepositoryError::UnknownTag { tag_id, .. } if tag_id == "gone"));
+        // All or nothing: one bad reminder keeps the others from changing
+        let error = fixture
+            .reminders
+            .bulk_update(vec![tagged("a", &["work"]), reminder("missing", "Missing")])
+            .unwrap_err();
+        assert!(matches!(error, RepositoryError::NotFound(_)));
+        fixture.assert_unchanged(&["a", "legacy"]);
+
+        // A tag that was already there doesn't block other edits
+        let edited = Reminder { title: "Still tagged".to_string(), ..tagged("legacy", &["gone", "home"]) };
+        fixture.reminders.update(edited).unwrap();
+        assert_eq!(fixture.reminders.get_by_id("legacy").unwrap().tag_ids, ["gone", "home"]);
+    }
+
+    #[test]
+    fn bulk_update_writes_every_updated_reminder_once() {
+        let fixture = Fixture::new(&[reminder("a", "A"), reminder("b", "B"), reminder("c", "C")]);
+        let done = |id: &str| Reminder { completed: true, ..reminder(id, id) };
+        fixture.reminders.bulk_update(vec![done("c"), done("a")]).unwrap();
+
+        assert_eq!(fixture.writes(), [(strings(&["a", "c"]), Vec::new())]);
+        let events = fixture.events();
+        assert_eq!(events.len(), 2);
+        assert!(matches!(&events[0], ReminderChange::Updated(r) if r.id == "c" && r.completed));
+        assert!(matches!(&events[1], ReminderChange::Updated(r) if r.id == "a" && r.completed));
+        assert_eq!(fixture.stored(), fixture.reminders.all());
+    }
+
+    #[test]
+    fn import_adds_and_replaces_all_or_nothing() {
+        let fixture = Fixture::new(&[reminder("a", "A")]);
+
+        let error = fixture
+            .reminders
+            .import(vec![reminder("new", "New"), tagged("other", &["gym"])])
+            .unwrap_err();
+        assert!(matches!(error, RepositoryError::UnknownTag { .. }));
+        fixture.assert_unchanged(&["a"]);
+
+        let imported = vec![tagged("b", &["home"]), reminder("a", "A imported")];
+        assert_eq!(fixture.reminders.import(imported).unwrap(), (1, 1));
+        assert_eq!(fixture.ids(), ["a", "b"]);
+        let replaced = fixture.reminders.get_by_id("a").unwrap();
+        assert_eq!(replaced.title, "A imported");
+        assert!(replaced.updated_at.is_some());
+        assert_eq!(fixture.reminders.get_by_id("b").unwrap().updated_at, None);
+
+        assert_eq!(fixture.stored(), fixture.reminders.all());
+        assert_eq!(fixture.writes(), [(strings(&["a", "b"]), Vec::new())]);
+        assert_eq!(
+            fixture.events(),
+            [
+                ReminderChange::Created(tagged("b", &["home"])),
+                ReminderChange::Updated(replaced),
+            ]
+        );
+    }
+
+    #[test]
+    fn import_keeps_the_last_reminder_with_a_repeated_id() {
+        let fixture = Fixture::new(&[reminder("a", "A")]);
+        let imported = vec![
+            reminder("b", "B first"),
+            reminder("a", "A first"),
+            reminder("c", "C"),
+            reminder("b", "B last"),
+            reminder("a", "A last"),
+        ];
+        assert_eq!(fixture.reminders.import(imported).unwrap(), (2, 1));
+        assert_eq!(fixture.ids(), ["a", "c", "b"]);
+        let titles: Vec<String> = fixture.reminders.all().into_iter().map(|r| r.title).collect();
+        assert_eq!(titles, ["A last", "C", "B last"]);
+        assert_eq!(fixture.stored(), fixture.reminders.all());
+        assert_eq!(fixture.writes(), [(strings(&["a", "c", "b"]), Vec::new())]);
+        assert_eq!(fixture.events().len(), 3);
+    }
+
+    #[test]
+    fn delete_removes_and_returns_the_reminder() {
+        let fixture = Fixture::new(&[reminder("a", "A"), reminder("b", "B")]);
+
+        let error = fixture.reminders.delete("missing").unwrap_err();
+        assert!(matches!(&error, RepositoryError::NotFound(id) if id == "missing"));
+        fixture.assert_unchanged(&["a", "b"]);
+
+        assert_eq!(fixture.reminders.delete("a").unwrap(), reminder("a", "A"));
+        assert_eq!(fixture.ids(), ["b"]);
+        assert_eq!(fixture.stored(), fixture.reminders.all());
+        assert_eq!(fixture.writes(), [(Vec::new(), strings(&["a"]))]);
+        assert_eq!(fixture.events(), [ReminderChange::Deleted(reminder("a", "A"))]);
+    }
+
+    #[test]
+    fn toggle_completes_reactivates_and_reschedules() {
+        let mut daily = due_reminder("daily", datetime(2024, 1, 15, 9, 0));
+        daily.recurrence = Some(Recurrence::new(RecurrenceFrequency::Daily));
+        let mut snoozed = reminder("snoozed", "Snoozed");
+        snoozed.snoozed_until = Some(Timestamp::from_millis(JAN_15_MS));
+        let fixture = Fixture::new(&[snoozed, daily]);
+
+        assert_eq!(fixture.reminders.toggle("snoozed").unwrap(), ToggleOutcome::Completed);
+        let completed = fixture.reminders.get_by_id("snoozed").unwrap();
+        assert!(completed.completed);
+        assert_eq!(completed.snoozed_until, None);
+        assert_eq!(fixture.reminders.toggle("snoozed").unwrap(), ToggleOutcome::Reactivated);
+        assert!(!fixture.reminders.get_by_id("snoozed").unwrap().completed);
+
+        assert_eq!(fixture.reminders.toggle("daily").unwrap(), ToggleOutcome::Rescheduled);
+        let rescheduled = fixture.reminders.get_by_id("daily").unwrap();
+        assert!(!rescheduled.completed);
+        assert_eq!(rescheduled.due_date.map(|due| due.naive()), Some(datetime(2024, 1, 16, 9, 0)));
+
+        assert!(matches!(fixture.reminders.toggle("missing"), Err(RepositoryError::NotFound(_))));
+        assert_eq!(fixture.stored(), fixture.reminders.all());
+        let written: Vec<_> = fixture.writes().into_iter().map(|(upserted, _)| upserted).collect();
+        assert_eq!(written, [strings(&["snoozed"]), strings(&["snoozed"]), strings(&["daily"])]);
+        assert_eq!(fixture.events().len(), 3);
+    }
+
+    #[test]
+    fn a_failed_save_changes_nothing_and_reports_nothing() {
+        let fixture = Fixture::new(&[reminder("a", "A")]);
+        fixture.storage.fail_writes.set(true);
+
+        assert!(matches!(
+            fixture.reminders.create(reminder("b", "B")),
+            Err(RepositoryError::Storage(StorageError::SaveFailed))
+        ));
+        assert!(fixture.reminders.update(reminder("a", "Edited")).is_err());
+        assert!(fixture.reminders.delete("a").is_err());
+        assert!(fixture.reminders.toggle("a").is_err());
+
+        fixture.assert_unchanged(&["a"]);
+        assert_eq!(fixture.reminders.all(), [reminder("a", "A")]);
+    }
+
+    #[test]
+    fn subscribers_get_every_change_until_they_unsubscribe() {
+        let fixture = Fixture::new(&[]);
+        let seen = Rc::new(Cell::new(0));
+        let counter = seen.clone();
+        let id = fixture.reminders.subscribe(move |_| counter.set(counter.get() + 1));
+
+        fixture.reminders.import(vec![reminder("a", "A"), reminder("b", "B")]).unwrap();
+        assert_eq!(seen.get(), 2);
+        fixture.reminders.unsubscribe(id);
+        fixture.reminders.delete("a").unwrap();
+        assert_eq!(seen.get(), 2);
+        // The first subscriber is still there
+        assert_eq!(fixture.events().len(), 3);
+    }
+
+    #[test]
+    fn subscribers_can_read_the_repository() {
+        let fixture = Rc::new(Fixture::new(&[]));
+        let titles = Rc::new(RefCell::new(Vec::new()));
+        let (repository, seen) = (Rc::downgrade(&fixture), titles.clone());
+        fixture.reminders.subscribe(move |change| {
+            let ReminderChange::Created(created) = change else { return };
+            let fixture = repository.upgrade().unwrap();
+            seen.borrow_mut().push(fixture.reminders.get_by_id(&created.id).unwrap().title);
+        });
+
+        fixture.reminders.create(reminder("a", "A")).unwrap();
+        assert_eq!(*titles.borrow(), ["A"]);
+    }
+}
@@ -0,0 +1,152 @@
+//! Typed access to the app's stored data
+//!
+//! `Repository` is the one place that knows which key holds what and how it
+//! is encoded; backends only store strings. Platform crates register a
+//! backend, and every build loads and saves through the same code.
+
+use super::{
+    decode_lenient, quarantine_key, run_migrations, ListChanges, Loaded, MemoryStorage, PlatformStorage,
+    StorageError, StorageHandle, REMINDERS_V2_KEY, SAVED_VIEWS_V1_KEY, TAGS_V1_KEY,
+};
+use crate::models::{Reminder, SavedView, Tag};
+use std::rc::Rc;
+
+/// Loads and saves reminders, tags and saved views over any backend
+#[derive(Clone)]
+pub struct Repository {
+    storage: StorageHandle,
+    /// Why stored data couldn't be brought up to date, if it couldn't
+    migration_error: Option<StorageError>,
+    /// What bringing stored data up to date had to leave out
+    migration_dropped: Vec<StorageError>,
+}
+
+impl Repository {
+    /// Repository over `storage`, upgrading the stored data first if needed
+    ///
+    /// If the upgrade fails, every load reports the failure and returns
+    /// nothing, so outdated data is never read as current.
+    pub fn new(storage: StorageHandle) -> Self {
+        let (migration_error, migration_dropped) = match run_migrations(&*storage) {
+            Ok(dropped) => (None, dropped),
+            Err(error) => (Some(error), Vec::new()),
+        };
+        Self {
+            storage,
+            migration_error,
+            migration_dropped,
+        }
+    }
+
+    /// Repository over a fresh in-memory backend
+    pub fn in_memory() -> Self {
+        Self::new(Rc::new(MemoryStorage::new()))
+    }
+
+    /// Values the upgrade of stored data left out when this repository was
+    /// created (see `StorageError::MigrationDropped`); the data loads anyway
+    pub fn migration_dropped(&self) -> &[StorageError] {
+        &self.migration_dropped
+    }
+
+    /// The backend, for raw key access
+    pub fn storage(&self) -> &dyn PlatformStorage {
+        &*self.storage
+    }
+
+    /// Load reminders
+    pub fn load_reminders(&self) -> Loaded<Reminder> {
+        self.load_list(REMINDERS_V2_KEY)
+    }
+
+    /// Save reminders
+    pub fn save_reminders(&self, reminders: &[Reminder]) -> Result<(), StorageError> {
+        self.save_list(REMINDERS_V2_KEY, reminders)
+    }
+
+    /// Save reminders when only the reminders with ids in `changed`, and the
+    /// removal of `removed`, differ from what is stored
+    pub fn save_reminder_changes(
+        &self,
+        reminders: &[Reminder],
+        changed: &[&str],
+        removed: &[String],
+    ) -> Result<(), StorageError> {
+        let json = serde_json::to_string(reminders).map_err(|_| StorageError::SerializationFailed)?;
+        let upserted = reminders
+            .iter()
+            .filter(|reminder| changed.contains(&reminder.id.as_str()))
+            .map(|reminder| {
+                serde_json::to_string(reminder)
+                    .map(|item| (reminder.id.clone(), item))
+                    .map_err(|_| StorageError::SerializationFailed)
+            })
+            .collect::<Result<_, _>>()?;
+        let changes = ListChanges {
+            upserted,
+            removed: removed.to_vec(),
+        };
+        self.storage().set_list(REMINDERS_V2_KEY, &json, &changes)
+    }
+
+    /// Load tags
+    pub fn load_tags(&self) -> Loaded<Tag> {
+        self.load_list(TAGS_V1_KEY)
+    }
+
+    /// Save tags
+    pub fn save_tags(&self, tags: &[Tag]) -> Result<(), StorageError> {
+        self.save_list(TAGS_V1_KEY, tags)
+    }
+
+    /// Load saved views
+    pub fn load_saved_views(&self) -> Loaded<SavedView> {
+        self.load_list(SAVED_VIEWS_V1_KEY)
+    }
+
+    /// Save saved views
+    pub fn save_saved_views(&self, views: &[SavedView]) -> Result<(), StorageError> {
+        self.save_list(SAVED_VIEWS_V1_KEY, views)
+    }
+
+    /// Load the JSON list stored under `key`
+    ///
+    /// Missing keys are empty lists. Damaged blobs are quarantined before the
+    /// readable items are returned.
+    fn load_list<T: serde::de::DeserializeOwned>(&self, key: &str) -> Loaded<T> {
+        if let Some(error) = &self.migration_error {
+            return Loaded::failed(error.clone());
+        }
+        let storage = self.storage();
+        let data = match storage.get(key) {
+            Ok(Some(data)) => data,
+            Ok(None) => return Loaded::ok(Vec::new()),
+            Err(error) => return Loaded::failed(error),
+        };
+
+        let (items, dropped) = decode_lenient(&data);
+        if dropped == Some(0) {
+            return Loaded::ok(items);
+        }
+
+        let quarantine_key = quarantine_key(key, &data);
+        if let Err(error) = storage.set(&quarantine_key, &data) {
+            // Without a copy, saving over the damaged blob would lose it for good
+            return Loaded::failed(error);
+        }
+        Loaded {
+            items,
+            error: Some(StorageError::Corrupted {
+                key: key.to_string(),
+                quarantine_key,
+                dropped,
+            }),
+        }
+    }
+
+    /// Encode `items` as JSON and store them under `key`
+    fn save_list<T: serde::Serialize>(&self, key: &str, items: &[T]) -> Result<(), StorageError> {
+        let json = serde_json::to_string(items).map_err(|_| StorageError::SerializationFailed)?;
+        self.storage().set(key, &json)
+    }
+}
@@ -0,0 +1,500 @@
+//! SQLite storage backend for native builds
+//!
+//! Reminders and tags are stored as one row per item, with the reminder↔tag
+//! relation in its own table and reminders indexed by due date. Other keys
+//! (schema version, saved views, ...) are kept as plain values.
+//!
+//! Each row keeps the item's full JSON, so fields the tables don't model
+//! survive a round trip.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use rusqlite::{params, Connection, OptionalExtension, Transaction};
+
+use super::{FileStorage, ListChanges, PlatformStorage, StorageError, REMINDERS_V2_KEY, TAGS_V1_KEY};
+
+/// File name of the database inside the data directory
+pub const DATABASE_FILE: &str = "remind_me.sqlite3";
+
+/// Version of the table layout, stored in `PRAGMA user_version`
+const DATABASE_VERSION: i64 = 1;
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS kv (
+        key TEXT PRIMARY KEY,
+        value TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS collections (
+        key TEXT PRIMARY KEY
+    );
+    CREATE TABLE IF NOT EXISTS reminders (
+        id TEXT PRIMARY KEY,
+        position INTEGER NOT NULL,
+        due_date TEXT,
+        data TEXT NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS reminders_due_date ON reminders (due_date);
+    CREATE TABLE IF NOT EXISTS tags (
+        id TEXT PRIMARY KEY,
+        position INTEGER NOT NULL,
+        data TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS reminder_tags (
+        reminder_id TEXT NOT NULL REFERENCES reminders (id) ON DELETE CASCADE,
+        tag_id TEXT NOT NULL,
+        PRIMARY KEY (reminder_id, tag_id)
+    );
+    CREATE INDEX IF NOT EXISTS reminder_tags_tag_id ON reminder_tags (tag_id);
+";
+
+/// Keys stored as rows instead of a single value
+#[derive(Clone, Copy)]
+enum Collection {
+    Reminders,
+    Tags,
+}
+
+impl Collection {
+    fn for_key(key: &str) -> Option<Self> {
+        match key {
+            REMINDERS_V2_KEY => Some(Self::Reminders),
+            TAGS_V1_KEY => Some(Self::Tags),
+            _ => None,
+        }
+    }
+
+    fn table(self) -> &'static str {
+        match self {
+            Self::Reminders => "reminders",
+            Self::Tags => "tags",
+        }
+    }
+}
+
+/// Storage backend using a SQLite database
+pub struct SqliteStorage {
+    conn: Connection,
+    path: PathBuf,
+}
+
+impl SqliteStorage {
+    /// Open (or create) the database in `dir`
+    ///
+    /// A new database imports the JSON files `FileStorage` left in `dir`
+    /// (e.g. `reminders_v2.json`, `tags_v1.json`); the files are kept.
+    pub fn open(dir: impl AsRef<Path>) -> Result<Self, StorageError> {
+        let dir = dir.as_ref();
+        std::fs::create_dir_all(dir).map_err(|_| StorageError::Unavailable)?;
+        let path = dir.join(DATABASE_FILE);
+        let conn = Connection::open(&path).map_err(|_| StorageError::Unavailable)?;
+        let mut storage = Self { conn, path };
+        storage.init(&FileStorage::new(dir))?;
+        Ok(storage)
+    }
+
+    /// Path of the database file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn init(&mut self, files: &FileStorage) -> Result<(), StorageError> {
+        self.conn
+            .pragma_update(None, "foreign_keys", true)
+            .map_err(|_| StorageError::Unavailable)?;
+        let version: i64 = self
+            .conn
+            .pragma_query_value(None, "user_version", |row| row.get(0))
+            .map_err(|_| StorageError::Unavailable)?;
+        if version > DATABASE_VERSION {
+            return Err(StorageError::UnsupportedVersion(version as u32));
+        }
+        if version == DATABASE_VERSION {
+            return Ok(());
+        }
+
+        let tx = self.conn.transaction().map_err(|_| StorageError::Unavailable)?;
+        tx.execute_batch(SCHEMA).map_err(|_| StorageError::Unavailable)?;
+        for key in files.keys()? {
+            if let Some(value) = files.get(&key)? {
+                write(&tx, &key, &value)?;
+            }
+        }
+        tx.pragma_update(None, "user_version", DATABASE_VERSION)
+            .map_err(|_| StorageError::SaveFailed)?;
+        tx.commit().map_err(|_| StorageError::SaveFailed)
+    }
+
+    fn in_transaction(&self, f: impl FnOnce(&Transaction) -> Result<(), StorageError>) -> Result<(), StorageError> {
+        // `Connection::transaction` needs `&mut self`; an unchecked one is fine
+        // here because the connection is never shared across threads
+        let tx = self.conn.unchecked_transaction().map_err(|_| StorageError::SaveFailed)?;
+        f(&tx)?;
+        tx.commit().map_err(|_| StorageError::SaveFailed)
+    }
+}
+
+/// Store `value` under `key`, splitting collections into rows when possible
+fn write(tx: &Transaction, key: &str, value: &str) -> Result<(), StorageError> {
+    let Some(collection) = Collection::for_key(key) else {
+        tx.execute(
+            "INSERT INTO kv (key, value) VALUES (?1, ?2)
+             ON CONFLICT (key) DO UPDATE SET value = excluded.value",
+            params![key, value],
+        )
+        .map_err(|_| StorageError::SaveFailed)?;
+        return Ok(());
+    };
+
+    delete(tx, key, collection)?;
+    let Some(items) = split_items(value) else {
+        // Not a list of items with unique ids: keep it whole so lenient
+        // loading can still recover what it can
+        tx.execute("INSERT INTO kv (key, value) VALUES (?1, ?2)", params![key, value])
+            .map_err(|_| StorageError::SaveFailed)?;
+        return Ok(());
+    };
+
+    tx.execute("INSERT INTO collections (key) VALUES (?1)", params![key])
+        .map_err(|_| StorageError::SaveFailed)?;
+    for (position, (id, item)) in items.iter().enumerate() {
+        put_item(tx, collection, id, item, Some(position as i64))?;
+    }
+    Ok(())
+}
+
+/// Insert or replace one item of a collection
+///
+/// New items go at `position`, or after the last item if it is `None`;
+/// replaced items keep their position.
+fn put_item(
+    tx: &Transaction,
+    collection: Collection,
+    id: &str,
+    item: &serde_json::Value,
+    position: Option<i64>,
+) -> Result<(), StorageError> {
+    let data = item.to_string();
+    match collection {
+        Collection::Reminders => {
+            let due_date = item.get("due_date").and_then(|due| due.as_str());
+            tx.execute(
+                "INSERT INTO reminders (id, position, due_date, data)
+                 VALUES (?1, COALESCE(?2, (SELECT COALESCE(MAX(position) + 1, 0) FROM reminders)), ?3, ?4)
+                 ON CONFLICT (id) DO UPDATE SET due_date = excluded.due_date, data = excluded.data",
+                params![id, position, due_date, data],
+            )
+            .and_then(|_| tx.execute("DELETE FROM reminder_tags WHERE reminder_id = ?1", params![id]))
+            .map_err(|_| StorageError::SaveFailed)?;
+
+            let tag_ids = item.get("tag_ids").and_then(|tags| tags.as_array());
+            for tag_id in tag_ids.into_iter().flatten().filter_map(|tag| tag.as_str()) {
+                tx.execute(
+                    "INSERT OR IGNORE INTO reminder_tags (reminder_id, tag_id) VALUES (?1, ?2)",
+                    params![id, tag_id],
+                )
+                .map_err(|_| StorageError::SaveFailed)?;
+            }
+        }
+        Collection::Tags => {
+            tx.execute(
+                "INSERT INTO tags (id, position, data)
+                 VALUES (?1, COALESCE(?2, (SELECT COALESCE(MAX(position) + 1, 0) FROM tags)), ?3)
+                 ON CONFLICT (id) DO UPDATE SET data = excluded.data",
+                params![id, position, data],
+            )
+            .map_err(|_| StorageError::SaveFailed)?;
+        }
+    }
+    Ok(())
+}
+
+/// Apply `changes` to a collection stored as rows
+fn write_changes(tx: &Transaction, collection: Collection, changes: &ListChanges) -> Result<(), StorageError> {
+    for id in &changes.removed {
+        tx.execute(&format!("DELETE FROM {} WHERE id = ?1", collection.table()), params![id])
+            .map_err(|_| StorageError::SaveFailed)?;
+    }
+    for (id, item) in &changes.upserted {
+        let item: serde_json::Value = serde_json::from_str(item).map_err(|_| StorageError::SerializationFailed)?;
+        put_item(tx, collection, id, &item, None)?;
+    }
+    Ok(())
+}
+
+/// Remove a collection's rows (the relation rows cascade with reminders)
+fn delete(tx: &Transaction, key: &str, collection: Collection) -> Result<(), StorageError> {
+    tx.execute(&format!("DELETE FROM {}", collection.table()), [])
+        .and_then(|_| tx.execute("DELETE FROM collections WHERE key = ?1", params![key]))
+        .and_then(|_| tx.execute("DELETE FROM kv WHERE key = ?1", params![key]))
+        .map(|_| ())
+        .map_err(|_| StorageError::SaveFailed)
+}
+
+/// Split a JSON array into `(id, item)` pairs; `None` unless every item has a unique string id
+fn split_items(value: &str) -> Option<Vec<(String, serde_json::Value)>> {
+    let items: Vec<serde_json::Value> = serde_json::from_str(value).ok()?;
+    let mut ids = HashSet::new();
+    items
+        .into_iter()
+        .map(|item| {
+            let id = item.get("id")?.as_str()?.to_string();
+            ids.insert(id.clone()).then_some((id, item))
+        })
+        .collect()
+}
+
+impl PlatformStorage for SqliteStorage {
+    fn get(&self, key: &str) -> Result<Option<String>, StorageError> {
+        let value: Option<String> = self
+            .conn
+            .query_row("SELECT value FROM kv WHERE key = ?1", params![key], |row| row.get(0))
+            .optional()
+            .map_err(|_| StorageError::ReadFailed)?;
+        let Some(collection) = Collection::for_key(key).filter(|_| value.is_none()) else {
+            return Ok(value);
+        };
+
+        let stored: bool = self
+            .conn
+            .query_row("SELECT EXISTS (SELECT 1 FROM collections WHERE key = ?1)", params![key], |row| row.get(0))
+            .map_err(|_| StorageError::ReadFailed)?;
+        if !stored {
+            return Ok(None);
+        }
+        let mut statement = self
+            .conn
+            .prepare(&format!("SELECT data FROM {} ORDER BY position", collection.table()))
+            .map_err(|_| StorageError::ReadFailed)?;
+        let items = statement
+            .query_map([], |row| row.get::<_, String>(0))
+            .and_then(|rows| rows.collect::<Result<Vec<_>, _>>())
+            .map_err(|_| StorageError::ReadFailed)?;
+        Ok(Some(format!("[{}]", items.join(","))))
+    }
+
+    fn set(&self, key: &str, value: &str) -> Result<(), StorageError> {
+        self.in_transaction(|tx| write(tx, key, value))
+    }
+
+    fn remove(&self, key: &str) -> Result<(), StorageError> {
+        self.in_transaction(|tx| match Collection::for_key(key) {
+            Some(collection) => delete(tx, key, collection),
+            None => tx
+                .execute("DELETE FROM kv WHERE key = ?1", params![key])
+                .map(|_| ())
+                .map_err(|_| StorageError::SaveFailed),
+        })
+    }
+
+    fn keys(&self) -> Result<Vec<String>, StorageError> {
+        let mut statement = self
+            .conn
+            .prepare("SELECT key FROM kv UNION SELECT key FROM collections ORDER BY key")
+            .map_err(|_| StorageError::ReadFailed)?;
+        statement
+            .query_map([], |row| row.get(0))
+            .and_then(|rows| rows.collect())
+            .map_err(|_| StorageError::ReadFailed)
+    }
+
+    fn set_list(&self, key: &str, value: &str, changes: &ListChanges) -> Result<(), StorageError> {
+        let Some(collection) = Collection::for_key(key) else {
+            return self.set(key, value);
+        };
+        self.in_transaction(|tx| {
+            let stored_as_rows: bool = tx
+                .query_row("SELECT EXISTS (SELECT 1 FROM collections WHERE key = ?1)", params![key], |row| row.get(0))
+                .map_err(|_| StorageError::ReadFailed)?;
+            if stored_as_rows {
+                write_changes(tx, collection, changes)
+            } else {
+                write(tx, key, value)
+            }
+        })
+    }
+
+    fn clear(&self) -> Result<(), StorageError> {
+        self.in_transaction(|tx| {
+            tx.execute_batch(
+                "DELETE FROM reminder_tags; DELETE FROM reminders; DELETE FROM tags;
+                 DELETE FROM collections; DELETE FROM kv;",
+            )
+            .map_err(|_| StorageError::SaveFailed)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Reminder;
+    use crate::test_support::{datetime, due_reminder, reminder, TempDir};
+
+    fn json(reminders: &[Reminder]) -> String {
+        serde_json::to_string(reminders).unwrap()
+    }
+
+    fn item(reminder: &Reminder) -> (String, String) {
+        (reminder.id.clone(), serde_json::to_string(reminder).unwrap())
+    }
+
+    fn ids(storage: &SqliteStorage, sql: &str) -> Vec<String> {
+        let mut statement = storage.conn.prepare(sql).unwrap();
+        let rows = statement.query_map([], |row| row.get(0)).unwrap();
+        rows.collect::<Result<_, _>>().unwrap()
+    }
+
+    /// The stored value, parsed (rows don't keep the key order of the JSON written)
+    fn stored(storage: &SqliteStorage, key: &str) -> serde_json::Value {
+        serde_json::from_str(&storage.get(key).unwrap().unwrap()).unwrap()
+    }
+
+    fn parsed(value: &str) -> serde_json::Value {
+        serde_json::from_str(value).unwrap()
+    }
+
+    fn stored_reminders(storage: &SqliteStorage) -> Vec<Reminder> {
+        serde_json::from_str(&storage.get(REMINDERS_V2_KEY).unwrap().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn opening_creates_the_schema_once() {
+        let dir = TempDir::new();
+        let storage = SqliteStorage::open(dir.path()).unwrap();
+        assert_eq!(storage.path(), dir.path().join(DATABASE_FILE));
+
+        let tables = ids(&storage, "SELECT name FROM sqlite_master WHERE type = 'table' ORDER BY name");
+        assert_eq!(tables, ["collections", "kv", "reminder_tags", "reminders", "tags"]);
+        let indexes = ids(
+            &storage,
+            "SELECT name FROM sqlite_master WHERE type = 'index' AND name NOT LIKE 'sqlite_%' ORDER BY name",
+        );
+        assert_eq!(indexes, ["reminder_tags_tag_id", "reminders_due_date"]);
+        let version: i64 = storage.conn.pragma_query_value(None, "user_version", |row| row.get(0)).unwrap();
+        assert_eq!(version, DATABASE_VERSION);
+
+        storage.set("saved_views", "[]").unwrap();
+        drop(storage);
+        let reopened = SqliteStorage::open(dir.path()).unwrap();
+        assert_eq!(reopened.get("saved_views").unwrap().as_deref(), Some("[]"));
+    }
+
+    #[test]
+    fn a_newer_database_is_refused() {
+        let dir = TempDir::new();
+        let storage = SqliteStorage::open(dir.path()).unwrap();
+        storage.conn.pragma_update(None, "user_version", DATABASE_VERSION + 1).unwrap();
+        drop(storage);
+
+        assert!(matches!(
+            SqliteStorage::open(dir.path()),
+            Err(StorageError::UnsupportedVersion(2))
+        ));
+    }
+
+    #[test]
+    fn json_files_in_the_dir_are_imported_on_first_run_only() {
+        let dir = TempDir::new();
+        let files = FileStorage::new(dir.path());
+        let mut tagged = reminder("a", "Tagged");
+        tagged.tag_ids = vec!["t1".to_string()];
+        let reminders = json(&[tagged, reminder("b", "Plain")]);
+        files.set(REMINDERS_V2_KEY, &reminders).unwrap();
+        files.set("schema_version", "2").unwrap();
+
+        let storage = SqliteStorage::open(dir.path()).unwrap();
+        assert_eq!(stored(&storage, REMINDERS_V2_KEY), parsed(&reminders));
+        assert_eq!(storage.get("schema_version").unwrap().as_deref(), Some("2"));
+        assert_eq!(ids(&storage, "SELECT id FROM reminders ORDER BY position"), ["a", "b"]);
+        assert_eq!(ids(&storage, "SELECT tag_id FROM reminder_tags WHERE reminder_id = 'a'"), ["t1"]);
+        // The files are kept
+        assert_eq!(files.get(REMINDERS_V2_KEY).unwrap().as_deref(), Some(reminders.as_str()));
+
+        // Later changes to the files are not imported again
+        drop(storage);
+        files.set("schema_version", "3").unwrap();
+        let reopened = SqliteStorage::open(dir.path()).unwrap();
+        assert_eq!(reopened.get("schema_version").unwrap().as_deref(), Some("2"));
+    }
+
+    #[test]
+    fn set_list_writes_only_the_changes() {
+        let dir = TempDir::new();
+        let storage = SqliteStorage::open(dir.path()).unwrap();
+        let (a, b, c) = (reminder("a", "A"), reminder("b", "B"), reminder("c", "C"));
+        storage.set(REMINDERS_V2_KEY, &json(&[a.clone(), b.clone(), c.clone()])).unwrap();
+        // Rows written outside the delta stay untouched
+        storage
+            .conn
+            .execute("UPDATE reminders SET data = json_set(data, '$.title', 'C (kept)') WHERE id = 'c'", [])
+            .unwrap();
+
+        let mut b_tagged = Reminder { title: "B edited".to_string(), ..b };
+        b_tagged.tag_ids = vec!["t1".to_string()];
+        let d = reminder("d", "D");
+        let changes = ListChanges {
+            upserted: vec![item(&b_tagged), item(&d)],
+            removed: vec![a.id.clone()],
+        };
+        let value = json(&[b_tagged.clone(), c, d.clone()]);
+        storage.set_list(REMINDERS_V2_KEY, &value, &changes).unwrap();
+
+        let titles: Vec<String> = stored_reminders(&storage).into_iter().map(|r| r.title).collect();
+        assert_eq!(titles, ["B edited", "C (kept)", "D"]);
+        assert_eq!(ids(&storage, "SELECT reminder_id FROM reminder_tags"), ["b"]);
+
+        // Removing a reminder removes its tag rows too
+        let changes = ListChanges { upserted: Vec::new(), removed: vec!["b".to_string()] };
+        storage.set_list(REMINDERS_V2_KEY, &json(&[d]), &changes).unwrap();
+        assert_eq!(ids(&storage, "SELECT id FROM reminders ORDER BY position"), ["c", "d"]);
+        assert!(ids(&storage, "SELECT reminder_id FROM reminder_tags").is_empty());
+    }
+
+    #[test]
+    fn set_list_writes_the_whole_value_when_not_stored_as_rows() {
+        let dir = TempDir::new();
+        let storage = SqliteStorage::open(dir.path()).unwrap();
+        let a = reminder("a", "A");
+        let value = json(std::slice::from_ref(&a));
+        let changes = ListChanges { upserted: vec![item(&a)], removed: Vec::new() };
+        storage.set_list(REMINDERS_V2_KEY, &value, &changes).unwrap();
+        assert_eq!(stored(&storage, REMINDERS_V2_KEY), parsed(&value));
+
+        // A value that isn't a list of items with unique ids is kept whole
+        let duplicates = json(&[reminder("x", "One"), reminder("x", "Two")]);
+        storage.set(REMINDERS_V2_KEY, &duplicates).unwrap();
+        assert_eq!(storage.get(REMINDERS_V2_KEY).unwrap().as_deref(), Some(duplicates.as_str()));
+        assert!(ids(&storage, "SELECT id FROM reminders").is_empty());
+
+        let b = reminder("b", "B");
+        let value = json(std::slice::from_ref(&b));
+        let changes = ListChanges { upserted: vec![item(&b)], removed: vec!["x".to_string()] };
+        storage.set_list(REMINDERS_V2_KEY, &value, &changes).unwrap();
+        assert_eq!(stored(&storage, REMINDERS_V2_KEY), parsed(&value));
+        assert_eq!(ids(&storage, "SELECT id FROM reminders"), ["b"]);
+    }
+
+    #[test]
+    fn due_dates_are_indexed_and_ordered() {
+        let dir = TempDir::new();
+        let storage = SqliteStorage::open(dir.path()).unwrap();
+        let reminders = [
+            due_reminder("march", datetime(2024, 3, 1, 9, 0)),
+            reminder("undated", "Undated"),
+            due_reminder("january", datetime(2024, 1, 20, 18, 30)),
+            due_reminder("february", datetime(2024, 2, 10, 7, 0)),
+        ];
+        storage.set(REMINDERS_V2_KEY, &json(&reminders)).unwrap();
+
+        let query = "SELECT id FROM reminders WHERE due_date < '2024-03-01' ORDER BY due_date";
+        assert_eq!(ids(&storage, query), ["january", "february"]);
+
+        let plan: Vec<String> = {
+            let mut statement = storage.conn.prepare(&format!("EXPLAIN QUERY PLAN {query}")).unwrap();
+            let rows = statement.query_map([], |row| row.get(3)).unwrap();
+            rows.collect::<Result<_, _>>().unwrap()
+        };
+        assert!(plan.iter().any(|step| step.contains("reminders_due_date")), "{plan:?}");
+    }
+}
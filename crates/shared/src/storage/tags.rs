@@ -0,0 +1,320 @@
+//! Tag operations that keep reminder references consistent
+//!
+//! Reminders refer to tags by id (`Reminder::tag_ids`). `TagRepository`
+//! changes tags together with those references. Reminders are updated
+//! first, through the `ReminderRepository`, so a failure part way through
+//! never leaves a reminder pointing at a tag that no longer exists.
+
+use std::collections::HashSet;
+
+use super::{Loaded, ReminderRepository, Repository, RepositoryError};
+use crate::models::{Reminder, Tag};
+
+/// What happens to a deleted tag's reminders
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TagDeletion {
+    /// The tag is removed from its reminders
+    RemoveFromReminders,
+    /// Its reminders get the tag with this id instead
+    ReassignTo(String),
+}
+
+/// References removed by `TagRepository::repair_references`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IntegrityReport {
+    /// Each repaired reminder's id, with the tag ids removed from it
+    pub repaired: Vec<(String, Vec<String>)>,
+}
+
+impl IntegrityReport {
+    /// Whether nothing needed repairing
+    pub fn is_empty(&self) -> bool {
+        self.repaired.is_empty()
+    }
+
+    /// Number of tag references removed
+    pub fn removed_count(&self) -> usize {
+        self.repaired.iter().map(|(_, tag_ids)| tag_ids.len()).sum()
+    }
+
+    /// Ids of the repaired reminders
+    pub fn reminder_ids(&self) -> impl Iterator<Item = &str> {
+        self.repaired.iter().map(|(id, _)| id.as_str())
+    }
+}
+
+/// Tags, with operations that update the reminders using them
+pub struct TagRepository {
+    repository: Repository,
+}
+
+impl TagRepository {
+    pub fn new(repository: Repository) -> Self {
+        Self { repository }
+    }
+
+    /// Load tags, upgrading stored data first if needed
+    pub fn load(&self) -> Loaded<Tag> {
+        self.repository.load_tags()
+    }
+
+    /// Add a tag, or replace the stored tag with the same id
+    pub fn save(&self, tag: Tag) -> Result<(), RepositoryError> {
+        let mut tags = self.load_all()?;
+        match tags.iter_mut().find(|t| t.id == tag.id) {
+            Some(existing) => *existing = tag,
+            None => tags.push(tag),
+        }
+        Ok(self.repository.save_tags(&tags)?)
+    }
+
+    /// Rename a tag; reminders refer to it by id, so they keep it
+    ///
+    /// Searches and imports match tags by name, so a name another tag has
+    /// (ignoring case and surrounding spaces) is refused.
+    pub fn rename(&self, id: &str, name: &str) -> Result<(), RepositoryError> {
+        let mut tags = self.load_all()?;
+        let wanted = name.trim().to_lowercase();
+        if tags.iter().any(|t| t.id != id && t.name.trim().to_lowercase() == wanted) {
+            return Err(RepositoryError::TagNameTaken(name.to_string()));
+        }
+        let tag = tags
+            .iter_mut()
+            .find(|t| t.id == id)
+            .ok_or_else(|| RepositoryError::TagNotFound(id.to_string()))?;
+        tag.name = name.to_string();
+        Ok(self.repository.save_tags(&tags)?)
+    }
+
+    /// Delete a tag, updating the reminders that use it; returns how many
+    /// reminders were updated
+    pub fn delete(
+        &self,
+        id: &str,
+        deletion: TagDeletion,
+        reminders: &ReminderRepository,
+    ) -> Result<usize, RepositoryError> {
+        let mut tags = self.load_all()?;
+        let index = tags
+            .iter()
+            .position(|t| t.id == id)
+            .ok_or_else(|| RepositoryError::TagNotFound(id.to_string()))?;
+        if let TagDeletion::ReassignTo(target) = &deletion {
+            if target == id || !tags.iter().any(|t| &t.id == target) {
+                return Err(RepositoryError::TagNotFound(target.clone()));
+            }
+        }
+
+        let updated: Vec<Reminder> = reminders
+            .all()
+            .into_iter()
+            .filter(|r| r.tag_ids.iter().any(|tag_id| tag_id == id))
+            .map(|mut r| {
+                r.tag_ids.retain(|tag_id| tag_id != id);
+                if let TagDeletion::ReassignTo(target) = &deletion {
+                    if !r.tag_ids.contains(target) {
+                        r.tag_ids.push(target.clone());
+                    }
+                }
+                r
+            })
+            .collect();
+        let count = updated.len();
+        if count > 0 {
+            reminders.bulk_update(updated)?;
+        }
+
+        tags.remove(index);
+        self.repository.save_tags(&tags)?;
+        Ok(count)
+    }
+
+    /// Merge tag `from` into tag `into`: `from`'s reminders get `into`, and
+    /// `from` is deleted; returns how many reminders were updated
+    pub fn merge(&self, from: &str, into: &str, reminders: &ReminderRepository) -> Result<usize, RepositoryError> {
+        self.delete(from, TagDeletion::ReassignTo(into.to_string()), reminders)
+    }
+
+    /// Remove tag references that don't name a stored tag, and repeated ones
+    ///
+    /// The repaired reminders keep their `updated_at` (see
+    /// `ReminderRepository::repair`).
+    pub fn repair_references(&self, reminders: &ReminderRepository) -> Result<IntegrityReport, RepositoryError> {
+        let tags = self.load_all()?;
+        let known: HashSet<&str> = tags.iter().map(|t| t.id.as_str()).collect();
+
+        let mut report = IntegrityReport::default();
+        let mut updated = Vec::new();
+        for mut reminder in reminders.all() {
+            let mut seen = HashSet::new();
+            let mut removed = Vec::new();
+            reminder.tag_ids.retain(|tag_id| {
+                let keep = known.contains(tag_id.as_str()) && seen.insert(tag_id.clone());
+                if !keep {
+                    removed.push(tag_id.clone());
+                }
+                keep
+            });
+            if !removed.is_empty() {
+                report.repaired.push((reminder.id.clone(), removed));
+                updated.push(reminder);
+            }
+        }
+
+        if !updated.is_empty() {
+            reminders.repair(updated)?;
+        }
+        Ok(report)
+    }
+
+    /// Every stored tag; fails rather than act on a partial list
+    fn load_all(&self) -> Result<Vec<Tag>, RepositoryError> {
+        let loaded = self.repository.load_tags();
+        match loaded.error {
+            Some(error) => Err(error.into()),
+            None => Ok(loaded.items),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use super::*;
+    use crate::datetime::Timestamp;
+    use crate::storage::ReminderChange;
+    use crate::test_support::{reminder, tag, JAN_15_MS};
+
+    fn tagged(id: &str, tag_ids: &[&str]) -> Reminder {
+        Reminder {
+            tag_ids: tag_ids.iter().map(|id| id.to_string()).collect(),
+            updated_at: Some(Timestamp::from_millis(JAN_15_MS)),
+            ..reminder(id, id)
+        }
+    }
+
+    /// Repository with tags `work`, `home` and `gym`, and `ReminderRepository` over it
+    fn fixture(reminders: &[Reminder]) -> (TagRepository, ReminderRepository) {
+        let repository = Repository::in_memory();
+        repository.save_tags(&[tag("work", "Work"), tag("home", "Home"), tag("gym", "Gym")]).unwrap();
+        repository.save_reminders(reminders).unwrap();
+        let (reminders, _) = ReminderRepository::open(repository.clone());
+        (TagRepository::new(repository), reminders)
+    }
+
+    fn tag_ids(tags: &TagRepository) -> Vec<String> {
+        tags.load().items.into_iter().map(|t| t.id).collect()
+    }
+
+    fn reminder_tags(reminders: &ReminderRepository, id: &str) -> Vec<String> {
+        reminders.get_by_id(id).unwrap().tag_ids
+    }
+
+    #[test]
+    fn rename_keeps_references_and_refuses_taken_names() {
+        let (tags, reminders) = fixture(&[tagged("a", &["work"])]);
+        tags.rename("work", "Office").unwrap();
+        assert_eq!(tags.load().items[0], tag("work", "Office"));
+        assert_eq!(reminder_tags(&reminders, "a"), ["work"]);
+
+        // Changing the case of its own name is fine
+        tags.rename("work", "OFFICE").unwrap();
+        for taken in ["Home", "home", " HOME "] {
+            assert!(matches!(tags.rename("work", taken), Err(RepositoryError::TagNameTaken(name)) if name == taken));
+        }
+        assert_eq!(tags.load().items[0], tag("work", "OFFICE"));
+        assert!(matches!(tags.rename("gone", "Gone"), Err(RepositoryError::TagNotFound(id)) if id == "gone"));
+    }
+
+    #[test]
+    fn delete_removes_or_reassigns_the_tag() {
+        let (tags, reminders) = fixture(&[
+            tagged("work", &["work"]),
+            tagged("both", &["work", "home"]),
+            tagged("home", &["home"]),
+            tagged("untagged", &[]),
+        ]);
+        assert_eq!(tags.delete("home", TagDeletion::RemoveFromReminders, &reminders).unwrap(), 2);
+        assert_eq!(tag_ids(&tags), ["work", "gym"]);
+        assert_eq!(reminder_tags(&reminders, "both"), ["work"]);
+        assert!(reminder_tags(&reminders, "home").is_empty());
+
+        // A reminder that already has the target doesn't get it twice
+        let (tags, reminders) = fixture(&[tagged("work", &["work"]), tagged("both", &["work", "home"])]);
+        assert_eq!(tags.delete("work", TagDeletion::ReassignTo("home".to_string()), &reminders).unwrap(), 2);
+        assert_eq!(tag_ids(&tags), ["home", "gym"]);
+        assert_eq!(reminder_tags(&reminders, "work"), ["home"]);
+        assert_eq!(reminder_tags(&reminders, "both"), ["home"]);
+        assert!(reminders.get_by_id("work").unwrap().updated_at > Some(Timestamp::from_millis(JAN_15_MS)));
+
+        let missing = TagDeletion::ReassignTo("gone".to_string());
+        assert!(matches!(tags.delete("home", missing, &reminders), Err(RepositoryError::TagNotFound(id)) if id == "gone"));
+        assert!(matches!(
+            tags.delete("gone", TagDeletion::RemoveFromReminders, &reminders),
+            Err(RepositoryError::TagNotFound(id)) if id == "gone"
+        ));
+        assert_eq!(tag_ids(&tags), ["home", "gym"]);
+    }
+
+    #[test]
+    fn merge_moves_reminders_to_the_other_tag() {
+        let (tags, reminders) = fixture(&[
+            tagged("gym", &["gym"]),
+            tagged("both", &["home", "gym"]),
+            tagged("work", &["work"]),
+        ]);
+        // Merging a tag into itself would delete it; it is refused and nothing changes
+        assert!(tags.merge("gym", "gym", &reminders).is_err());
+        assert_eq!(tag_ids(&tags), ["work", "home", "gym"]);
+        assert_eq!(reminder_tags(&reminders, "gym"), ["gym"]);
+
+        assert_eq!(tags.merge("gym", "home", &reminders).unwrap(), 2);
+        assert_eq!(tag_ids(&tags), ["work", "home"]);
+        assert_eq!(reminder_tags(&reminders, "gym"), ["home"]);
+        assert_eq!(reminder_tags(&reminders, "both"), ["home"]);
+        assert_eq!(reminder_tags(&reminders, "work"), ["work"]);
+    }
+
+    #[test]
+    fn repair_removes_dangling_and_repeated_references_without_touching_updated_at() {
+        let repository = Repository::in_memory();
+        repository.save_tags(&[tag("work", "Work")]).unwrap();
+        repository
+            .save_reminders(&[
+                tagged("clean", &["work"]),
+                tagged("dangling", &["gone", "work", "old"]),
+                tagged("repeated", &["work", "work"]),
+            ])
+            .unwrap();
+        let (reminders, _) = ReminderRepository::open(repository.clone());
+        let events = Rc::new(RefCell::new(Vec::new()));
+        let recorded = events.clone();
+        reminders.subscribe(move |change| recorded.borrow_mut().push(change.clone()));
+
+        let report = TagRepository::new(repository.clone()).repair_references(&reminders).unwrap();
+        assert_eq!(
+            report.repaired,
+            [
+                ("dangling".to_string(), vec!["gone".to_string(), "old".to_string()]),
+                ("repeated".to_string(), vec!["work".to_string()]),
+            ]
+        );
+        assert_eq!(report.removed_count(), 3);
+        assert_eq!(report.reminder_ids().collect::<Vec<_>>(), ["dangling", "repeated"]);
+
+        let stored = repository.load_reminders().items;
+        assert_eq!(stored, [tagged("clean", &["work"]), tagged("dangling", &["work"]), tagged("repeated", &["work"])]);
+        assert_eq!(
+            *events.borrow(),
+            [
+                ReminderChange::Updated(tagged("dangling", &["work"])),
+                ReminderChange::Updated(tagged("repeated", &["work"])),
+            ]
+        );
+
+        // Nothing left to repair
+        assert!(TagRepository::new(repository).repair_references(&reminders).unwrap().is_empty());
+    }
+}
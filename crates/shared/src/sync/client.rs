@@ -0,0 +1,458 @@
+//! Client for the sync server's API
+
+use std::collections::BTreeMap;
+use std::rc::Rc;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use super::merge::{item_hash, plan, MergeAction, SyncState, Syncable};
+use super::{encode_path_segment, ErrorBody, Method, SyncError, SyncRequest, SyncTransport, API_PREFIX};
+use crate::models::{Reminder, Tag};
+use crate::storage::{Loaded, Repository, StorageError};
+
+const REMINDERS: &str = "reminders";
+const TAGS: &str = "tags";
+
+/// A kind of synced item: its endpoint, how it is saved locally, and its
+/// hashes in `SyncState`
+struct Collection<T> {
+    path: &'static str,
+    save: fn(&Repository, &[T]) -> Result<(), StorageError>,
+    base: fn(&mut SyncState) -> &mut BTreeMap<String, u64>,
+}
+
+const REMINDER_COLLECTION: Collection<Reminder> = Collection {
+    path: REMINDERS,
+    save: Repository::save_reminders,
+    base: |state| &mut state.reminders,
+};
+
+const TAG_COLLECTION: Collection<Tag> = Collection {
+    path: TAGS,
+    save: Repository::save_tags,
+    base: |state| &mut state.tags,
+};
+
+/// What a running sync writes to
+struct SyncRun<'a> {
+    repository: &'a Repository,
+    state: &'a mut SyncState,
+    report: &'a mut SyncReport,
+}
+
+/// What `SyncClient::sync` did
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SyncReport {
+    /// Items created or updated on the server
+    pub sent: usize,
+    /// Items created or updated locally
+    pub received: usize,
+    /// Items deleted on the server
+    pub deleted_remotely: usize,
+    /// Items deleted locally
+    pub deleted_locally: usize,
+    /// Items both sides changed (see `merge` for which copy was kept)
+    pub conflicts: usize,
+}
+
+impl SyncReport {
+    /// Whether both sides were already in sync
+    pub fn is_empty(&self) -> bool {
+        *self == SyncReport::default()
+    }
+}
+
+/// Calls the sync server's API as one user
+#[derive(Clone)]
+pub struct SyncClient {
+    base_url: String,
+    token: String,
+    transport: Rc<dyn SyncTransport>,
+}
+
+impl SyncClient {
+    /// Client for the server at `base_url` (e.g. `http://127.0.0.1:8080`),
+    /// authenticating with `token`
+    pub fn new(base_url: &str, token: &str, transport: Rc<dyn SyncTransport>) -> Self {
+        Self {
+            base_url: base_url.trim().trim_end_matches('/').to_string(),
+            token: token.trim().to_string(),
+            transport,
+        }
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    pub async fn reminders(&self) -> Result<Vec<Reminder>, SyncError> {
+        self.list(REMINDERS).await
+    }
+
+    pub async fn reminder(&self, id: &str) -> Result<Reminder, SyncError> {
+        self.get(REMINDERS, id).await
+    }
+
+    /// Create a reminder, returning it as stored
+    pub async fn create_reminder(&self, reminder: &Reminder) -> Result<Reminder, SyncError> {
+        self.create(REMINDERS, reminder).await
+    }
+
+    /// Replace a reminder, returning it as stored (with the server's `updated_at`)
+    pub async fn update_reminder(&self, reminder: &Reminder) -> Result<Reminder, SyncError> {
+        self.update(REMINDERS, reminder).await
+    }
+
+    pub async fn delete_reminder(&self, id: &str) -> Result<(), SyncError> {
+        self.delete(REMINDERS, id).await
+    }
+
+    pub async fn tags(&self) -> Result<Vec<Tag>, SyncError> {
+        self.list(TAGS).await
+    }
+
+    pub async fn tag(&self, id: &str) -> Result<Tag, SyncError> {
+        self.get(TAGS, id).await
+    }
+
+    pub async fn create_tag(&self, tag: &Tag) -> Result<Tag, SyncError> {
+        self.create(TAGS, tag).await
+    }
+
+    pub async fn update_tag(&self, tag: &Tag) -> Result<Tag, SyncError> {
+        self.update(TAGS, tag).await
+    }
+
+    /// Delete a tag; the server also removes it from its reminders
+    pub async fn delete_tag(&self, id: &str) -> Result<(), SyncError> {
+        self.delete(TAGS, id).await
+    }
+
+    /// Bring the reminders and tags in `repository` and on the server together
+    ///
+    /// Remote changes are written to `repository` directly, so a
+    /// `ReminderRepository` over the same store must be reloaded afterwards.
+    /// Nothing is synced while stored data can't be read in full, as the
+    /// unreadable items would look deleted.
+    ///
+    /// The local items and the `SyncState` are saved after every step, so a
+    /// sync that fails part way (e.g. when the connection drops) leaves them
+    /// agreeing with the server about what was done, and the next sync picks
+    /// up from there.
+    pub async fn sync(&self, repository: &Repository) -> Result<SyncReport, SyncError> {
+        let local_tags = loaded(repository.load_tags())?;
+        let local_reminders = loaded(repository.load_reminders())?;
+        let mut state = SyncState::load(repository.storage(), &self.base_url)?;
+        let remote_tags = self.tags().await?;
+        let remote_reminders = self.reminders().await?;
+
+        let tag_plan = plan(&local_tags, &remote_tags, &state.tags);
+        let reminder_plan = plan(&local_reminders, &remote_reminders, &state.reminders);
+        let mut report = SyncReport {
+            conflicts: tag_plan.conflicts + reminder_plan.conflicts,
+            ..SyncReport::default()
+        };
+
+        // Tags are created before the reminders using them, and deleted after
+        let (tag_deletions, tag_actions): (Vec<_>, Vec<_>) = tag_plan
+            .actions
+            .into_iter()
+            .partition(|action| matches!(action, MergeAction::DeleteRemote(_)));
+        let mut sync = SyncRun {
+            repository,
+            state: &mut state,
+            report: &mut report,
+        };
+        let tags = self.apply(&mut sync, &TAG_COLLECTION, local_tags, tag_actions).await?;
+        let reminders = self
+            .apply(&mut sync, &REMINDER_COLLECTION, local_reminders, reminder_plan.actions)
+            .await?;
+        let tags = self.apply(&mut sync, &TAG_COLLECTION, tags, tag_deletions).await?;
+
+        // Items that were already the same on both sides join the base too
+        state.tags = tags.iter().map(|tag| (tag.id.clone(), item_hash(tag))).collect();
+        state.reminders = reminders.iter().map(|r| (r.id.clone(), item_hash(r))).collect();
+        state.save(repository.storage())?;
+        Ok(report)
+    }
+
+    /// Carry out `actions` on the server and on `items`, returning the new local items
+    ///
+    /// After each action the items and the state are saved, items first: if
+    /// saving the state fails, the next sync finds both sides equal.
+    async fn apply<T: Syncable + DeserializeOwned>(
+        &self,
+        sync: &mut SyncRun<'_>,
+        collection: &Collection<T>,
+        mut items: Vec<T>,
+        actions: Vec<MergeAction<T>>,
+    ) -> Result<Vec<T>, SyncError> {
+        for action in actions {
+            let id = match &action {
+                MergeAction::Push { item, .. } | MergeAction::Pull(item) => item.id().to_string(),
+                MergeAction::DeleteRemote(id) | MergeAction::DeleteLocal(id) => id.clone(),
+            };
+            let agreed = match action {
+                MergeAction::Push { item, on_server } => {
+                    // Retry the other way if the server changed since it was listed
+                    let stored = if on_server {
+                        match self.update(collection.path, &item).await {
+                            Err(SyncError::Rejected { status: 404, .. }) => self.create(collection.path, &item).await,
+                            result => result,
+                        }
+                    } else {
+                        match self.create(collection.path, &item).await {
+                            Err(SyncError::Rejected { status: 409, .. }) => self.update(collection.path, &item).await,
+                            result => result,
+                        }
+                    }?;
+                    let hash = item_hash(&stored);
+                    put(&mut items, stored);
+                    (collection.save)(sync.repository, &items)?;
+                    sync.report.sent += 1;
+                    Some(hash)
+                }
+                MergeAction::Pull(item) => {
+                    let hash = item_hash(&item);
+                    put(&mut items, item);
+                    (collection.save)(sync.repository, &items)?;
+                    sync.report.received += 1;
+                    Some(hash)
+                }
+                MergeAction::DeleteRemote(id) => {
+                    match self.delete(collection.path, &id).await {
+                        Ok(()) | Err(SyncError::Rejected { status: 404, .. }) => {}
+                        Err(error) => return Err(error),
+                    }
+                    sync.report.deleted_remotely += 1;
+                    None
+                }
+                MergeAction::DeleteLocal(id) => {
+                    items.retain(|item| item.id() != id);
+                    (collection.save)(sync.repository, &items)?;
+                    sync.report.deleted_locally += 1;
+                    None
+                }
+            };
+            let base = (collection.base)(sync.state);
+            match agreed {
+                Some(hash) => base.insert(id, hash),
+                None => base.remove(&id),
+            };
+            sync.state.save(sync.repository.storage())?;
+        }
+        Ok(items)
+    }
+
+    async fn list<T: DeserializeOwned>(&self, collection: &str) -> Result<Vec<T>, SyncError> {
+        let body = self.send(Method::Get, collection, None).await?;
+        decode(&body)
+    }
+
+    async fn get<T: DeserializeOwned>(&self, collection: &str, id: &str) -> Result<T, SyncError> {
+        let path = format!("{}/{}", collection, encode_path_segment(id));
+        let body = self.send(Method::Get, &path, None).await?;
+        decode(&body)
+    }
+
+    async fn create<T: Syncable + DeserializeOwned>(&self, collection: &str, item: &T) -> Result<T, SyncError> {
+        let body = self.send(Method::Post, collection, Some(encode(item)?)).await?;
+        decode(&body)
+    }
+
+    async fn update<T: Syncable + DeserializeOwned>(&self, collection: &str, item: &T) -> Result<T, SyncError> {
+        let path = format!("{}/{}", collection, encode_path_segment(item.id()));
+        let body = self.send(Method::Put, &path, Some(encode(item)?)).await?;
+        decode(&body)
+    }
+
+    async fn delete(&self, collection: &str, id: &str) -> Result<(), SyncError> {
+        let path = format!("{}/{}", collection, encode_path_segment(id));
+        self.send(Method::Delete, &path, None).await.map(|_| ())
+    }
+
+    /// Send a request to `API_PREFIX/path`, returning the body of a successful answer
+    async fn send(&self, method: Method, path: &str, body: Option<String>) -> Result<String, SyncError> {
+        let request = SyncRequest {
+            method,
+            url: format!("{}{}/{}", self.base_url, API_PREFIX, path),
+            token: self.token.clone(),
+            body,
+        };
+        let response = self.transport.send(request).await?;
+        match response.status {
+            200..=299 => Ok(response.body),
+            401 => Err(SyncError::Unauthorized),
+            status => {
+                let message = serde_json::from_str::<ErrorBody>(&response.body)
+                    .map(|body| body.error)
+                    .unwrap_or(response.body);
+                Err(SyncError::Rejected { status, message })
+            }
+        }
+    }
+}
+
+/// Replace the item with the same id as `item`, or add it
+fn put<T: Syncable>(items: &mut Vec<T>, item: T) {
+    match items.iter_mut().find(|existing| existing.id() == item.id()) {
+        Some(existing) => *existing = item,
+        None => items.push(item),
+    }
+}
+
+fn loaded<T>(loaded: Loaded<T>) -> Result<Vec<T>, SyncError> {
+    match loaded.error {
+        Some(error) => Err(SyncError::Storage(error)),
+        None => Ok(loaded.items),
+    }
+}
+
+fn encode<T: Serialize>(item: &T) -> Result<String, SyncError> {
+    serde_json::to_string(item).map_err(|_| SyncError::Storage(StorageError::SerializationFailed))
+}
+
+fn decode<T: DeserializeOwned>(body: &str) -> Result<T, SyncError> {
+    serde_json::from_str(body).map_err(|e| SyncError::InvalidResponse(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::{Cell, RefCell};
+    use std::future::Future;
+    use std::task::{Context, Poll, Waker};
+
+    use super::*;
+    use crate::storage::MemoryStorage;
+    use crate::sync::{Method, SyncFuture, SyncResponse};
+    use crate::test_support::{reminder, tag};
+
+    const SERVER: &str = "http://sync.test";
+
+    /// In-memory stand-in for the sync server, which can drop the connection
+    #[derive(Default)]
+    struct FakeServer {
+        reminders: RefCell<Vec<Reminder>>,
+        tags: RefCell<Vec<Tag>>,
+        /// Requests answered before the connection drops; `None` for all of them
+        answers_left: Cell<Option<usize>>,
+    }
+
+    impl FakeServer {
+        fn answer(&self, request: &SyncRequest) -> Result<SyncResponse, SyncError> {
+            match self.answers_left.get() {
+                Some(0) => return Err(SyncError::Network("connection dropped".to_string())),
+                Some(left) => self.answers_left.set(Some(left - 1)),
+                None => {}
+            }
+            let path = request.url.strip_prefix(&format!("{}{}/", SERVER, API_PREFIX)).unwrap();
+            let (collection, id) = match path.split_once('/') {
+                Some((collection, id)) => (collection, Some(id)),
+                None => (path, None),
+            };
+            let body = request.body.as_deref();
+            Ok(match collection {
+                REMINDERS => handle(&mut self.reminders.borrow_mut(), request.method, id, body),
+                _ => handle(&mut self.tags.borrow_mut(), request.method, id, body),
+            })
+        }
+    }
+
+    impl SyncTransport for FakeServer {
+        fn send(&self, request: SyncRequest) -> SyncFuture<'_> {
+            Box::pin(std::future::ready(self.answer(&request)))
+        }
+    }
+
+    fn handle<T: Syncable + DeserializeOwned>(
+        items: &mut Vec<T>,
+        method: Method,
+        id: Option<&str>,
+        body: Option<&str>,
+    ) -> SyncResponse {
+        let respond = |status, body: String| SyncResponse { status, body };
+        let position = |items: &[T], id: &str| items.iter().position(|item| item.id() == id);
+        match (method, id) {
+            (Method::Get, None) => respond(200, serde_json::to_string(items).unwrap()),
+            (Method::Post, None) => {
+                let item: T = serde_json::from_str(body.unwrap()).unwrap();
+                if position(items, item.id()).is_some() {
+                    return respond(409, String::new());
+                }
+                items.push(item.clone());
+                respond(201, serde_json::to_string(&item).unwrap())
+            }
+            (Method::Put, Some(id)) => match position(items, id) {
+                Some(index) => {
+                    items[index] = serde_json::from_str(body.unwrap()).unwrap();
+                    respond(200, body.unwrap().to_string())
+                }
+                None => respond(404, String::new()),
+            },
+            (Method::Delete, Some(id)) => match position(items, id) {
+                Some(index) => {
+                    items.remove(index);
+                    respond(204, String::new())
+                }
+                None => respond(404, String::new()),
+            },
+            _ => respond(405, String::new()),
+        }
+    }
+
+    /// Run a future whose transport answers at once
+    fn block_on<F: Future>(future: F) -> F::Output {
+        let mut future = std::pin::pin!(future);
+        match future.as_mut().poll(&mut Context::from_waker(Waker::noop())) {
+            Poll::Ready(output) => output,
+            Poll::Pending => panic!("the fake server answers at once"),
+        }
+    }
+
+    fn ids<T: Syncable>(items: &[T]) -> Vec<&str> {
+        items.iter().map(Syncable::id).collect()
+    }
+
+    #[test]
+    fn an_interrupted_sync_resumes_where_it_stopped() {
+        let server = Rc::new(FakeServer::default());
+        let client = SyncClient::new(SERVER, "token", server.clone());
+        let repository = Repository::new(Rc::new(MemoryStorage::new()));
+        repository.save_tags(&[tag("work", "Work")]).unwrap();
+        repository
+            .save_reminders(&[reminder("a", "A"), reminder("b", "B"), reminder("c", "C")])
+            .unwrap();
+        block_on(client.sync(&repository)).unwrap();
+
+        // Both sides change: "a" deleted and "b" edited here, "c" deleted and "d" added there
+        let edited = reminder("b", "B, edited");
+        repository.save_reminders(&[edited.clone(), reminder("c", "C")]).unwrap();
+        server.reminders.borrow_mut().retain(|r| r.id != "c");
+        server.reminders.borrow_mut().push(reminder("d", "D"));
+
+        // Listing both, then `PUT b`, answer; `DELETE a` doesn't
+        server.answers_left.set(Some(3));
+        assert!(matches!(block_on(client.sync(&repository)), Err(SyncError::Network(_))));
+        let state = SyncState::load(repository.storage(), SERVER).unwrap();
+        assert_eq!(ids(&repository.load_reminders().items), ["b"]);
+        assert_eq!(state.reminders.keys().collect::<Vec<_>>(), ["a", "b"]);
+        assert_eq!(state.reminders["b"], item_hash(&edited));
+
+        // Only what is left is done: the deletion on the server and taking "d"
+        server.answers_left.set(None);
+        let report = block_on(client.sync(&repository)).unwrap();
+        assert_eq!(
+            report,
+            SyncReport {
+                received: 1,
+                deleted_remotely: 1,
+                ..SyncReport::default()
+            }
+        );
+        assert_eq!(repository.load_reminders().items, *server.reminders.borrow());
+        assert_eq!(ids(&repository.load_reminders().items), ["b", "d"]);
+        assert_eq!(repository.load_tags().items, *server.tags.borrow());
+        assert!(block_on(client.sync(&repository)).unwrap().is_empty());
+    }
+}
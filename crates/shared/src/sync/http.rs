@@ -0,0 +1,58 @@
+//! Blocking HTTP transport for native builds
+
+use std::time::Duration;
+
+use super::{SyncError, SyncFuture, SyncRequest, SyncResponse, SyncTransport};
+
+/// Timeout for connecting to the server and for each answer
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+/// `SyncTransport` over HTTP(S), for desktop and mobile builds
+///
+/// Requests block the calling thread until the server answers, so the
+/// futures it returns are always ready.
+pub struct HttpTransport {
+    agent: ureq::Agent,
+}
+
+impl HttpTransport {
+    pub fn new() -> Self {
+        Self {
+            agent: ureq::AgentBuilder::new().timeout_connect(TIMEOUT).timeout_read(TIMEOUT).build(),
+        }
+    }
+
+    fn send_blocking(&self, request: &SyncRequest) -> Result<SyncResponse, SyncError> {
+        let call = self
+            .agent
+            .request(request.method.as_str(), &request.url)
+            .set("Authorization", &format!("Bearer {}", request.token))
+            .set("Accept", "application/json");
+        let result = match &request.body {
+            Some(body) => call.set("Content-Type", "application/json").send_string(body),
+            None => call.call(),
+        };
+        let response = match result {
+            Ok(response) => response,
+            Err(ureq::Error::Status(_, response)) => response,
+            Err(ureq::Error::Transport(error)) => return Err(SyncError::Network(error.to_string())),
+        };
+        let status = response.status();
+        let body = response
+            .into_string()
+            .map_err(|e| SyncError::Network(e.to_string()))?;
+        Ok(SyncResponse { status, body })
+    }
+}
+
+impl Default for HttpTransport {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SyncTransport for HttpTransport {
+    fn send(&self, request: SyncRequest) -> SyncFuture<'_> {
+        Box::pin(std::future::ready(self.send_blocking(&request)))
+    }
+}
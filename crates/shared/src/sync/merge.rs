@@ -0,0 +1,173 @@
+//! Three-way merge of the local store with the server's copy
+//!
+//! `SyncState` remembers a hash of every item as it was after the last sync
+//! (the base). Comparing the local and the server's copy of an item with its
+//! base tells which side changed it:
+//!
+//! - If only one side created, edited or deleted the item, that change wins.
+//! - An edit wins over a deletion on the other side.
+//! - If both sides changed it differently (or there is no base yet, as on
+//!   the first sync), it is a conflict: the reminder changed last wins
+//!   (`updated_at`, or `created_at` if never changed), ties going to the
+//!   server; for tags the server's copy wins.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::models::{Reminder, Tag};
+use crate::storage::{fnv1a, PlatformStorage, StorageError};
+
+/// Key holding the `SyncState`
+pub const SYNC_STATE_KEY: &str = "sync_state_v1";
+
+/// What the local store and the server agreed on after the last sync
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SyncState {
+    /// Server the hashes belong to; syncing with another server starts over
+    pub server: String,
+    /// Hash of each reminder, by id
+    pub reminders: BTreeMap<String, u64>,
+    /// Hash of each tag, by id
+    pub tags: BTreeMap<String, u64>,
+}
+
+impl SyncState {
+    /// The state stored for `server`, or an empty one if there is none
+    ///
+    /// An unreadable state is treated as missing: with no base, nothing is
+    /// taken for a deletion, so the next sync only adds and updates.
+    pub fn load(storage: &dyn PlatformStorage, server: &str) -> Result<Self, StorageError> {
+        let state = storage
+            .get(SYNC_STATE_KEY)?
+            .and_then(|json| serde_json::from_str::<SyncState>(&json).ok())
+            .filter(|state| state.server == server);
+        Ok(state.unwrap_or_else(|| SyncState {
+            server: server.to_string(),
+            ..SyncState::default()
+        }))
+    }
+
+    pub fn save(&self, storage: &dyn PlatformStorage) -> Result<(), StorageError> {
+        let json = serde_json::to_string(self).map_err(|_| StorageError::SerializationFailed)?;
+        storage.set(SYNC_STATE_KEY, &json)
+    }
+}
+
+/// An item kept in sync with the server
+pub trait Syncable: Clone + Serialize {
+    fn id(&self) -> &str;
+
+    /// Whether `local` wins a conflict with `remote`
+    fn prefer_local(local: &Self, remote: &Self) -> bool;
+}
+
+impl Syncable for Reminder {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn prefer_local(local: &Self, remote: &Self) -> bool {
+        let changed = |r: &Reminder| r.updated_at.unwrap_or(r.created_at);
+        changed(local) > changed(remote)
+    }
+}
+
+impl Syncable for Tag {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn prefer_local(_local: &Self, _remote: &Self) -> bool {
+        false
+    }
+}
+
+/// Hash of an item as stored in `SyncState`
+pub fn item_hash<T: Serialize>(item: &T) -> u64 {
+    fnv1a(&serde_json::to_string(item).unwrap_or_default())
+}
+
+/// One step of a sync
+#[derive(Debug, Clone, PartialEq)]
+pub enum MergeAction<T> {
+    /// Send the local copy to the server, updating it if the server has the
+    /// item and creating it otherwise
+    Push { item: T, on_server: bool },
+    /// Take the server's copy
+    Pull(T),
+    /// Delete the item on the server
+    DeleteRemote(String),
+    /// Delete the item locally
+    DeleteLocal(String),
+}
+
+/// What a sync of one kind of item has to do
+#[derive(Debug, Clone, PartialEq)]
+pub struct MergePlan<T> {
+    /// Steps, in local order followed by items only the server has
+    pub actions: Vec<MergeAction<T>>,
+    /// How many items both sides changed
+    pub conflicts: usize,
+}
+
+/// Work out how to bring `local` and `remote` together, given the hashes in
+/// `base` from the last sync
+pub fn plan<T: Syncable>(local: &[T], remote: &[T], base: &BTreeMap<String, u64>) -> MergePlan<T> {
+    let remote_by_id: BTreeMap<&str, &T> = remote.iter().map(|item| (item.id(), item)).collect();
+    let mut actions = Vec::new();
+    let mut conflicts = 0;
+
+    for item in local {
+        let base_hash = base.get(item.id()).copied();
+        let local_hash = item_hash(item);
+        let local_changed = base_hash != Some(local_hash);
+        match remote_by_id.get(item.id()) {
+            Some(remote_item) => {
+                let remote_hash = item_hash(*remote_item);
+                if local_hash == remote_hash {
+                    continue;
+                }
+                let remote_changed = base_hash != Some(remote_hash);
+                let push = match (local_changed, remote_changed) {
+                    (true, false) => true,
+                    (false, _) => false,
+                    (true, true) => {
+                        conflicts += 1;
+                        T::prefer_local(item, remote_item)
+                    }
+                };
+                actions.push(if push {
+                    MergeAction::Push {
+                        item: item.clone(),
+                        on_server: true,
+                    }
+                } else {
+                    MergeAction::Pull((*remote_item).clone())
+                });
+            }
+            // Deleted on the server since the last sync
+            None if base_hash.is_some() && !local_changed => {
+                actions.push(MergeAction::DeleteLocal(item.id().to_string()));
+            }
+            None => actions.push(MergeAction::Push {
+                item: item.clone(),
+                on_server: false,
+            }),
+        }
+    }
+
+    let local_ids: BTreeSet<&str> = local.iter().map(Syncable::id).collect();
+    for item in remote.iter().filter(|item| !local_ids.contains(&item.id())) {
+        match base.get(item.id()) {
+            // Deleted locally since the last sync, and not changed on the server
+            Some(base_hash) if *base_hash == item_hash(item) => {
+                actions.push(MergeAction::DeleteRemote(item.id().to_string()));
+            }
+            _ => actions.push(MergeAction::Pull(item.clone())),
+        }
+    }
+
+    MergePlan { actions, conflicts }
+}
+
@@ -0,0 +1,143 @@
+//! Syncing the local store with a self-hosted sync server
+//!
+//! The sync server (`sync-server`, in `remind-me-server`) keeps each user's
+//! reminders and tags, behind plain CRUD endpoints under `API_PREFIX`:
+//!
+//! - `GET /reminders`, `POST /reminders`
+//! - `GET /reminders/{id}`, `PUT /reminders/{id}`, `DELETE /reminders/{id}`
+//! - the same for `/tags` (deleting a tag removes it from its reminders)
+//!
+//! Every request carries `Authorization: Bearer <token>`; the token picks
+//! the user. Bodies are the JSON of `Reminder` and `Tag`, and failures
+//! answer with an `ErrorBody`.
+//!
+//! `SyncClient` calls these endpoints through a `SyncTransport`, so each
+//! platform brings its own HTTP stack: `HttpTransport` on native builds,
+//! `FetchTransport` in `remind_me_web`. `SyncClient::sync` merges the local
+//! store with the server's copy; see `merge` for how conflicts are settled.
+
+mod client;
+#[cfg(not(target_arch = "wasm32"))]
+mod http;
+pub mod merge;
+
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+
+use serde::{Deserialize, Serialize};
+
+use crate::storage::StorageError;
+
+pub use client::{SyncClient, SyncReport};
+#[cfg(not(target_arch = "wasm32"))]
+pub use http::HttpTransport;
+pub use merge::{SyncState, SYNC_STATE_KEY};
+
+/// Path of the sync API on the server
+pub const API_PREFIX: &str = "/api/v1";
+
+/// JSON body of a failed request
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ErrorBody {
+    pub error: String,
+}
+
+/// HTTP method of a `SyncRequest`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Method {
+    Get,
+    Post,
+    Put,
+    Delete,
+}
+
+impl Method {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Method::Get => "GET",
+            Method::Post => "POST",
+            Method::Put => "PUT",
+            Method::Delete => "DELETE",
+        }
+    }
+}
+
+/// A request to the sync server
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyncRequest {
+    pub method: Method,
+    /// Full URL, including `API_PREFIX`
+    pub url: String,
+    /// Bearer token
+    pub token: String,
+    /// JSON body, if any
+    pub body: Option<String>,
+}
+
+/// The server's answer to a `SyncRequest`, whatever its status
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyncResponse {
+    pub status: u16,
+    pub body: String,
+}
+
+/// Future returned by `SyncTransport::send`
+pub type SyncFuture<'a> = Pin<Box<dyn Future<Output = Result<SyncResponse, SyncError>> + 'a>>;
+
+/// Sends requests to the sync server
+///
+/// Transports only fail when no answer arrives (`SyncError::Network`);
+/// error statuses are returned as responses for `SyncClient` to read.
+pub trait SyncTransport {
+    fn send(&self, request: SyncRequest) -> SyncFuture<'_>;
+}
+
+/// Error from syncing or calling the sync server
+#[derive(Debug, Clone)]
+pub enum SyncError {
+    /// The server couldn't be reached
+    Network(String),
+    /// The server didn't accept the token
+    Unauthorized,
+    /// The server refused the request
+    Rejected { status: u16, message: String },
+    /// The server's answer couldn't be read
+    InvalidResponse(String),
+    /// Reading or writing the local store failed
+    Storage(StorageError),
+}
+
+impl fmt::Display for SyncError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SyncError::Network(reason) => write!(f, "could not reach the sync server: {}", reason),
+            SyncError::Unauthorized => write!(f, "the sync server did not accept the token"),
+            SyncError::Rejected { status, message } => {
+                write!(f, "the sync server refused the request ({}): {}", status, message)
+            }
+            SyncError::InvalidResponse(reason) => write!(f, "unexpected answer from the sync server: {}", reason),
+            SyncError::Storage(error) => error.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for SyncError {}
+
+impl From<StorageError> for SyncError {
+    fn from(error: StorageError) -> Self {
+        SyncError::Storage(error)
+    }
+}
+
+/// Percent-encode an id for use as one URL path segment
+pub fn encode_path_segment(segment: &str) -> String {
+    let mut encoded = String::with_capacity(segment.len());
+    for byte in segment.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => encoded.push(byte as char),
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
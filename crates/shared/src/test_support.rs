@@ -0,0 +1,98 @@
+//! Helpers shared by the unit tests
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use chrono::{NaiveDate, NaiveDateTime};
+
+use crate::datetime::{LocalDateTime, Timestamp};
+use crate::models::{Priority, PriorityFilter, Reminder, ReminderFilter, ReminderSort, SavedView, Tag};
+
+/// 2024-01-15 00:00:00 UTC
+pub const JAN_15_MS: i64 = 1_705_276_800_000;
+
+/// A reminder with only an id and title, created at `JAN_15_MS`
+pub fn reminder(id: &str, title: &str) -> Reminder {
+    Reminder {
+        id: id.to_string(),
+        title: title.to_string(),
+        description: String::new(),
+        due_date: None,
+        time_zone: None,
+        completed: false,
+        created_at: Timestamp::from_millis(JAN_15_MS),
+        updated_at: None,
+        tag_ids: Vec::new(),
+        priority: Priority::None,
+        recurrence: None,
+        lead_times: Vec::new(),
+        snoozed_until: None,
+        snooze_log: Vec::new(),
+        checklist: Vec::new(),
+        auto_complete: false,
+        extensions: BTreeMap::new(),
+    }
+}
+
+/// A reminder due at `due` in UTC
+pub fn due_reminder(id: &str, due: NaiveDateTime) -> Reminder {
+    Reminder {
+        due_date: Some(LocalDateTime::floating(due)),
+        time_zone: Some(chrono_tz::UTC),
+        ..reminder(id, id)
+    }
+}
+
+pub fn tag(id: &str, name: &str) -> Tag {
+    Tag {
+        id: id.to_string(),
+        name: name.to_string(),
+        color: "#FA8A59".to_string(),
+    }
+}
+
+pub fn saved_view(id: &str, name: &str) -> SavedView {
+    SavedView {
+        id: id.to_string(),
+        name: name.to_string(),
+        filter: ReminderFilter::All,
+        priority_filter: PriorityFilter::All,
+        search_query: String::new(),
+        sort: ReminderSort::Date,
+        view: "list".to_string(),
+    }
+}
+
+pub fn datetime(year: i32, month: u32, day: u32, hour: u32, minute: u32) -> NaiveDateTime {
+    NaiveDate::from_ymd_opt(year, month, day)
+        .and_then(|date| date.and_hms_opt(hour, minute, 0))
+        .expect("valid date")
+}
+
+/// Directory under the system temp dir, removed when dropped
+pub struct TempDir(PathBuf);
+
+impl TempDir {
+    pub fn new() -> Self {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let path = std::env::temp_dir().join(format!(
+            "remind-me-test-{}-{}",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        let _ = std::fs::remove_dir_all(&path);
+        std::fs::create_dir_all(&path).expect("create temp dir");
+        Self(path)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
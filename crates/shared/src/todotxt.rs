@@ -0,0 +1,317 @@
+//! todo.txt import and export
+//!
+//! Each reminder is one line in the [todo.txt](https://github.com/todotxt/todo.txt)
+//! format:
+//!
+//! ```text
+//! (A) 2024-01-20 Renew passport +errands @town due:2024-01-31 t:2024-01-25
+//! x 2024-01-22 2024-01-20 Call Alex +work pri:B
+//! ```
+//!
+//! - `x` marks a completed task, followed by its completion date (the
+//!   reminder's `updated_at`); a completed task keeps its priority as `pri:`
+//! - Priorities `(A)` to `(D)` are urgent, high, medium and low; `(E)` to
+//!   `(Z)` are read as low
+//! - The creation date is the reminder's `created_at`
+//! - `+project` tags are named `project`, and `@context` tags keep their `@`
+//!   (`@town`), so both come back as they were; spaces in names become `-`
+//! - `due:` is the due date, either a date or a date and time
+//! - Other `key:value` pairs (`t:`, `rec:`, `id:`, …) are kept in
+//!   `Reminder::extensions` and written back on export
+//!
+//! A task is a single line, so descriptions and checklists aren't written.
+//! On import, a task with an unreadable date or no title is reported and left
+//! out.
+
+use chrono::{NaiveDate, NaiveTime};
+
+use crate::datetime::{LocalDateTime, Timestamp};
+use crate::models::{Priority, Reminder, Tag};
+use crate::transfer::{format_due, ImportBuilder, ReminderImport};
+
+/// MIME type of `todo.txt` files
+pub const TODO_TXT_MIME_TYPE: &str = "text/plain";
+
+const DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// Write `reminders` as todo.txt lines, naming tags from `tags`
+pub fn export_todo_txt(reminders: &[Reminder], tags: &[Tag]) -> String {
+    let mut out = String::new();
+    for reminder in reminders {
+        let mut words: Vec<String> = Vec::new();
+        if reminder.completed {
+            words.push("x".to_string());
+            let completed_at = reminder.updated_at.unwrap_or(reminder.created_at);
+            words.push(completed_at.to_local().date().format(DATE_FORMAT).to_string());
+        } else if let Some(letter) = priority_letter(reminder.priority) {
+            words.push(format!("({})", letter));
+        }
+        words.push(reminder.created_at.to_local().date().format(DATE_FORMAT).to_string());
+        words.extend(reminder.title.split_whitespace().map(str::to_string));
+
+        for tag in reminder.tag_ids.iter().filter_map(|id| tags.iter().find(|t| &t.id == id)) {
+            let name = tag.name.split_whitespace().collect::<Vec<_>>().join("-");
+            if name.starts_with('@') {
+                words.push(name);
+            } else {
+                words.push(format!("+{}", name));
+            }
+        }
+        if let Some(due) = reminder.due_date {
+            // A floating due date at midnight is written as a plain date
+            let value = match (reminder.time_zone, due.offset()) {
+                (None, None) if due.naive().time() == NaiveTime::MIN => due.date().format(DATE_FORMAT).to_string(),
+                _ => format_due(reminder).unwrap_or_default(),
+            };
+            words.push(format!("due:{}", value));
+        }
+        if reminder.completed {
+            if let Some(letter) = priority_letter(reminder.priority) {
+                words.push(format!("pri:{}", letter));
+            }
+        }
+        for (key, value) in &reminder.extensions {
+            words.push(format!("{}:{}", key, value));
+        }
+
+        out.push_str(&words.join(" "));
+        out.push('\n');
+    }
+    out
+}
+
+/// Read the tasks in a todo.txt file
+///
+/// Row numbers in errors are line numbers.
+pub fn import_todo_txt(text: &str, tags: &[Tag]) -> ReminderImport {
+    let mut import = ImportBuilder::new(tags);
+    for (index, line) in text.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        match read_task(&mut import, line) {
+            Ok(reminder) => import.push(reminder),
+            Err(reason) => import.error(index + 1, reason),
+        }
+    }
+    import.finish()
+}
+
+/// A reminder from one todo.txt line
+fn read_task(import: &mut ImportBuilder, line: &str) -> Result<Reminder, String> {
+    let mut words = line.split_whitespace().peekable();
+
+    let completed = words.next_if_eq(&"x").is_some();
+    let completed_on = if completed { words.next_if(|w| parse_date(w).is_some()) } else { None };
+    let mut priority = if completed {
+        Priority::None
+    } else {
+        words.next_if(|w| parse_priority(w).is_some()).and_then(parse_priority).unwrap_or_default()
+    };
+    let created_on = words.next_if(|w| parse_date(w).is_some());
+
+    let mut title_words = Vec::new();
+    let mut tag_names = Vec::new();
+    let mut due_date = None;
+    let mut extensions = Vec::new();
+    for word in words {
+        if let Some(project) = word.strip_prefix('+').filter(|name| !name.is_empty()) {
+            tag_names.push(project);
+        } else if word.len() > 1 && word.starts_with('@') {
+            tag_names.push(word);
+        } else if let Some((key, value)) = split_key_value(word) {
+            match key {
+                "due" => {
+                    let date = value.parse::<LocalDateTime>().map_err(|e| e.to_string())?;
+                    due_date = Some(date);
+                }
+                "pri" if completed => match priority_from_letter(value) {
+                    Some(letter_priority) => priority = letter_priority,
+                    None => extensions.push((key.to_string(), value.to_string())),
+                },
+                _ => extensions.push((key.to_string(), value.to_string())),
+            }
+        } else {
+            title_words.push(word);
+        }
+    }
+    if title_words.is_empty() {
+        return Err("the task has no title".to_string());
+    }
+
+    let mut reminder = import.reminder(title_words.join(" "));
+    reminder.completed = completed;
+    reminder.priority = priority;
+    reminder.due_date = due_date;
+    reminder.extensions = extensions.into_iter().collect();
+    if let Some(date) = created_on.and_then(parse_date) {
+        reminder.created_at = start_of_day(date);
+    }
+    if let Some(date) = completed_on.and_then(parse_date) {
+        reminder.updated_at = Some(start_of_day(date));
+    }
+    import.add_tags(&mut reminder, tag_names);
+    Ok(reminder)
+}
+
+fn priority_letter(priority: Priority) -> Option<char> {
+    match priority {
+        Priority::Urgent => Some('A'),
+        Priority::High => Some('B'),
+        Priority::Medium => Some('C'),
+        Priority::Low => Some('D'),
+        Priority::None => None,
+    }
+}
+
+/// Priority of a `(A)`…`(Z)` word, if it is one
+fn parse_priority(word: &str) -> Option<Priority> {
+    priority_from_letter(word.strip_prefix('(')?.strip_suffix(')')?)
+}
+
+fn priority_from_letter(letter: &str) -> Option<Priority> {
+    match letter {
+        "A" => Some(Priority::Urgent),
+        "B" => Some(Priority::High),
+        "C" => Some(Priority::Medium),
+        _ if letter.len() == 1 && letter.chars().all(|c| c.is_ascii_uppercase()) => Some(Priority::Low),
+        _ => None,
+    }
+}
+
+fn parse_date(word: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(word, DATE_FORMAT).ok()
+}
+
+/// A `key:value` word, if it is one
+///
+/// Keys start with a letter, so times like `10:30` stay in the title, and
+/// values can't start with `/`, so URLs do too.
+fn split_key_value(word: &str) -> Option<(&str, &str)> {
+    let (key, value) = word.split_once(':')?;
+    let valid_key = key.starts_with(|c: char| c.is_ascii_alphabetic())
+        && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    (valid_key && !value.is_empty() && !value.starts_with('/')).then_some((key, value))
+}
+
+/// Midnight at the start of `date` on the device
+fn start_of_day(date: NaiveDate) -> Timestamp {
+    LocalDateTime::floating(date.and_time(NaiveTime::MIN)).to_timestamp()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{datetime, due_reminder, reminder, tag};
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    fn extensions(reminder: &Reminder) -> Vec<(&str, &str)> {
+        reminder.extensions.iter().map(|(k, v)| (k.as_str(), v.as_str())).collect()
+    }
+
+    #[test]
+    fn tasks_are_written_as_single_lines() {
+        let tags = [tag("errands", "errands"), tag("town", "@town"), tag("garden", "Home garden")];
+        let mut passport = due_reminder("passport", datetime(2024, 1, 31, 0, 0));
+        passport.time_zone = None;
+        passport.title = "Renew  passport".to_string();
+        passport.description = "Not written".to_string();
+        passport.priority = Priority::High;
+        passport.created_at = start_of_day(date(2024, 1, 20));
+        passport.tag_ids = vec!["errands".to_string(), "town".to_string(), "garden".to_string()];
+        passport.extensions = [("t", "2024-01-25"), ("rec", "1w")].map(|(k, v)| (k.to_string(), v.to_string())).into();
+        let mut call = due_reminder("call", datetime(2024, 1, 31, 9, 30));
+        call.time_zone = None;
+        call.title = "Call Alex".to_string();
+        call.completed = true;
+        call.priority = Priority::Urgent;
+        call.created_at = start_of_day(date(2024, 1, 20));
+        call.updated_at = Some(start_of_day(date(2024, 1, 22)));
+
+        assert_eq!(
+            export_todo_txt(&[passport, call], &tags),
+            "(B) 2024-01-20 Renew passport +errands @town +Home-garden due:2024-01-31 rec:1w t:2024-01-25\n\
+             x 2024-01-22 2024-01-20 Call Alex due:2024-01-31T09:30 pri:A\n"
+        );
+    }
+
+    #[test]
+    fn exported_tasks_import_unchanged() {
+        let tags = [tag("errands", "errands"), tag("town", "@town"), tag("garden", "Home garden")];
+        let mut passport = due_reminder("passport", datetime(2024, 1, 31, 0, 0));
+        passport.time_zone = None;
+        passport.priority = Priority::Medium;
+        passport.tag_ids = vec!["errands".to_string(), "town".to_string(), "garden".to_string()];
+        passport.extensions = [("id", "7"), ("t", "2024-01-25")].map(|(k, v)| (k.to_string(), v.to_string())).into();
+        let mut zoned = due_reminder("zoned", datetime(2024, 7, 1, 18, 30));
+        zoned.time_zone = Some(chrono_tz::Asia::Tokyo);
+        zoned.completed = true;
+        zoned.priority = Priority::Low;
+        zoned.updated_at = Some(start_of_day(date(2024, 7, 2)));
+        let mut plain = reminder("plain", "Meet at 10:30, see https://example.com");
+        plain.created_at = start_of_day(date(2024, 1, 15));
+        for r in [&mut passport, &mut zoned] {
+            r.created_at = start_of_day(date(2024, 1, 15));
+        }
+        let reminders = [passport, zoned, plain];
+
+        let imported = import_todo_txt(&export_todo_txt(&reminders, &tags), &tags);
+        assert!(imported.errors.is_empty(), "{:?}", imported.errors);
+        assert!(imported.new_tags.is_empty());
+        assert_eq!(imported.reminders.len(), 3);
+        for (original, read) in reminders.iter().zip(&imported.reminders) {
+            assert_eq!(read.title, original.title);
+            assert_eq!(read.completed, original.completed);
+            assert_eq!(read.priority, original.priority);
+            assert_eq!(read.tag_ids, original.tag_ids);
+            assert_eq!(read.due_timestamp(), original.due_timestamp());
+            assert_eq!(read.extensions, original.extensions);
+            assert_eq!(read.created_at, original.created_at);
+            assert_eq!(read.updated_at, original.updated_at);
+        }
+        assert_eq!(imported.reminders[0].due_date, reminders[0].due_date);
+    }
+
+    #[test]
+    fn key_value_pairs_are_kept_as_extensions() {
+        let text = "(E) Water plants due:2024-02-01T08:00 rec:+1w t:2024-01-30 my_key:a:b\n\
+                    x 2024-01-22 Filed pri:b note: at 10:30 via http://example.com 1:2\n\
+                    (a) 2024-01-20 xylophone lesson @ + +music\n";
+        let imported = import_todo_txt(text, &[]);
+        assert!(imported.errors.is_empty());
+        let [water, filed, lesson] = &imported.reminders[..] else {
+            panic!("expected three reminders");
+        };
+        assert_eq!(water.title, "Water plants");
+        assert_eq!(water.priority, Priority::Low);
+        assert_eq!(water.due_date, Some("2024-02-01T08:00".parse().unwrap()));
+        assert_eq!(extensions(water), [("my_key", "a:b"), ("rec", "+1w"), ("t", "2024-01-30")]);
+
+        // A lowercase `pri:` isn't a priority, and is kept as it was
+        assert_eq!(filed.title, "Filed note: at 10:30 via http://example.com 1:2");
+        assert!(filed.completed);
+        assert_eq!(filed.priority, Priority::None);
+        assert_eq!(filed.updated_at, Some(start_of_day(date(2024, 1, 22))));
+        assert_eq!(extensions(filed), [("pri", "b")]);
+
+        assert_eq!(lesson.title, "(a) 2024-01-20 xylophone lesson @ +");
+        assert_eq!(lesson.priority, Priority::None);
+        assert_eq!(imported.new_tags.len(), 1);
+        assert_eq!(imported.new_tags[0].name, "music");
+    }
+
+    #[test]
+    fn unreadable_tasks_are_reported_with_their_lines() {
+        let text = "Good\n\nBad due:someday\n+only @tags due:2024-01-31\nx\nAlso good\n";
+        let imported = import_todo_txt(text, &[]);
+        let titles: Vec<&str> = imported.reminders.iter().map(|r| r.title.as_str()).collect();
+        assert_eq!(titles, ["Good", "Also good"]);
+        let rows: Vec<usize> = imported.errors.iter().map(|e| e.row).collect();
+        assert_eq!(rows, [3, 4, 5]);
+        assert_eq!(imported.errors[1].reason, "the task has no title");
+        assert!(imported.new_tags.is_empty());
+    }
+}
@@ -0,0 +1,310 @@
+//! Moving reminders in and out of the app in other tools' file formats
+//!
+//! Each format has its own module (`ical`, `csv`, `markdown`, `todotxt`); this module
+//! holds what they share. An import reads every row it can: rows that can't
+//! be read are reported in `ReminderImport::errors` and left out, rather
+//! than failing the whole file. Tag names are matched to stored tags, and
+//! names that don't match become new tags to save before the reminders.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::datetime::Timestamp;
+use crate::models::{Priority, Reminder, Tag};
+use crate::{csv, ical, markdown, todotxt};
+
+/// Color of tags created for imported tag names
+const IMPORTED_TAG_COLOR: &str = "#6A7CED";
+
+/// A file format reminders can be exported to and imported from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransferFormat {
+    /// iCalendar (`.ics`), see `crate::ical`
+    Calendar,
+    /// Comma-separated values, see `crate::csv`
+    Csv,
+    /// Markdown task list, see `crate::markdown`
+    Markdown,
+    /// todo.txt, see `crate::todotxt`
+    TodoTxt,
+}
+
+impl TransferFormat {
+    pub const ALL: [TransferFormat; 4] = [
+        TransferFormat::Calendar,
+        TransferFormat::Csv,
+        TransferFormat::Markdown,
+        TransferFormat::TodoTxt,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TransferFormat::Calendar => "calendar",
+            TransferFormat::Csv => "csv",
+            TransferFormat::Markdown => "markdown",
+            TransferFormat::TodoTxt => "todotxt",
+        }
+    }
+
+    /// File name extension, without the dot
+    pub fn extension(&self) -> &'static str {
+        match self {
+            TransferFormat::Calendar => "ics",
+            TransferFormat::Csv => "csv",
+            TransferFormat::Markdown => "md",
+            TransferFormat::TodoTxt => "txt",
+        }
+    }
+
+    /// Name of exported files; todo.txt tools look for `todo.txt`
+    pub fn file_name(&self) -> String {
+        match self {
+            TransferFormat::TodoTxt => "todo.txt".to_string(),
+            _ => format!("remind-me.{}", self.extension()),
+        }
+    }
+
+    pub fn mime_type(&self) -> &'static str {
+        match self {
+            TransferFormat::Calendar => ical::ICS_MIME_TYPE,
+            TransferFormat::Csv => csv::CSV_MIME_TYPE,
+            TransferFormat::Markdown => markdown::MARKDOWN_MIME_TYPE,
+            TransferFormat::TodoTxt => todotxt::TODO_TXT_MIME_TYPE,
+        }
+    }
+
+    /// Write `reminders` in this format, naming tags from `tags`
+    pub fn export(&self, reminders: &[Reminder], tags: &[Tag]) -> String {
+        match self {
+            TransferFormat::Calendar => ical::export_ics(reminders, tags),
+            TransferFormat::Csv => csv::export_csv(reminders, tags),
+            TransferFormat::Markdown => markdown::export_markdown(reminders, tags),
+            TransferFormat::TodoTxt => todotxt::export_todo_txt(reminders, tags),
+        }
+    }
+
+    /// Read reminders in this format; fails only if the file as a whole can't be read
+    pub fn import(&self, text: &str, tags: &[Tag]) -> Result<ReminderImport, String> {
+        match self {
+            TransferFormat::Calendar => ical::import_ics(text, tags).map_err(|e| e.to_string()),
+            TransferFormat::Csv => csv::import_csv(text, tags).map_err(|e| e.to_string()),
+            TransferFormat::Markdown => Ok(markdown::import_markdown(text, tags)),
+            TransferFormat::TodoTxt => Ok(todotxt::import_todo_txt(text, tags)),
+        }
+    }
+}
+
+/// Reminders read from a file
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReminderImport {
+    pub reminders: Vec<Reminder>,
+    /// Tags created for names that didn't match an existing tag; they must
+    /// be saved before the reminders
+    pub new_tags: Vec<Tag>,
+    /// Rows that were left out, in file order
+    pub errors: Vec<RowError>,
+}
+
+/// A row (or line, or component) of an imported file that was left out
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RowError {
+    /// 1-based row or line number
+    pub row: usize,
+    pub reason: String,
+}
+
+impl fmt::Display for RowError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "row {}: {}", self.row, self.reason)
+    }
+}
+
+/// Builds a `ReminderImport`, matching tag names and numbering new ids
+pub(crate) struct ImportBuilder<'a> {
+    tags: &'a [Tag],
+    import: ReminderImport,
+    /// When the import started, shared by every new id and `created_at`
+    started: Timestamp,
+    next_id: usize,
+}
+
+impl<'a> ImportBuilder<'a> {
+    pub(crate) fn new(tags: &'a [Tag]) -> Self {
+        Self {
+            tags,
+            import: ReminderImport {
+                reminders: Vec::new(),
+                new_tags: Vec::new(),
+                errors: Vec::new(),
+            },
+            started: Timestamp::now(),
+            next_id: 0,
+        }
+    }
+
+    /// A reminder with a fresh id and nothing else set
+    pub(crate) fn reminder(&mut self, title: String) -> Reminder {
+        self.next_id += 1;
+        Reminder {
+            id: format!("reminder_{}_{}", self.started.as_millis(), self.next_id),
+            title,
+            description: String::new(),
+            due_date: None,
+            time_zone: None,
+            completed: false,
+            created_at: self.started,
+            updated_at: None,
+            tag_ids: Vec::new(),
+            priority: Priority::None,
+            recurrence: None,
+            lead_times: Vec::new(),
+            snoozed_until: None,
+            snooze_log: Vec::new(),
+            checklist: Vec::new(),
+            auto_complete: false,
+            extensions: BTreeMap::new(),
+        }
+    }
+
+    /// Id of the tag called `name`, creating it if there is none
+    ///
+    /// Names match ignoring case, and with `-` standing in for spaces (as
+    /// in Markdown `#tags`).
+    pub(crate) fn tag_id(&mut self, name: &str) -> String {
+        let wanted = tag_key(name);
+        let existing = self
+            .tags
+            .iter()
+            .chain(self.import.new_tags.iter())
+            .find(|t| tag_key(&t.name) == wanted);
+        if let Some(tag) = existing {
+            return tag.id.clone();
+        }
+        let tag = Tag {
+            id: format!("tag_{}_{}", self.started.as_millis(), self.import.new_tags.len() + 1),
+            name: name.trim().to_string(),
+            color: IMPORTED_TAG_COLOR.to_string(),
+        };
+        let id = tag.id.clone();
+        self.import.new_tags.push(tag);
+        id
+    }
+
+    /// Add the tags called `names` to `reminder`, skipping blanks and repeats
+    pub(crate) fn add_tags<'n>(&mut self, reminder: &mut Reminder, names: impl IntoIterator<Item = &'n str>) {
+        for name in names {
+            if name.trim().is_empty() {
+                continue;
+            }
+            let id = self.tag_id(name);
+            if !reminder.tag_ids.contains(&id) {
+                reminder.tag_ids.push(id);
+            }
+        }
+    }
+
+    pub(crate) fn push(&mut self, reminder: Reminder) {
+        self.import.reminders.push(reminder);
+    }
+
+    pub(crate) fn contains(&self, id: &str) -> bool {
+        self.import.reminders.iter().any(|r| r.id == id)
+    }
+
+    pub(crate) fn error(&mut self, row: usize, reason: impl Into<String>) {
+        self.import.errors.push(RowError {
+            row,
+            reason: reason.into(),
+        });
+    }
+
+    pub(crate) fn finish(self) -> ReminderImport {
+        self.import
+    }
+}
+
+/// Tag name as compared on import: lower case, words joined by `-`
+fn tag_key(name: &str) -> String {
+    name.to_lowercase()
+        .split(|c: char| c.is_whitespace() || c == '-')
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// Due date as text for line-based formats
+///
+/// Floating and pinned due dates are written as stored; a due date in a
+/// time zone is written as the UTC instant, so it means the same after import.
+pub(crate) fn format_due(reminder: &Reminder) -> Option<String> {
+    let due = reminder.due_date?;
+    Some(match reminder.time_zone {
+        Some(_) => reminder.due_timestamp()?.to_rfc3339(),
+        None => due.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{datetime, due_reminder, reminder, tag};
+
+    #[test]
+    fn tag_names_match_ignoring_case_and_dashes() {
+        assert_eq!(tag_key(" Home  Garden "), "home-garden");
+        assert_eq!(tag_key("home-garden"), "home-garden");
+        assert_eq!(tag_key("HOME--garden"), "home-garden");
+
+        let tags = [tag("garden", "Home garden")];
+        let mut import = ImportBuilder::new(&tags);
+        let mut first = import.reminder("First".to_string());
+        import.add_tags(&mut first, ["home-Garden", "New tag", " ", "new-tag", "Home garden"]);
+        let second = import.reminder("Second".to_string());
+        assert_ne!(first.id, second.id);
+        assert_eq!(first.tag_ids.len(), 2);
+        assert_eq!(first.tag_ids[0], "garden");
+        let first_id = first.id.clone();
+        import.push(first);
+        assert!(import.contains(&first_id));
+        assert!(!import.contains(&second.id));
+        let import = import.finish();
+        assert_eq!(import.new_tags.len(), 1);
+        assert_eq!(import.new_tags[0].name, "New tag");
+        assert_eq!(import.new_tags[0].color, IMPORTED_TAG_COLOR);
+        assert_eq!(import.reminders[0].tag_ids[1], import.new_tags[0].id);
+    }
+
+    #[test]
+    fn zoned_due_dates_are_written_as_utc() {
+        let mut due = due_reminder("due", datetime(2024, 7, 1, 18, 30));
+        due.time_zone = None;
+        assert_eq!(format_due(&due).as_deref(), Some("2024-07-01T18:30"));
+        due.time_zone = Some(chrono_tz::Europe::Paris);
+        assert_eq!(format_due(&due).as_deref(), Some("2024-07-01T16:30:00.000Z"));
+        assert_eq!(format_due(&reminder("undated", "Undated")), None);
+    }
+
+    #[test]
+    fn every_format_round_trips_through_its_file() {
+        let tags = [tag("work", "Work")];
+        let mut due = due_reminder("due", datetime(2024, 1, 31, 9, 0));
+        due.tag_ids = vec!["work".to_string()];
+        let reminders = [due, reminder("plain", "Plain")];
+        for format in TransferFormat::ALL {
+            let file = format.export(&reminders, &tags);
+            let imported = format.import(&file, &tags).unwrap();
+            assert!(imported.errors.is_empty(), "{}: {:?}", format.as_str(), imported.errors);
+            assert!(imported.new_tags.is_empty(), "{}", format.as_str());
+            let read: Vec<_> = imported.reminders.iter().map(|r| (r.title.as_str(), r.due_timestamp(), &r.tag_ids)).collect();
+            let written: Vec<_> = reminders.iter().map(|r| (r.title.as_str(), r.due_timestamp(), &r.tag_ids)).collect();
+            assert_eq!(read, written, "{}", format.as_str());
+            assert!(format.file_name().ends_with(format.extension()));
+            assert!(format.mime_type().starts_with("text/"));
+        }
+        assert_eq!(TransferFormat::TodoTxt.file_name(), "todo.txt");
+        assert_eq!(TransferFormat::Markdown.file_name(), "remind-me.md");
+        assert!(TransferFormat::Csv.import("", &tags).is_err());
+        assert!(TransferFormat::Calendar.import("not a calendar", &tags).is_err());
+        assert!(TransferFormat::Markdown.import("", &tags).unwrap().reminders.is_empty());
+    }
+}
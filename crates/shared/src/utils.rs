@@ -1,51 +1,36 @@
 //! Utility functions for the Remind Me application
 //! 
-//! This module contains helper functions for date formatting, statistics calculation,
-//! and reminder filtering/sorting. Some functions have platform-specific implementations
-//! using conditional compilation.
-
-use crate::models::{Reminder, Statistics, ReminderFilter, ReminderSort};
-
-#[cfg(not(target_arch = "wasm32"))]
-use chrono::TimeZone;
-
-pub fn format_date(date_str: &str) -> String {
-    #[cfg(target_arch = "wasm32")]
-    {
-        return format_date_wasm(date_str);
-    }
-
-    #[cfg(not(target_arch = "wasm32"))]
-    {
-        if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(date_str) {
-            dt.format("%Y-%m-%d %H:%M").to_string()
-        } else if let Ok(dt) = chrono::NaiveDateTime::parse_from_str(date_str, "%Y-%m-%dT%H:%M") {
-            dt.format("%Y-%m-%d %H:%M").to_string()
-        } else {
-            date_str.to_string()
-        }
-    }
+//! This module contains helper functions for statistics calculation, reminder
+//! filtering/sorting and calendar layout. Date parsing and formatting live on the
+//! types in `crate::datetime`.
+
+use chrono::{Datelike, Days, Local, NaiveDate, TimeZone};
+
+use crate::datetime::{device_time_zone, Timestamp};
+use crate::models::{PriorityFilter, Reminder, Statistics, ReminderFilter, ReminderSort, Tag};
+use crate::query::Query;
+use crate::recurrence::occurrence_dates;
+use crate::snooze::{is_snoozed, is_snoozed_at};
+
+/// Sort key for a reminder's due date (reminders without one sort last)
+fn due_sort_key(reminder: &Reminder) -> i64 {
+    reminder
+        .due_timestamp()
+        .map_or(i64::MAX, |due| due.as_millis())
 }
 
-pub fn parse_date_for_sort(date_str: &str) -> i64 {
-    #[cfg(target_arch = "wasm32")]
-    {
-        return parse_date_for_sort_wasm(date_str);
-    }
-
-    #[cfg(not(target_arch = "wasm32"))]
-    {
-        if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(date_str) {
-            dt.timestamp_millis()
-        } else if let Ok(dt) = chrono::NaiveDateTime::parse_from_str(date_str, "%Y-%m-%dT%H:%M") {
-            if let Some(local_dt) = chrono::Local.from_local_datetime(&dt).single() {
-                local_dt.timestamp_millis()
-            } else {
-                i64::MAX
-            }
-        } else {
-            i64::MAX
+/// Display string for a reminder's due date in the device's time zone.
+///
+/// If the reminder is pinned to a different time zone, its wall-clock time
+/// there is appended, e.g. `2026-05-01 03:00 (09:00 Europe/Paris)`.
+pub fn format_due_date(reminder: &Reminder) -> Option<String> {
+    let device_local = reminder.due_device_local()?.format("%Y-%m-%d %H:%M").to_string();
+    match reminder.time_zone {
+        Some(tz) if Some(tz) != device_time_zone() => {
+            let zone_local = reminder.due_zone_local()?;
+            Some(format!("{} ({} {})", device_local, zone_local.format("%H:%M"), tz.name()))
         }
+        _ => Some(device_local),
     }
 }
 
@@ -53,25 +38,226 @@ pub fn calculate_statistics(reminders: &[Reminder]) -> Statistics {
     let total = reminders.len();
     let active = reminders.iter().filter(|r| !r.completed).count();
     let completed = reminders.iter().filter(|r| r.completed).count();
-    let overdue = reminders
+    let overdue = reminders.iter().filter(|r| is_overdue(r)).count();
+    let snoozed = reminders.iter().filter(|r| !r.completed && is_snoozed(r)).count();
+    let snooze_count = reminders.iter().map(|r| r.snooze_log.len()).sum();
+    let (checklist_completed, checklist_items) = reminders
         .iter()
-        .filter(|r| {
-            if r.completed || r.due_date.is_empty() {
-                return false;
-            }
-            is_overdue(&r.due_date)
-        })
-        .count();
+        .map(Reminder::checklist_progress)
+        .fold((0, 0), |(done, total), (d, t)| (done + d, total + t));
 
     Statistics {
         total,
         active,
         completed,
         overdue,
+        snoozed,
+        snooze_count,
+        checklist_items,
+        checklist_completed,
+    }
+}
+
+/// Score for a search word equal to a whole word of the text
+const MATCH_EXACT: u32 = 100;
+/// Score for a search word that starts a word of the text
+const MATCH_PREFIX: u32 = 80;
+/// Score for a search word found anywhere in the text
+const MATCH_SUBSTRING: u32 = 60;
+/// Score for a search word within a few typos of a word of the text
+const MATCH_TYPO: u32 = 50;
+/// Score lost per typo
+const MATCH_TYPO_PENALTY: u32 = 15;
+
+/// A search word matched against a piece of text
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FuzzyMatch {
+    /// Relevance of the match (higher is better)
+    pub score: u32,
+    /// Byte range of the matched characters in the text
+    pub range: std::ops::Range<usize>,
+}
+
+/// A word or phrase to search for
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SearchWord<'a> {
+    pub text: &'a str,
+    /// Match `text` literally (quoted phrases) instead of fuzzily
+    pub exact: bool,
+}
+
+/// Relevance and highlight ranges of a reminder matching a search
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SearchMatch {
+    /// Total relevance (higher is better)
+    pub score: u32,
+    /// Byte ranges to highlight in the title
+    pub title: Vec<std::ops::Range<usize>>,
+    /// Byte ranges to highlight in the description
+    pub description: Vec<std::ops::Range<usize>>,
+    /// Tags whose names matched
+    pub tag_ids: Vec<String>,
+}
+
+/// Number of typos tolerated in a search word of `len` characters
+fn max_typos(len: usize) -> usize {
+    match len {
+        0..=3 => 0,
+        4..=6 => 1,
+        _ => 2,
+    }
+}
+
+/// Optimal string alignment distance (Levenshtein plus adjacent transpositions)
+fn edit_distance(a: &[char], b: &[char]) -> usize {
+    let mut rows = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in rows[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut best = (rows[i - 1][j] + 1)
+                .min(rows[i][j - 1] + 1)
+                .min(rows[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                best = best.min(rows[i - 2][j - 2] + 1);
+            }
+            rows[i][j] = best;
+        }
     }
+    rows[a.len()][b.len()]
+}
+
+/// Match `needle` against `text`, case-insensitively.
+///
+/// Unless `exact` is set, words of `text` within a few typos of `needle`
+/// also match (so "dentsit" finds "Dentist"), scoring lower than exact,
+/// prefix and substring matches.
+fn match_text(needle: &str, text: &str, exact: bool) -> Option<FuzzyMatch> {
+    let needle: Vec<char> = needle.trim().to_lowercase().chars().collect();
+    if needle.is_empty() {
+        return None;
+    }
+    let chars: Vec<(usize, char)> = text
+        .char_indices()
+        .map(|(i, c)| (i, c.to_lowercase().next().unwrap_or(c)))
+        .collect();
+    let lowered: Vec<char> = chars.iter().map(|(_, c)| *c).collect();
+    let byte_at = |index: usize| chars.get(index).map_or(text.len(), |(i, _)| *i);
+
+    let mut best: Option<FuzzyMatch> = None;
+    let mut consider = |score: u32, start: usize, end: usize| {
+        if best.as_ref().is_none_or(|b| score > b.score) {
+            best = Some(FuzzyMatch {
+                score,
+                range: byte_at(start)..byte_at(end),
+            });
+        }
+    };
+
+    if let Some(start) = lowered.windows(needle.len()).position(|window| window == needle.as_slice()) {
+        let at_word_start = start == 0 || !lowered[start - 1].is_alphanumeric();
+        let end = start + needle.len();
+        let at_word_end = end == lowered.len() || !lowered[end].is_alphanumeric();
+        let score = match (at_word_start, at_word_end) {
+            (true, true) => MATCH_EXACT,
+            (true, false) => MATCH_PREFIX,
+            _ => MATCH_SUBSTRING,
+        };
+        consider(score, start, end);
+    }
+
+    let typos = max_typos(needle.len());
+    if exact || typos == 0 {
+        return best;
+    }
+
+    let mut start = 0;
+    while start < lowered.len() {
+        if !lowered[start].is_alphanumeric() {
+            start += 1;
+            continue;
+        }
+        let mut end = start;
+        while end < lowered.len() && lowered[end].is_alphanumeric() {
+            end += 1;
+        }
+        let word = &lowered[start..end];
+        let prefix_end = start + needle.len().min(word.len());
+        let (distance, match_end) = [(edit_distance(&needle, word), end), (edit_distance(&needle, &lowered[start..prefix_end]), prefix_end)]
+            .into_iter()
+            .min_by_key(|(distance, _)| *distance)
+            .unwrap_or((usize::MAX, end));
+        if distance <= typos {
+            consider(MATCH_TYPO - MATCH_TYPO_PENALTY * distance as u32, start, match_end);
+        }
+        start = end;
+    }
+
+    best
+}
+
+/// Fuzzily match a single search word against `text` (case-insensitive)
+pub fn fuzzy_match(word: &str, text: &str) -> Option<FuzzyMatch> {
+    match_text(word, text, false)
+}
+
+/// Score `reminder` against search words, with ranges to highlight.
+///
+/// Every word must match the title, description, a checklist item or the name
+/// of one of the reminder's tags. Title matches weigh most, then tag names,
+/// then description and checklist. Returns `None` if any word does not match.
+pub fn fuzzy_match_reminder(reminder: &Reminder, tags: &[Tag], words: &[SearchWord<'_>]) -> Option<SearchMatch> {
+    let mut result = SearchMatch::default();
+
+    for word in words {
+        let title = match_text(word.text, &reminder.title, word.exact);
+        let description = match_text(word.text, &reminder.description, word.exact);
+        let checklist = reminder
+            .checklist
+            .iter()
+            .filter_map(|item| match_text(word.text, &item.text, word.exact))
+            .map(|m| m.score)
+            .max();
+        let tag_matches: Vec<(&Tag, FuzzyMatch)> = tags
+            .iter()
+            .filter(|tag| reminder.tag_ids.contains(&tag.id))
+            .filter_map(|tag| match_text(word.text, &tag.name, word.exact).map(|m| (tag, m)))
+            .collect();
+
+        let best = [
+            title.as_ref().map(|m| m.score * 3),
+            tag_matches.iter().map(|(_, m)| m.score * 2).max(),
+            description.as_ref().map(|m| m.score),
+            checklist,
+        ]
+        .into_iter()
+        .flatten()
+        .max()?;
+
+        result.score += best;
+        result.title.extend(title.map(|m| m.range));
+        result.description.extend(description.map(|m| m.range));
+        for (tag, _) in tag_matches {
+            if !result.tag_ids.contains(&tag.id) {
+                result.tag_ids.push(tag.id.clone());
+            }
+        }
+    }
+
+    Some(result)
 }
 
 /// Filter and sort reminders based on filter, search query, and sort criteria.
+///
+/// The search query uses the syntax in `crate::query`; a query that fails to
+/// parse falls back to a plain substring search so typing is never blocked.
+/// When the query contains search words, results are ranked by relevance,
+/// with `sort_by` breaking ties.
 /// 
 /// Performance: This function is optimized for typical use cases (<100 reminders).
 /// For larger datasets (>500 items), consider implementing virtual scrolling
@@ -79,10 +265,15 @@ pub fn calculate_statistics(reminders: &[Reminder]) -> Statistics {
 /// chains and in-place sorting.
 pub fn get_filtered_and_sorted_reminders(
     reminders: &[Reminder],
+    tags: &[Tag],
     filter: &ReminderFilter,
+    priority_filter: &PriorityFilter,
     search_query: &str,
     sort_by: &ReminderSort,
 ) -> Vec<Reminder> {
+    let query = Query::parse_lenient(search_query);
+    let now = Timestamp::now();
+
     let mut filtered: Vec<Reminder> = reminders
         .iter()
         .filter(|r| {
@@ -93,16 +284,16 @@ pub fn get_filtered_and_sorted_reminders(
                 ReminderFilter::All => true,
             };
 
+            // Apply priority filter
+            let matches_priority = match priority_filter {
+                PriorityFilter::All => true,
+                PriorityFilter::Only(priority) => r.priority == *priority,
+            };
+
             // Apply search
-            let matches_search = search_query.is_empty()
-                || r.title
-                    .to_lowercase()
-                    .contains(&search_query.to_lowercase())
-                || r.description
-                    .to_lowercase()
-                    .contains(&search_query.to_lowercase());
-
-            matches_filter && matches_search
+            let matches_search = query.matches_at(r, tags, now);
+
+            matches_filter && matches_priority && matches_search
         })
         .cloned()
         .collect();
@@ -124,253 +315,144 @@ pub fn get_filtered_and_sorted_reminders(
         }
         ReminderSort::Date => {
             // Sort by date (default)
-            filtered.sort_by(|a, b| {
-                let date_a = parse_date_for_sort(&a.due_date);
-                let date_b = parse_date_for_sort(&b.due_date);
-                date_a.cmp(&date_b)
-            });
+            filtered.sort_by_key(due_sort_key);
+        }
+        ReminderSort::Priority => {
+            filtered.sort_by_key(|r| (std::cmp::Reverse(r.priority), due_sort_key(r)));
+        }
+        ReminderSort::Smart => {
+            filtered.sort_by_cached_key(|r| (!is_overdue_at(r, now), std::cmp::Reverse(r.priority), due_sort_key(r)));
         }
     }
 
+    // Rank by relevance (stable, so the chosen sort breaks ties)
+    if query.has_search_words() {
+        filtered.sort_by_cached_key(|r| {
+            std::cmp::Reverse(query.search_match(r, tags).map_or(0, |m| m.score))
+        });
+    }
+
     filtered
 }
 
 pub fn now_timestamp_millis() -> i64 {
-    #[cfg(target_arch = "wasm32")]
-    {
-        return js_sys::Date::now() as i64;
-    }
-
-    #[cfg(not(target_arch = "wasm32"))]
-    {
-        return chrono::Utc::now().timestamp_millis();
-    }
+    Timestamp::now().as_millis()
 }
 
-pub fn now_rfc3339() -> String {
-    #[cfg(target_arch = "wasm32")]
-    {
-        let d = js_sys::Date::new_0();
-        return d.to_iso_string().as_string().unwrap_or_default();
-    }
-
-    #[cfg(not(target_arch = "wasm32"))]
-    {
-        return chrono::Utc::now().to_rfc3339();
-    }
+/// Whether an active reminder is past its due date and not currently snoozed
+pub fn is_overdue(reminder: &Reminder) -> bool {
+    is_overdue_at(reminder, Timestamp::now())
 }
 
-pub fn is_overdue(date_str: &str) -> bool {
-    let Some(due_ms) = parse_date_to_epoch_ms(date_str) else {
+/// Whether an active reminder is past its due date and not snoozed at `now`
+pub fn is_overdue_at(reminder: &Reminder, now: Timestamp) -> bool {
+    if reminder.completed {
         return false;
-    };
-    due_ms < now_timestamp_millis()
-}
-
-pub fn to_datetime_local_value(date_str: &str) -> String {
-    // If it's already a datetime-local value, keep it.
-    if date_str.len() >= 16 && date_str.as_bytes().get(10) == Some(&b'T') {
-        return date_str.chars().take(16).collect();
-    }
-
-    #[cfg(target_arch = "wasm32")]
-    {
-        let Some(ms) = parse_date_to_epoch_ms(date_str) else {
-            return date_str.to_string();
-        };
-        let d = js_sys::Date::new(&wasm_bindgen::JsValue::from_f64(ms as f64));
-        return format!(
-            "{:04}-{:02}-{:02}T{:02}:{:02}",
-            d.get_full_year(),
-            d.get_month() + 1,
-            d.get_date(),
-            d.get_hours(),
-            d.get_minutes()
-        );
-    }
-
-    #[cfg(not(target_arch = "wasm32"))]
-    {
-        // Fallback: try chrono parse for consistent formatting.
-        if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(date_str) {
-            return dt.format("%Y-%m-%dT%H:%M").to_string();
-        }
-        date_str.to_string()
-    }
-}
-
-fn parse_date_to_epoch_ms(date_str: &str) -> Option<i64> {
-    if date_str.trim().is_empty() {
-        return None;
-    }
-
-    #[cfg(target_arch = "wasm32")]
-    {
-        let d = js_sys::Date::new(&wasm_bindgen::JsValue::from_str(date_str));
-        let ms = d.get_time();
-        if ms.is_nan() {
-            None
-        } else {
-            Some(ms as i64)
-        }
-    }
-
-    #[cfg(not(target_arch = "wasm32"))]
-    {
-        if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(date_str) {
-            Some(dt.timestamp_millis())
-        } else if let Ok(dt) = chrono::NaiveDateTime::parse_from_str(date_str, "%Y-%m-%dT%H:%M") {
-            chrono::Local
-                .from_local_datetime(&dt)
-                .single()
-                .map(|dt| dt.timestamp_millis())
-        } else {
-            None
-        }
     }
+    let Some(due) = reminder.due_timestamp() else {
+        return false;
+    };
+    due < now && !is_snoozed_at(reminder, now)
 }
 
-#[cfg(target_arch = "wasm32")]
-fn format_date_wasm(date_str: &str) -> String {
-    let d = js_sys::Date::new(&wasm_bindgen::JsValue::from_str(date_str));
-    let ms = d.get_time();
-    if ms.is_nan() {
-        return date_str.to_string();
-    }
-
-    format!(
-        "{:04}-{:02}-{:02} {:02}:{:02}",
-        d.get_full_year(),
-        d.get_month() + 1,
-        d.get_date(),
-        d.get_hours(),
-        d.get_minutes()
-    )
+/// Calendar date key (YYYY-MM-DD) of a wall-clock time
+fn date_key(date_time: chrono::NaiveDateTime) -> String {
+    date_time.format("%Y-%m-%d").to_string()
 }
 
-#[cfg(target_arch = "wasm32")]
-fn parse_date_for_sort_wasm(date_str: &str) -> i64 {
-    parse_date_to_epoch_ms(date_str).unwrap_or(i64::MAX)
+/// Wall-clock due time of `reminder` on a device in time zone `device`
+fn due_on_device<Z: TimeZone>(reminder: &Reminder, device: &Z) -> Option<chrono::NaiveDateTime> {
+    let due = reminder.due_date?;
+    Some(match reminder.time_zone {
+        Some(tz) => due.to_timestamp_in(&tz).to_utc().with_timezone(device).naive_local(),
+        None => due.wall_clock_in(device),
+    })
 }
 
-/// Extract date part (YYYY-MM-DD) from a date string
-pub fn extract_date_key(date_str: &str) -> Option<String> {
-    if date_str.trim().is_empty() {
-        return None;
-    }
-
-    #[cfg(target_arch = "wasm32")]
-    {
-        let Some(ms) = parse_date_to_epoch_ms(date_str) else {
-            return None;
-        };
-        let d = js_sys::Date::new(&wasm_bindgen::JsValue::from_f64(ms as f64));
-        if d.get_time().is_nan() {
-            return None;
-        }
-        Some(format!(
-            "{:04}-{:02}-{:02}",
-            d.get_full_year(),
-            d.get_month() + 1,
-            d.get_date()
-        ))
-    }
-
-    #[cfg(not(target_arch = "wasm32"))]
-    {
-        if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(date_str) {
-            Some(dt.format("%Y-%m-%d").to_string())
-        } else if let Ok(dt) = chrono::NaiveDateTime::parse_from_str(date_str, "%Y-%m-%dT%H:%M") {
-            Some(dt.format("%Y-%m-%d").to_string())
-        } else {
-            None
-        }
-    }
+/// Group reminders by date key (YYYY-MM-DD) for the calendar month `year`/`month`
+///
+/// Reminders are placed on the day they fall due in the device's time zone.
+/// Active recurring reminders are expanded into one entry per occurrence in that
+/// month, each carrying the occurrence's due date. One-off reminders are grouped
+/// by their own due date.
+pub fn group_reminders_by_date(reminders: &[Reminder], year: i32, month: u32) -> std::collections::HashMap<String, Vec<Reminder>> {
+    group_reminders_by_date_in(reminders, year, month, &Local)
 }
 
-/// Group reminders by date key (YYYY-MM-DD)
-pub fn group_reminders_by_date(reminders: &[Reminder]) -> std::collections::HashMap<String, Vec<Reminder>> {
+/// `group_reminders_by_date` for a device in time zone `device`
+///
+/// Occurrences are expanded on the wall clock of the reminder's own time
+/// zone, where the rule applies, then moved to the device's. UTC offsets
+/// span 26 hours, so the two dates can differ by up to two days: expansion
+/// covers two more days on each side of the month and keeps the occurrences
+/// that land in it on the device.
+fn group_reminders_by_date_in<Z: TimeZone>(
+    reminders: &[Reminder],
+    year: i32,
+    month: u32,
+    device: &Z,
+) -> std::collections::HashMap<String, Vec<Reminder>> {
     let mut grouped = std::collections::HashMap::new();
+    let month_range = NaiveDate::from_ymd_opt(year, month, 1)
+        .zip(NaiveDate::from_ymd_opt(year, month, get_days_in_month(year, month)));
     
     for reminder in reminders {
-        if let Some(date_key) = extract_date_key(&reminder.due_date) {
-            grouped.entry(date_key).or_insert_with(Vec::new).push(reminder.clone());
+        if reminder.recurrence.is_some() && !reminder.completed {
+            let Some((from, to)) = month_range else {
+                continue;
+            };
+            let margin = Days::new(2);
+            let (Some(expand_from), Some(expand_to)) = (from.checked_sub_days(margin), to.checked_add_days(margin)) else {
+                continue;
+            };
+            for due_date in occurrence_dates(reminder, expand_from, expand_to) {
+                let occurrence = Reminder {
+                    due_date: Some(due_date),
+                    ..reminder.clone()
+                };
+                match due_on_device(&occurrence, device) {
+                    Some(due) if (from..=to).contains(&due.date()) => {
+                        grouped.entry(date_key(due)).or_insert_with(Vec::new).push(occurrence);
+                    }
+                    _ => {}
+                }
+            }
+        } else if let Some(due) = due_on_device(reminder, device) {
+            grouped.entry(date_key(due)).or_insert_with(Vec::new).push(reminder.clone());
         }
     }
     
     // Sort reminders within each date group
     for reminders_in_date in grouped.values_mut() {
-        reminders_in_date.sort_by(|a, b| {
-            let date_a = parse_date_for_sort(&a.due_date);
-            let date_b = parse_date_for_sort(&b.due_date);
-            date_a.cmp(&date_b)
-        });
+        reminders_in_date.sort_by_key(due_sort_key);
     }
     
     grouped
 }
 
 /// Calendar helper: Get current date components (year, month, day)
-#[cfg(target_arch = "wasm32")]
-pub fn get_current_date() -> (i32, u32, u32) {
-    let d = js_sys::Date::new_0();
-    (
-        d.get_full_year() as i32,
-        (d.get_month() + 1) as u32,
-        d.get_date() as u32,
-    )
-}
-
-#[cfg(not(target_arch = "wasm32"))]
 pub fn get_current_date() -> (i32, u32, u32) {
-    use chrono::Datelike;
-    let now = chrono::Local::now();
-    (now.year(), now.month(), now.day())
+    let today = Timestamp::now().to_local().naive().date();
+    (today.year(), today.month(), today.day())
 }
 
 /// Calendar helper: Get number of days in a month
-#[cfg(target_arch = "wasm32")]
 pub fn get_days_in_month(year: i32, month: u32) -> u32 {
-    // Create a date for the first day of next month, then subtract 1 day to get last day of current month
-    let next_month = if month == 12 { 1 } else { month + 1 };
-    let next_year = if month == 12 { year + 1 } else { year };
-    let d = js_sys::Date::new(&format!("{}-{:02}-01", next_year, next_month).into());
-    d.set_date(0); // Set to last day of previous month (current month)
-    d.get_date() as u32
-}
-
-#[cfg(not(target_arch = "wasm32"))]
-pub fn get_days_in_month(year: i32, month: u32) -> u32 {
-    use chrono::{Datelike, NaiveDate, Duration};
     // Get last day of month by going to first day of next month and subtracting 1 day
     let next_month = if month == 12 { 1 } else { month + 1 };
     let next_year = if month == 12 { year + 1 } else { year };
-    if let Some(first_of_next) = NaiveDate::from_ymd_opt(next_year, next_month, 1) {
-        (first_of_next - Duration::days(1)).day()
-    } else {
-        31 // Fallback
-    }
+    NaiveDate::from_ymd_opt(next_year, next_month, 1)
+        .and_then(|first_of_next| first_of_next.pred_opt())
+        .map_or(31, |last| last.day())
 }
 
 /// Calendar helper: Get day of week for the first day of a month (0 = Sunday, 6 = Saturday)
-#[cfg(target_arch = "wasm32")]
 pub fn get_first_day_of_week(year: i32, month: u32) -> u32 {
-    let d = js_sys::Date::new(&format!("{}-{:02}-01", year, month).into());
-    d.get_day() as u32
-}
-
-#[cfg(not(target_arch = "wasm32"))]
-pub fn get_first_day_of_week(year: i32, month: u32) -> u32 {
-    use chrono::{Datelike, NaiveDate};
-    if let Some(date) = NaiveDate::from_ymd_opt(year, month, 1) {
-        date.weekday().num_days_from_sunday() as u32
-    } else {
-        0 // Fallback to Sunday
-    }
+    NaiveDate::from_ymd_opt(year, month, 1)
+        .map_or(0, |date| date.weekday().num_days_from_sunday())
 }
 
 /// Format month and year for display (e.g., "January 2024")
-#[cfg(target_arch = "wasm32")]
 pub fn format_month_year(year: i32, month: u32) -> String {
     let month_names = [
         "January", "February", "March", "April", "May", "June",
@@ -383,16 +465,228 @@ pub fn format_month_year(year: i32, month: u32) -> String {
     }
 }
 
-#[cfg(not(target_arch = "wasm32"))]
-pub fn format_month_year(year: i32, month: u32) -> String {
-    let month_names = [
-        "January", "February", "March", "April", "May", "June",
-        "July", "August", "September", "October", "November", "December"
-    ];
-    if month >= 1 && month <= 12 {
-        format!("{} {}", month_names[(month - 1) as usize], year)
-    } else {
-        format!("Month {} {}", month, year)
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::datetime::LocalDateTime;
+    use crate::models::{Priority, Recurrence, RecurrenceFrequency};
+    use crate::test_support::{datetime, reminder, tag};
+
+    fn recurring(due: chrono::NaiveDateTime, time_zone: Option<chrono_tz::Tz>, frequency: RecurrenceFrequency) -> Reminder {
+        Reminder {
+            due_date: Some(LocalDateTime::floating(due)),
+            time_zone,
+            recurrence: Some(Recurrence::new(frequency)),
+            ..reminder("r", "Recurring")
+        }
+    }
+
+    fn sorted_keys(grouped: &std::collections::HashMap<String, Vec<Reminder>>) -> Vec<&str> {
+        let mut keys: Vec<&str> = grouped.keys().map(String::as_str).collect();
+        keys.sort();
+        keys
+    }
+
+    #[test]
+    fn occurrence_on_the_next_month_in_its_zone_lands_in_this_month_on_the_device() {
+        // 00:30 in Paris on the 1st is 23:30 UTC on the last day of the month before
+        let r = recurring(datetime(2024, 1, 1, 0, 30), Some(chrono_tz::Europe::Paris), RecurrenceFrequency::Monthly);
+        let grouped = group_reminders_by_date_in(&[r], 2024, 2, &chrono_tz::UTC);
+
+        assert_eq!(sorted_keys(&grouped), ["2024-02-29"]);
+        let occurrence = &grouped["2024-02-29"][0];
+        assert_eq!(occurrence.due_date.unwrap().naive(), datetime(2024, 3, 1, 0, 30));
+    }
+
+    #[test]
+    fn daily_occurrences_shift_a_day_across_zones() {
+        // 23:00 in New York is 13:00 the next day in Tokyo
+        let r = recurring(datetime(2023, 12, 1, 23, 0), Some(chrono_tz::America::New_York), RecurrenceFrequency::Daily);
+        let grouped = group_reminders_by_date_in(&[r], 2024, 1, &chrono_tz::Asia::Tokyo);
+
+        assert_eq!(grouped.len(), 31);
+        assert!(grouped.values().all(|day| day.len() == 1));
+        assert_eq!(grouped["2024-01-01"][0].due_date.unwrap().naive(), datetime(2023, 12, 31, 23, 0));
+        assert_eq!(grouped["2024-01-31"][0].due_date.unwrap().naive(), datetime(2024, 1, 30, 23, 0));
+    }
+
+    #[test]
+    fn occurrences_leaving_the_month_on_the_device_are_dropped() {
+        // 00:30 in Tokyo on the 1st is still the previous month in UTC
+        let r = recurring(datetime(2024, 1, 1, 0, 30), Some(chrono_tz::Asia::Tokyo), RecurrenceFrequency::Monthly);
+        let grouped = group_reminders_by_date_in(&[r], 2024, 3, &chrono_tz::UTC);
+
+        assert_eq!(sorted_keys(&grouped), ["2024-03-31"]);
+        assert_eq!(grouped["2024-03-31"][0].due_date.unwrap().naive(), datetime(2024, 4, 1, 0, 30));
+    }
+
+    #[test]
+    fn floating_reminders_keep_their_wall_clock_day() {
+        let weekly = recurring(datetime(2024, 2, 5, 23, 30), None, RecurrenceFrequency::Weekly);
+        let one_off = Reminder {
+            due_date: Some(LocalDateTime::floating(datetime(2024, 2, 14, 9, 0))),
+            ..reminder("o", "One-off")
+        };
+        let grouped = group_reminders_by_date_in(&[weekly, one_off], 2024, 2, &chrono_tz::Asia::Tokyo);
+
+        assert_eq!(sorted_keys(&grouped), ["2024-02-05", "2024-02-12", "2024-02-14", "2024-02-19", "2024-02-26"]);
+    }
+
+    #[test]
+    fn completed_recurring_reminders_stay_on_their_due_date() {
+        let mut r = recurring(datetime(2024, 2, 5, 9, 0), None, RecurrenceFrequency::Daily);
+        r.completed = true;
+        let grouped = group_reminders_by_date_in(&[r], 2024, 2, &chrono_tz::UTC);
+
+        assert_eq!(sorted_keys(&grouped), ["2024-02-05"]);
+    }
+
+    fn prioritized(id: &str, due: Option<chrono::NaiveDateTime>, priority: Priority) -> Reminder {
+        Reminder {
+            due_date: due.map(LocalDateTime::floating),
+            priority,
+            ..reminder(id, id)
+        }
     }
-}
 
+    fn sorted_ids(reminders: &[Reminder], priority_filter: PriorityFilter, sort: ReminderSort) -> Vec<String> {
+        get_filtered_and_sorted_reminders(reminders, &[], &ReminderFilter::All, &priority_filter, "", &sort)
+            .into_iter()
+            .map(|r| r.id)
+            .collect()
+    }
+
+    #[test]
+    fn smart_sort_puts_overdue_first_then_priority_then_due_date() {
+        let mut snoozed = prioritized("snoozed_medium", Some(datetime(2000, 1, 1, 9, 0)), Priority::Medium);
+        snoozed.snoozed_until = Some(Timestamp::from_millis(i64::MAX / 2));
+        let mut completed = prioritized("completed_urgent", Some(datetime(2000, 1, 1, 9, 0)), Priority::Urgent);
+        completed.completed = true;
+        let reminders = [
+            prioritized("future_none", Some(datetime(2100, 1, 1, 9, 0)), Priority::None),
+            prioritized("overdue_low", Some(datetime(2000, 1, 1, 9, 0)), Priority::Low),
+            prioritized("undated_high", None, Priority::High),
+            prioritized("future_urgent_late", Some(datetime(2100, 1, 2, 9, 0)), Priority::Urgent),
+            prioritized("overdue_high", Some(datetime(2000, 1, 2, 9, 0)), Priority::High),
+            prioritized("future_urgent_early", Some(datetime(2100, 1, 1, 9, 0)), Priority::Urgent),
+            snoozed,
+            completed,
+        ];
+
+        assert_eq!(
+            sorted_ids(&reminders, PriorityFilter::All, ReminderSort::Smart),
+            [
+                "overdue_high",
+                "overdue_low",
+                "completed_urgent",
+                "future_urgent_early",
+                "future_urgent_late",
+                "undated_high",
+                "snoozed_medium",
+                "future_none",
+            ]
+        );
+        assert_eq!(
+            sorted_ids(&reminders, PriorityFilter::All, ReminderSort::Priority),
+            [
+                "completed_urgent",
+                "future_urgent_early",
+                "future_urgent_late",
+                "overdue_high",
+                "undated_high",
+                "snoozed_medium",
+                "overdue_low",
+                "future_none",
+            ]
+        );
+    }
+
+    #[test]
+    fn priority_filter_keeps_only_that_level() {
+        let reminders: Vec<Reminder> = Priority::ALL
+            .into_iter()
+            .map(|priority| prioritized(priority.as_str(), None, priority))
+            .collect();
+        for priority in Priority::ALL {
+            assert_eq!(
+                sorted_ids(&reminders, PriorityFilter::Only(priority), ReminderSort::Smart),
+                [priority.as_str()]
+            );
+        }
+        assert_eq!(sorted_ids(&reminders, PriorityFilter::All, ReminderSort::Title).len(), 5);
+    }
+
+    fn chars(text: &str) -> Vec<char> {
+        text.chars().collect()
+    }
+
+    fn word(text: &str) -> SearchWord<'_> {
+        SearchWord { text, exact: false }
+    }
+
+    #[test]
+    fn edit_distance_counts_adjacent_swaps_as_one_typo() {
+        assert_eq!(edit_distance(&chars("dentsit"), &chars("dentist")), 1);
+        assert_eq!(edit_distance(&chars("kitten"), &chars("sitting")), 3);
+        assert_eq!(edit_distance(&chars(""), &chars("abc")), 3);
+        assert_eq!(edit_distance(&chars("same"), &chars("same")), 0);
+    }
+
+    #[test]
+    fn exact_matches_outscore_prefixes_substrings_and_typos() {
+        let text = "Call the Dentist";
+        let scored = |needle: &str, exact: bool| match_text(needle, text, exact).map(|m| (m.score, m.range));
+        assert_eq!(scored("dentist", false), Some((MATCH_EXACT, 9..16)));
+        assert_eq!(scored("DENT", false), Some((MATCH_PREFIX, 9..13)));
+        assert_eq!(scored("entis", false), Some((MATCH_SUBSTRING, 10..15)));
+        assert_eq!(scored("dentsit", false), Some((MATCH_TYPO - MATCH_TYPO_PENALTY, 9..16)));
+        // Typos at the end of a word still match its start
+        assert_eq!(scored("dentsi", false), Some((MATCH_TYPO - MATCH_TYPO_PENALTY, 9..15)));
+        assert_eq!(scored("dentsit", true), None);
+        assert_eq!(scored("the dentist", true), Some((MATCH_EXACT, 5..16)));
+        // Short words must be spelt right
+        assert_eq!(scored("cak", false), None);
+        assert_eq!(scored("  ", false), None);
+        assert_eq!(fuzzy_match("dentsit", text), match_text("dentsit", text, false));
+    }
+
+    #[test]
+    fn highlight_ranges_are_byte_ranges() {
+        let text = "Café Über";
+        let matched = match_text("über", text, false).unwrap();
+        assert_eq!(matched.range, 6..11);
+        assert_eq!(&text[matched.range], "Über");
+        let typo = match_text("cafe", text, false).unwrap();
+        assert_eq!(&text[typo.range], "Café");
+    }
+
+    #[test]
+    fn reminders_match_every_word_in_title_tags_description_or_checklist() {
+        let tags = [tag("health", "Health"), tag("work", "Work")];
+        let mut r = reminder("a", "Call the dentist");
+        r.description = "Ask about the filling".to_string();
+        r.tag_ids = vec!["health".to_string()];
+        r.checklist = vec![crate::checklist::new_item(&[], "Find insurance card")];
+
+        let found = fuzzy_match_reminder(&r, &tags, &[word("dentsit")]).unwrap();
+        assert_eq!(found.score, (MATCH_TYPO - MATCH_TYPO_PENALTY) * 3);
+        assert_eq!(found.title, vec![9..16]);
+        assert!(found.description.is_empty());
+
+        let found = fuzzy_match_reminder(&r, &tags, &[word("health")]).unwrap();
+        assert_eq!(found.score, MATCH_EXACT * 2);
+        assert_eq!(found.tag_ids, ["health"]);
+        assert!(found.title.is_empty());
+
+        let found = fuzzy_match_reminder(&r, &tags, &[word("call"), word("filling"), word("insurance")]).unwrap();
+        assert_eq!(found.score, MATCH_EXACT * 3 + MATCH_EXACT + MATCH_EXACT);
+        assert_eq!(found.title, vec![0..4]);
+        assert_eq!(found.description, vec![14..21]);
+
+        // Tags the reminder doesn't have don't count, and every word must match
+        assert_eq!(fuzzy_match_reminder(&r, &tags, &[word("work")]), None);
+        assert_eq!(fuzzy_match_reminder(&r, &tags, &[word("call"), word("plumber")]), None);
+        let phrase = SearchWord { text: "dentsit", exact: true };
+        assert_eq!(fuzzy_match_reminder(&r, &tags, &[phrase]), None);
+    }
+}
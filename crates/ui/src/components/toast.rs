@@ -71,6 +71,13 @@ pub struct ToastProps {
     
     /// Close event handler
     pub on_close: Option<EventHandler<()>>,
+    
+    /// Label of an optional action button (e.g. "Export")
+    #[props(default)]
+    pub action_label: String,
+    
+    /// Action button click handler
+    pub on_action: Option<EventHandler<()>>,
 }
 
 /// Toast component
@@ -157,6 +164,17 @@ pub fn Toast(props: ToastProps) -> Element {
                             class: "text-sm",
                             "{props.message}"
                         }
+                        if !props.action_label.is_empty() {
+                            button {
+                                class: "mt-2 text-sm font-semibold underline",
+                                onclick: move |_| {
+                                    if let Some(handler) = props.on_action.as_ref() {
+                                        handler.call(());
+                                    }
+                                },
+                                "{props.action_label}"
+                            }
+                        }
                     }
                     
                     if props.show_close {
@@ -8,7 +8,9 @@ use dioxus::dioxus_core::use_hook_with_cleanup;
 use std::rc::Rc;
 use remind_me_shared::router::Route;
 use remind_me_components::{LandingPage, ReminderApp, PrivacyPolicyPage, TermsOfUsePage, MediaCacheProvider};
+use remind_me_components::storage::StorageProvider;
 use crate::router::{get_initial_route, update_url};
+use crate::storage::open_web_storage;
 use crate::i18n::{use_init_i18n, use_i18n};
 use remind_me_shared::i18n::Locale;
 use crate::deployment::{get_base_path, get_base_url};
@@ -108,6 +110,9 @@ pub fn App() -> Element {
     // Provide the i18n context for the entire app
     use_init_i18n();
 
+    // Open IndexedDB (or the localStorage fallback); the app view waits for it
+    let storage = use_resource(|| async { CopyValue::new(open_web_storage().await) });
+
     // Get access to i18n context
     let mut i18n = use_i18n();
 
@@ -1008,7 +1013,12 @@ pub fn App() -> Element {
                             }
                         }
                     },
-                    Route::App => rsx! { ReminderApp {} },
+                    Route::App => match storage() {
+                        Some(storage) => rsx! {
+                            StorageProvider { storage, ReminderApp {} }
+                        },
+                        None => rsx! {},
+                    },
                     Route::PrivacyPolicy => rsx! {
                         PrivacyPolicyPage {
                             on_enter_app: move |_| {
@@ -3,14 +3,18 @@
 //! This crate contains platform-specific implementations for web/WASM builds.
 
 pub mod storage;
+pub mod sync;
 pub mod i18n;
 pub mod router;
 pub mod deployment;
 pub mod app;
 pub mod services;
 
-// Re-export storage functions for convenience
-pub use storage::{load_reminders, save_reminders, load_tags, save_tags};
+// Re-export the storage backend for convenience
+pub use storage::{open_web_storage, IndexedDbStorage, WebStorage};
+
+// Re-export the sync transport for convenience
+pub use sync::FetchTransport;
 
 // Re-export i18n hooks for convenience
 pub use i18n::{use_init_i18n, use_i18n, use_t, use_set_locale, use_current_locale, I18nContext};
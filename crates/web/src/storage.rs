@@ -1,37 +1,491 @@
-//! Web storage implementation using localStorage
-//! 
-//! This module provides the WebStorage implementation of PlatformStorage
-//! for web/WASM builds.
+//! Web storage backends
+//!
+//! `IndexedDbStorage` is the primary backend for web/WASM builds: reminders
+//! and tags are kept as one record per item, so a change only writes the
+//! items that changed. `WebStorage` (localStorage) is the fallback when
+//! IndexedDB cannot be opened. Use `open_web_storage` to get whichever is
+//! available.
 
-use remind_me_shared::storage::{PlatformStorage, StorageError};
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+
+use js_sys::{Array, Promise};
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{IdbDatabase, IdbObjectStore, IdbRequest, IdbTransaction, IdbTransactionMode};
+
+use remind_me_shared::storage::{
+    ListChanges, MemoryStorage, PlatformStorage, StorageError, StorageHandle, REMINDERS_V2_KEY, TAGS_V1_KEY,
+};
 
 /// Web storage implementation using browser localStorage
 pub struct WebStorage;
 
+impl WebStorage {
+    fn local_storage() -> Result<web_sys::Storage, StorageError> {
+        web_sys::window()
+            .and_then(|window| window.local_storage().ok().flatten())
+            .ok_or(StorageError::Unavailable)
+    }
+}
+
 impl PlatformStorage for WebStorage {
-    fn get(key: &str) -> Option<String> {
-        if let Some(window) = web_sys::window() {
-            if let Some(storage) = window.local_storage().ok().flatten() {
-                if let Ok(Some(data)) = storage.get_item(key) {
-                    return Some(data);
+    fn get(&self, key: &str) -> Result<Option<String>, StorageError> {
+        Self::local_storage()?.get_item(key).map_err(|_| StorageError::ReadFailed)
+    }
+
+    fn set(&self, key: &str, value: &str) -> Result<(), StorageError> {
+        Self::local_storage()?.set_item(key, value).map_err(|_| StorageError::SaveFailed)
+    }
+
+    fn remove(&self, key: &str) -> Result<(), StorageError> {
+        Self::local_storage()?.remove_item(key).map_err(|_| StorageError::SaveFailed)
+    }
+
+    fn keys(&self) -> Result<Vec<String>, StorageError> {
+        let storage = Self::local_storage()?;
+        let len = storage.length().map_err(|_| StorageError::ReadFailed)?;
+        (0..len)
+            .map(|index| storage.key(index).map_err(|_| StorageError::ReadFailed))
+            .filter_map(Result::transpose)
+            .collect()
+    }
+
+    fn clear(&self) -> Result<(), StorageError> {
+        Self::local_storage()?.clear().map_err(|_| StorageError::SaveFailed)
+    }
+}
+
+const DB_NAME: &str = "remind_me";
+const DB_VERSION: u32 = 1;
+
+/// Object store for keys kept as a single value (schema version, saved views, ...)
+const VALUES_STORE: &str = "values";
+/// Object store for bookkeeping: record order and the migration marker
+const META_STORE: &str = "meta";
+/// Keys whose JSON arrays are split into one record per item, with their object store
+const RECORD_STORES: [(&str, &str); 2] = [(REMINDERS_V2_KEY, "reminders"), (TAGS_V1_KEY, "tags")];
+const ALL_STORES: [&str; 4] = [VALUES_STORE, META_STORE, "reminders", "tags"];
+
+/// Meta key set once the localStorage data has been copied into IndexedDB
+const LOCAL_STORAGE_MIGRATED_KEY: &str = "local_storage_migrated";
+
+/// Records currently stored for a split key
+#[derive(Default)]
+struct RecordList {
+    order: Vec<String>,
+    items: HashMap<String, String>,
+}
+
+/// Listener for writes failing in the background
+type ErrorListener = Rc<RefCell<Option<Box<dyn Fn(StorageError)>>>>;
+
+/// Storage backend using IndexedDB
+///
+/// Everything is loaded into memory when the database is opened, so reads
+/// stay synchronous. Writes update memory immediately and are queued as
+/// IndexedDB transactions, which the browser runs in order. A transaction
+/// that fails is reported to the listener set with `set_error_listener` as
+/// soon as it aborts.
+pub struct IndexedDbStorage {
+    db: IdbDatabase,
+    cache: MemoryStorage,
+    records: RefCell<HashMap<String, RecordList>>,
+    write_failed: Rc<Cell<bool>>,
+    error_listener: ErrorListener,
+    on_abort: Closure<dyn FnMut()>,
+}
+
+impl IndexedDbStorage {
+    /// Open (or create) the database and load its contents
+    ///
+    /// On first run, data found in localStorage is copied over. localStorage
+    /// itself is left untouched so it is still there for the fallback.
+    pub async fn open() -> Result<Self, StorageError> {
+        let factory = web_sys::window()
+            .and_then(|window| window.indexed_db().ok().flatten())
+            .ok_or(StorageError::Unavailable)?;
+        let request = factory
+            .open_with_u32(DB_NAME, DB_VERSION)
+            .map_err(|_| StorageError::Unavailable)?;
+
+        let upgrade_request = request.clone();
+        let on_upgrade = Closure::once_into_js(move |_: web_sys::IdbVersionChangeEvent| {
+            if let Ok(db) = upgrade_request.result().and_then(|result| result.dyn_into::<IdbDatabase>()) {
+                let existing = db.object_store_names();
+                for store in ALL_STORES {
+                    if !existing.contains(store) {
+                        let _ = db.create_object_store(store);
+                    }
                 }
             }
+        });
+        request.set_onupgradeneeded(Some(on_upgrade.unchecked_ref()));
+
+        let db = request_result(&request)
+            .await
+            .and_then(|result| result.dyn_into::<IdbDatabase>().map_err(|_| StorageError::Unavailable))?;
+
+        let write_failed = Rc::new(Cell::new(false));
+        let error_listener: ErrorListener = Rc::new(RefCell::new(None));
+        let (failed, listener) = (write_failed.clone(), error_listener.clone());
+        let storage = Self {
+            db,
+            cache: MemoryStorage::new(),
+            records: RefCell::new(HashMap::new()),
+            write_failed,
+            error_listener,
+            on_abort: Closure::new(move || {
+                failed.set(true);
+                if let Some(listener) = listener.borrow().as_ref() {
+                    listener(StorageError::SaveFailed);
+                }
+            }),
+        };
+
+        let migrated = storage.load().await?;
+        if !migrated {
+            storage.migrate_local_storage().await?;
+        }
+        Ok(storage)
+    }
+
+    /// Load every store into memory; returns whether localStorage was already migrated
+    async fn load(&self) -> Result<bool, StorageError> {
+        let tx = self
+            .transaction(&ALL_STORES, IdbTransactionMode::Readonly)
+            .map_err(|_| StorageError::ReadFailed)?;
+
+        // Issue every request before awaiting so the transaction stays active
+        let values = read_all(&tx, VALUES_STORE)?;
+        let meta = read_all(&tx, META_STORE)?;
+        let mut lists = Vec::new();
+        for (key, store) in RECORD_STORES {
+            lists.push((key, read_all(&tx, store)?));
+        }
+
+        let values = entries(values).await?;
+        let meta: HashMap<String, String> = entries(meta).await?.into_iter().collect();
+
+        for (key, value) in &values {
+            self.cache.set(key, value)?;
+        }
+
+        for (key, requests) in lists {
+            let stored = entries(requests).await?;
+            let order: Vec<String> = meta
+                .get(key)
+                .and_then(|order| serde_json::from_str(order).ok())
+                .unwrap_or_default();
+            let items: HashMap<String, String> = stored.iter().cloned().collect();
+
+            // Ids in their saved order, then any records the order doesn't list
+            let mut seen = HashSet::new();
+            let ids: Vec<String> = order
+                .into_iter()
+                .filter(|id| items.contains_key(id))
+                .chain(stored.into_iter().map(|(id, _)| id))
+                .filter(|id| seen.insert(id.clone()))
+                .collect();
+
+            if !ids.is_empty() && self.cache.get(key)?.is_none() {
+                let json: Vec<&str> = ids.iter().map(|id| items[id].as_str()).collect();
+                self.cache.set(key, &format!("[{}]", json.join(",")))?;
+            }
+            self.records
+                .borrow_mut()
+                .insert(key.to_string(), RecordList { order: ids, items });
+        }
+
+        Ok(meta.contains_key(LOCAL_STORAGE_MIGRATED_KEY))
+    }
+
+    /// Copy keys from localStorage that aren't in IndexedDB yet
+    async fn migrate_local_storage(&self) -> Result<(), StorageError> {
+        let local = WebStorage;
+        for key in local.keys().unwrap_or_default() {
+            if self.cache.get(&key)?.is_some() {
+                continue;
+            }
+            if let Some(value) = local.get(&key)? {
+                self.set(&key, &value)?;
+            }
+        }
+
+        // Only mark the migration done once every copy has been committed
+        self.flush().await?;
+        self.write(&[META_STORE], |tx| {
+            tx.object_store(META_STORE)?
+                .put_with_key(&JsValue::TRUE, &JsValue::from_str(LOCAL_STORAGE_MIGRATED_KEY))?;
+            Ok(())
+        })
+    }
+
+    /// Wait until every queued write has finished
+    async fn flush(&self) -> Result<(), StorageError> {
+        let request = self
+            .transaction(&ALL_STORES, IdbTransactionMode::Readonly)
+            .and_then(|tx| tx.object_store(META_STORE)?.count())
+            .map_err(|_| StorageError::ReadFailed)?;
+        request_result(&request).await?;
+        self.take_write_error()
+    }
+
+    fn transaction(&self, stores: &[&str], mode: IdbTransactionMode) -> Result<IdbTransaction, JsValue> {
+        let names: Array = stores.iter().map(|store| JsValue::from_str(store)).collect();
+        let tx = self.db.transaction_with_str_sequence_and_mode(&names, mode)?;
+        tx.set_onabort(Some(self.on_abort.as_ref().unchecked_ref()));
+        Ok(tx)
+    }
+
+    /// Queue a read-write transaction
+    ///
+    /// Errors are those of queuing it; if it fails later, its abort handler
+    /// reports that.
+    fn write(
+        &self,
+        stores: &[&str],
+        queue: impl FnOnce(&IdbTransaction) -> Result<(), JsValue>,
+    ) -> Result<(), StorageError> {
+        self.transaction(stores, IdbTransactionMode::Readwrite)
+            .and_then(|tx| queue(&tx))
+            .map_err(|_| StorageError::SaveFailed)
+    }
+
+    /// Whether a write failed since the last call (for `flush`)
+    fn take_write_error(&self) -> Result<(), StorageError> {
+        if self.write_failed.replace(false) {
+            Err(StorageError::SaveFailed)
+        } else {
+            Ok(())
         }
-        None
     }
-    
-    fn set(key: &str, value: &str) -> Result<(), StorageError> {
-        if let Some(window) = web_sys::window() {
-            if let Some(storage) = window.local_storage().ok().flatten() {
-                if let Err(_) = storage.set_item(key, value) {
-                    return Err(StorageError::SaveFailed);
+
+    /// Write a split key: only changed records, removed records and a changed order
+    fn set_records(&self, key: &str, store: &str, value: &str) -> Result<(), StorageError> {
+        let mut records = self.records.borrow_mut();
+        let previous = records.entry(key.to_string()).or_default();
+
+        let Some(items) = split_records(value) else {
+            // Not a list of records: keep it whole so lenient loading can still see it
+            self.write(&[VALUES_STORE, META_STORE, store], |tx| {
+                tx.object_store(VALUES_STORE)?
+                    .put_with_key(&JsValue::from_str(value), &JsValue::from_str(key))?;
+                tx.object_store(META_STORE)?.delete(&JsValue::from_str(key))?;
+                tx.object_store(store)?.clear()?;
+                Ok(())
+            })?;
+            *previous = RecordList::default();
+            return Ok(());
+        };
+
+        let order: Vec<String> = items.iter().map(|(id, _)| id.clone()).collect();
+        let items: HashMap<String, String> = items.into_iter().collect();
+
+        self.write(&[VALUES_STORE, META_STORE, store], |tx| {
+            let record_store = tx.object_store(store)?;
+            for (id, json) in &items {
+                if previous.items.get(id) != Some(json) {
+                    record_store.put_with_key(&JsValue::from_str(json), &JsValue::from_str(id))?;
                 }
-                return Ok(());
+            }
+            for id in previous.items.keys().filter(|id| !items.contains_key(*id)) {
+                record_store.delete(&JsValue::from_str(id))?;
+            }
+            if previous.order != order {
+                let order_json = serde_json::to_string(&order).unwrap_or_default();
+                tx.object_store(META_STORE)?
+                    .put_with_key(&JsValue::from_str(&order_json), &JsValue::from_str(key))?;
+            }
+            tx.object_store(VALUES_STORE)?.delete(&JsValue::from_str(key))?;
+            Ok(())
+        })?;
+        *previous = RecordList { order, items };
+        Ok(())
+    }
+
+    /// Write the records in `changes` to a split key already stored as records
+    ///
+    /// Returns `false`, writing nothing, if the key isn't stored as records yet.
+    fn write_record_changes(&self, key: &str, store: &str, changes: &ListChanges) -> Result<bool, StorageError> {
+        let mut records = self.records.borrow_mut();
+        let Some(previous) = records.get_mut(key).filter(|list| !list.order.is_empty()) else {
+            return Ok(false);
+        };
+
+        let mut order: Vec<String> = previous
+            .order
+            .iter()
+            .filter(|id| !changes.removed.contains(id))
+            .cloned()
+            .collect();
+        for (id, _) in &changes.upserted {
+            if !previous.items.contains_key(id) {
+                order.push(id.clone());
             }
         }
-        Err(StorageError::Unavailable)
+
+        self.write(&[META_STORE, store], |tx| {
+            let record_store = tx.object_store(store)?;
+            for (id, json) in &changes.upserted {
+                record_store.put_with_key(&JsValue::from_str(json), &JsValue::from_str(id))?;
+            }
+            for id in &changes.removed {
+                record_store.delete(&JsValue::from_str(id))?;
+            }
+            if previous.order != order {
+                let order_json = serde_json::to_string(&order).unwrap_or_default();
+                tx.object_store(META_STORE)?
+                    .put_with_key(&JsValue::from_str(&order_json), &JsValue::from_str(key))?;
+            }
+            Ok(())
+        })?;
+
+        for id in &changes.removed {
+            previous.items.remove(id);
+        }
+        for (id, json) in &changes.upserted {
+            previous.items.insert(id.clone(), json.clone());
+        }
+        previous.order = order;
+        Ok(true)
     }
 }
 
-// Re-export storage functions from shared (which already has platform-specific implementations)
-pub use remind_me_shared::storage::{load_reminders, save_reminders, load_tags, save_tags};
+impl PlatformStorage for IndexedDbStorage {
+    fn get(&self, key: &str) -> Result<Option<String>, StorageError> {
+        self.cache.get(key)
+    }
+
+    fn set(&self, key: &str, value: &str) -> Result<(), StorageError> {
+        self.cache.set(key, value)?;
+        match record_store(key) {
+            Some(store) => self.set_records(key, store, value),
+            None => self.write(&[VALUES_STORE], |tx| {
+                tx.object_store(VALUES_STORE)?
+                    .put_with_key(&JsValue::from_str(value), &JsValue::from_str(key))?;
+                Ok(())
+            }),
+        }
+    }
+
+    fn remove(&self, key: &str) -> Result<(), StorageError> {
+        self.cache.remove(key)?;
+        match record_store(key) {
+            Some(store) => {
+                self.records.borrow_mut().remove(key);
+                self.write(&[VALUES_STORE, META_STORE, store], |tx| {
+                    tx.object_store(VALUES_STORE)?.delete(&JsValue::from_str(key))?;
+                    tx.object_store(META_STORE)?.delete(&JsValue::from_str(key))?;
+                    tx.object_store(store)?.clear()?;
+                    Ok(())
+                })
+            }
+            None => self.write(&[VALUES_STORE], |tx| {
+                tx.object_store(VALUES_STORE)?.delete(&JsValue::from_str(key))?;
+                Ok(())
+            }),
+        }
+    }
+
+    fn keys(&self) -> Result<Vec<String>, StorageError> {
+        self.cache.keys()
+    }
+
+    /// Write only the changed records, without re-reading the whole list
+    fn set_list(&self, key: &str, value: &str, changes: &ListChanges) -> Result<(), StorageError> {
+        let Some(store) = record_store(key) else {
+            return self.set(key, value);
+        };
+        if self.write_record_changes(key, store, changes)? {
+            self.cache.set(key, value)
+        } else {
+            self.set(key, value)
+        }
+    }
+
+    fn set_error_listener(&self, listener: Box<dyn Fn(StorageError)>) {
+        *self.error_listener.borrow_mut() = Some(listener);
+    }
+
+    fn clear(&self) -> Result<(), StorageError> {
+        self.cache.clear()?;
+        self.records.borrow_mut().clear();
+        self.write(&ALL_STORES, |tx| {
+            for store in ALL_STORES {
+                tx.object_store(store)?.clear()?;
+            }
+            // Cleared on purpose: don't copy localStorage in again on the next run
+            tx.object_store(META_STORE)?
+                .put_with_key(&JsValue::TRUE, &JsValue::from_str(LOCAL_STORAGE_MIGRATED_KEY))?;
+            Ok(())
+        })
+    }
+}
+
+fn record_store(key: &str) -> Option<&'static str> {
+    RECORD_STORES
+        .iter()
+        .find(|(list_key, _)| *list_key == key)
+        .map(|(_, store)| *store)
+}
+
+/// Split a JSON array into `(id, item JSON)` pairs; `None` unless every item has a unique string id
+fn split_records(value: &str) -> Option<Vec<(String, String)>> {
+    let items: Vec<serde_json::Value> = serde_json::from_str(value).ok()?;
+    let mut ids = HashSet::new();
+    items
+        .iter()
+        .map(|item| {
+            let id = item.get("id")?.as_str()?.to_string();
+            ids.insert(id.clone()).then(|| (id, item.to_string()))
+        })
+        .collect()
+}
+
+/// Requests for every key and value of a store
+fn read_all(tx: &IdbTransaction, store: &str) -> Result<(IdbRequest, IdbRequest), StorageError> {
+    let store: IdbObjectStore = tx.object_store(store).map_err(|_| StorageError::ReadFailed)?;
+    let keys = store.get_all_keys().map_err(|_| StorageError::ReadFailed)?;
+    let values = store.get_all().map_err(|_| StorageError::ReadFailed)?;
+    Ok((keys, values))
+}
+
+/// String entries returned by `read_all`, in key order
+async fn entries((keys, values): (IdbRequest, IdbRequest)) -> Result<Vec<(String, String)>, StorageError> {
+    let keys = Array::from(&request_result(&keys).await?);
+    let values = Array::from(&request_result(&values).await?);
+    Ok(keys
+        .iter()
+        .zip(values.iter())
+        .filter_map(|(key, value)| Some((key.as_string()?, value.as_string()?)))
+        .collect())
+}
+
+/// Wait for an IndexedDB request to finish
+async fn request_result(request: &IdbRequest) -> Result<JsValue, StorageError> {
+    let promise = Promise::new(&mut |resolve, reject| {
+        let finished = request.clone();
+        let on_success = Closure::once_into_js(move || {
+            let _ = resolve.call1(&JsValue::NULL, &finished.result().unwrap_or(JsValue::UNDEFINED));
+        });
+        let on_error = Closure::once_into_js(move || {
+            let _ = reject.call0(&JsValue::NULL);
+        });
+        request.set_onsuccess(Some(on_success.unchecked_ref()));
+        request.set_onerror(Some(on_error.unchecked_ref()));
+    });
+    JsFuture::from(promise).await.map_err(|_| StorageError::ReadFailed)
+}
+
+/// Open the storage to provide to components (see `remind_me_components::storage`)
+///
+/// IndexedDB when it is available, localStorage otherwise.
+pub async fn open_web_storage() -> StorageHandle {
+    match IndexedDbStorage::open().await {
+        Ok(storage) => Rc::new(storage),
+        Err(_) => Rc::new(WebStorage),
+    }
+}
@@ -0,0 +1,54 @@
+//! Sync transport over the browser's `fetch`
+
+use js_sys::Promise;
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{Headers, Request, RequestInit, Response};
+
+use remind_me_shared::sync::{SyncError, SyncFuture, SyncRequest, SyncResponse, SyncTransport};
+
+/// `SyncTransport` for the web build
+///
+/// Unless the app is served from the sync server's own origin, that origin
+/// must be listed in the server's `SYNC_ALLOWED_ORIGINS`; by default the
+/// server sends no CORS headers and the browser blocks the requests.
+pub struct FetchTransport;
+
+impl SyncTransport for FetchTransport {
+    fn send(&self, request: SyncRequest) -> SyncFuture<'_> {
+        Box::pin(async move { fetch(&request).await.map_err(|e| SyncError::Network(describe(&e))) })
+    }
+}
+
+async fn fetch(request: &SyncRequest) -> Result<SyncResponse, JsValue> {
+    let window = web_sys::window().ok_or_else(|| JsValue::from_str("no window"))?;
+
+    let headers = Headers::new()?;
+    headers.set("Authorization", &format!("Bearer {}", request.token))?;
+    headers.set("Accept", "application/json")?;
+    let init = RequestInit::new();
+    init.set_method(request.method.as_str());
+    if let Some(body) = &request.body {
+        headers.set("Content-Type", "application/json")?;
+        init.set_body(&JsValue::from_str(body));
+    }
+    init.set_headers(&headers);
+
+    let fetch_request = Request::new_with_str_and_init(&request.url, &init)?;
+    let response: Response = JsFuture::from(window.fetch_with_request(&fetch_request)).await?.dyn_into()?;
+    let text: Promise = response.text()?;
+    let body = JsFuture::from(text).await?.as_string().unwrap_or_default();
+    Ok(SyncResponse {
+        status: response.status(),
+        body,
+    })
+}
+
+/// Message of a JavaScript error, for `SyncError::Network`
+fn describe(error: &JsValue) -> String {
+    error
+        .dyn_ref::<js_sys::Error>()
+        .map(|e| String::from(e.message()))
+        .or_else(|| error.as_string())
+        .unwrap_or_else(|| "request failed".to_string())
+}
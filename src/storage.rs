@@ -1,3 +1,10 @@
+//! Deprecated: legacy storage for the pre-workspace app in `src/`.
+//!
+//! The workspace crates load and save through `remind_me_shared::storage`
+//! (`Repository` over a `PlatformStorage` backend), which also runs the v1 →
+//! v2 migration this module used to do inline. Don't add callers here; the
+//! module goes away with the rest of `src/`.
+
 use crate::models::{Reminder, Tag};
 use crate::storage_platform::{PlatformStorage, PlatformStorageImpl, StorageError};
 
@@ -1,5 +1,9 @@
-// Platform-agnostic storage abstraction
-// This module provides a unified storage interface for both web (localStorage) and mobile (file system)
+//! Deprecated: platform storage for the pre-workspace app in `src/`.
+//!
+//! Superseded by `remind_me_shared::storage`, whose `PlatformStorage` trait
+//! is implemented by the localStorage, IndexedDB, file, SQLite and in-memory
+//! backends and reports read errors instead of hiding them. Don't add callers
+//! here; the module goes away with the rest of `src/`.
 
 /// Platform storage trait for key-value storage
 pub trait PlatformStorage {